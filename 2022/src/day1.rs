@@ -1,17 +1,33 @@
-#![allow(dead_code)]
+//! Day 1: Calorie Counting
+//!
+//! `parse` totals the calories each elf carries.
 
-pub fn day1_1(input: &str) -> i32 {
+use crate::parse::{OrUnexpected, ParseError};
+#[cfg(feature = "scaling")]
+use rand::Rng;
+
+/// The calories carried by each elf. Lines that aren't a number are skipped,
+/// an elf carrying more than an `i32` of calories is an error
+pub fn parse(input: &str) -> Result<Vec<i32>, ParseError> {
     input
         .split("\n\n")
-        .map(|g| g.lines().filter_map(|s| s.parse::<i32>().ok()).sum::<i32>())
-        .max()
-        .unwrap_or(0)
+        .map(|g| {
+            g.lines()
+                .filter_map(|s| s.parse::<i32>().ok())
+                .try_fold(0i32, |total, calories| total.checked_add(calories))
+                .or_unexpected(g)
+        })
+        .collect()
 }
 
-pub fn day1_2(input: &str) -> i32 {
-    input
-        .split("\n\n")
-        .map(|g| g.lines().filter_map(|s| s.parse::<i32>().ok()).sum::<i32>())
+pub fn part1(elves: &[i32]) -> i32 {
+    elves.iter().copied().max().unwrap_or(0)
+}
+
+pub fn part2(elves: &[i32]) -> i32 {
+    elves
+        .iter()
+        .copied()
         .fold([i32::MIN; 3], |mut big3, n| {
             if n <= big3[0] {
                 return big3;
@@ -23,3 +39,60 @@ pub fn day1_2(input: &str) -> i32 {
         .into_iter()
         .sum()
 }
+
+pub fn day1_1(input: &str) -> Result<i32, ParseError> {
+    parse(input).map(|elves| part1(&elves))
+}
+
+pub fn day1_2(input: &str) -> Result<i32, ParseError> {
+    parse(input).map(|elves| part2(&elves))
+}
+
+/// Generates the snacks of `size` elves, for the scaling benchmark
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        for _ in 0..rng.gen_range(1..=10) {
+            input += &format!("{}\n", rng.gen_range(1000..=60000));
+        }
+        input.push('\n');
+    }
+    input
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day1_1, day1_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000
+";
+
+    example_tests! {
+        test_day1_1: day1_1(INPUT) => Ok(24000),
+        test_day1_2: day1_2(INPUT) => Ok(45000),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[0-9\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,30 +1,34 @@
-#![allow(dead_code)]
+//! Day 10: Cathode-Ray Tube
+//!
+//! `parse` reads the CPU's [`Instruction`]s.
 
+use crate::parse::{OrUnexpected, ParseError};
 use itertools::Itertools;
+#[cfg(feature = "scaling")]
+use rand::Rng;
 
 #[derive(Debug, Copy, Clone)]
-enum Instruction {
+pub enum Instruction {
     Addx(i64),
     Noop,
 }
 
-fn parse_instructions(input: &str) -> Vec<Instruction> {
+/// The CPU's program
+pub fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
     input
         .lines()
         .map(|line| {
             let command = line.split_whitespace().collect_vec();
             match command.as_slice() {
-                ["addx", n] => Instruction::Addx(n.parse().ok().unwrap()),
-                ["noop"] => Instruction::Noop,
-                _ => panic!(),
+                ["addx", n] => Ok(Instruction::Addx(n.parse().or_unexpected(n)?)),
+                ["noop"] => Ok(Instruction::Noop),
+                _ => Err(ParseError::new(line)),
             }
         })
         .collect()
 }
 
-pub fn day10_1(input: &str) -> i64 {
-    let instruction = parse_instructions(input);
-
+pub fn part1(instruction: &[Instruction]) -> i64 {
     let mut pc: usize = 0;
     let mut i_cycle: usize = 0;
     let mut reg_x: i64 = 1;
@@ -55,9 +59,7 @@ pub fn day10_1(input: &str) -> i64 {
     signal_strength
 }
 
-pub fn day10_2(input: &str) -> String {
-    let instruction = parse_instructions(input);
-
+pub fn part2(instruction: &[Instruction]) -> String {
     let mut pc: usize = 0;
     let mut i_cycle: usize = 0;
     let mut reg_x: i64 = 1;
@@ -94,3 +96,207 @@ pub fn day10_2(input: &str) -> String {
         .flat_map(|line| std::iter::once('\n').chain(line.map(|b| if *b { '#' } else { '.' })));
     String::from_iter(screen)
 }
+
+pub fn day10_1(input: &str) -> Result<i64, ParseError> {
+    parse(input).map(|program| part1(&program))
+}
+
+pub fn day10_2(input: &str) -> Result<String, ParseError> {
+    parse(input).map(|program| part2(&program))
+}
+
+/// Generates a program of `size` instructions, for the scaling benchmark. The
+/// additions keep the register on the screen, like the puzzle's
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let mut reg_x = 1;
+    (0..size)
+        .map(|_| {
+            if rng.gen_bool(0.3) {
+                return "noop\n".to_owned();
+            }
+            let mut n = rng.gen_range(-5..=5);
+            if !(0..40).contains(&(reg_x + n)) {
+                n = -n;
+            }
+            reg_x += n;
+            format!("addx {n}\n")
+        })
+        .collect()
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day10_1, day10_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "addx 15
+addx -11
+addx 6
+addx -3
+addx 5
+addx -1
+addx -8
+addx 13
+addx 4
+noop
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx -35
+addx 1
+addx 24
+addx -19
+addx 1
+addx 16
+addx -11
+noop
+noop
+addx 21
+addx -15
+noop
+noop
+addx -3
+addx 9
+addx 1
+addx -3
+addx 8
+addx 1
+addx 5
+noop
+noop
+noop
+noop
+noop
+addx -36
+noop
+addx 1
+addx 7
+noop
+noop
+noop
+addx 2
+addx 6
+noop
+noop
+noop
+noop
+noop
+addx 1
+noop
+noop
+addx 7
+addx 1
+noop
+addx -13
+addx 13
+addx 7
+noop
+addx 1
+addx -33
+noop
+noop
+noop
+addx 2
+noop
+noop
+noop
+addx 8
+noop
+addx -1
+addx 2
+addx 1
+noop
+addx 17
+addx -9
+addx 1
+addx 1
+addx -3
+addx 11
+noop
+noop
+addx 1
+noop
+addx 1
+noop
+noop
+addx -13
+addx -19
+addx 1
+addx 3
+addx 26
+addx -30
+addx 12
+addx -1
+addx 3
+addx 1
+noop
+noop
+noop
+addx -9
+addx 18
+addx 1
+addx 2
+noop
+noop
+addx 9
+noop
+noop
+noop
+addx -1
+addx 2
+addx -37
+addx 1
+addx 3
+noop
+addx 15
+addx -21
+addx 22
+addx -6
+addx 1
+noop
+addx 2
+addx 1
+noop
+addx -10
+noop
+noop
+addx 20
+addx 1
+addx 2
+addx 2
+addx -6
+addx -11
+noop
+noop
+noop
+";
+
+    const IMAGE: &str = "
+##..##..##..##..##..##..##..##..##..##..
+###...###...###...###...###...###...###.
+####....####....####....####....####....
+#####.....#####.....#####.....#####.....
+######......######......######......####
+#######.......#######.......#######.....";
+
+    example_tests! {
+        test_day10_1: day10_1(INPUT) => Ok(13140),
+        test_day10_2: day10_2(INPUT) => Ok(IMAGE.to_string()),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[adxnop 0-9\n-]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
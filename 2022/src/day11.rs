@@ -1,13 +1,19 @@
-#![allow(dead_code)]
+//! Day 11: Monkey in the Middle
+//!
+//! `parse` reads each [`Monkey`] with the items it starts with.
 
 use std::collections::VecDeque;
 
+use crate::parse::{OrUnexpected, ParseError};
 use itertools::Itertools;
+#[cfg(feature = "scaling")]
+use rand::{seq::SliceRandom, Rng};
 
 type Item = u64;
 
-#[derive(Debug)]
-struct Monkey {
+/// A monkey with the items it holds, and how it decides where to throw them
+#[derive(Debug, Clone)]
+pub struct Monkey {
     _name: usize,
     items: VecDeque<Item>,
     worry_divider: Item,
@@ -18,24 +24,31 @@ struct Monkey {
 }
 
 impl Monkey {
-    pub fn from_input(input: &str, worry_divider: Item) -> Monkey {
+    /// Parses a monkey's note. Once inspected, worry levels are divided by
+    /// `worry_divider`
+    pub fn from_input(input: &str, worry_divider: Item) -> Result<Monkey, ParseError> {
         let mut lines = input.lines();
         let _name: usize = {
-            let monkey_name = lines.next().unwrap().chars().filter(|c| c.is_ascii_digit());
-            String::from_iter(monkey_name).parse().unwrap()
+            let monkey_name = lines.next().or_unexpected(input)?;
+            let digits = monkey_name.chars().filter(|c| c.is_ascii_digit());
+            String::from_iter(digits)
+                .parse()
+                .or_unexpected(monkey_name)?
         };
         let items = {
-            let items_line = lines.next().unwrap().split_whitespace().skip(1);
+            let items_line = lines.next().or_unexpected(input)?;
             items_line
+                .split_whitespace()
+                .skip(1)
                 .map(|item| item.chars().filter(|c| c.is_ascii_digit()))
                 .map(|i| String::from_iter(i))
                 .filter(|s| !s.is_empty())
-                .map(|s| s.parse().unwrap())
-                .collect()
+                .map(|s| s.parse().or_unexpected(items_line))
+                .collect::<Result<_, _>>()?
         };
-        let operation = Op::from_input(lines.next().unwrap());
-        let test = Test::from_lines(lines);
-        Self {
+        let operation = Op::from_input(lines.next().or_unexpected(input)?)?;
+        let test = Test::from_lines(lines)?;
+        Ok(Self {
             _name,
             items,
             worry_divider,
@@ -43,8 +56,10 @@ impl Monkey {
             operation,
             test,
             inspections: 0,
-        }
+        })
     }
+    /// Inspects the next item, returning the monkey to throw it to and its
+    /// new worry level, or `None` once it has no items left
     pub fn throw(&mut self) -> Option<(usize, Item)> {
         let item = self.items.pop_front()?;
         self.inspections += 1;
@@ -60,26 +75,26 @@ impl Monkey {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Op {
     Add(Option<Item>, Option<Item>),
     Mul(Option<Item>, Option<Item>),
 }
 
 impl Op {
-    pub fn from_input(input: &str) -> Op {
+    pub fn from_input(input: &str) -> Result<Op, ParseError> {
         let line = input.split_whitespace().collect_vec();
         match line.as_slice() {
             ["Operation:", "new", "=", n1, op, n2] => {
                 let n1 = n1.parse().ok();
                 let n2 = n2.parse().ok();
                 match *op {
-                    "+" => Op::Add(n1, n2),
-                    "*" => Op::Mul(n1, n2),
-                    _ => panic!("what the heck is {op:?}"),
+                    "+" => Ok(Op::Add(n1, n2)),
+                    "*" => Ok(Op::Mul(n1, n2)),
+                    _ => Err(ParseError::new(op)),
                 }
             }
-            _ => panic!("what the heck is {line:?}"),
+            _ => Err(ParseError::new(input)),
         }
     }
     pub fn apply(&self, item: Item, divisors_product: Option<u64>) -> Item {
@@ -95,7 +110,7 @@ impl Op {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Test {
     test: Item,
     case_true: usize,
@@ -103,36 +118,40 @@ struct Test {
 }
 
 impl Test {
-    pub fn from_lines(mut lines: std::str::Lines) -> Test {
+    pub fn from_lines(mut lines: std::str::Lines) -> Result<Test, ParseError> {
         let test = {
-            let test = lines.next().unwrap().split_whitespace().collect_vec();
-            match test.as_slice() {
-                ["Test:", "divisible", "by", n] => n.parse().unwrap(),
-                _ => panic!("what the heck is {test:?}"),
+            let test = lines.next().or_unexpected("a monkey's test")?;
+            match test.split_whitespace().collect_vec().as_slice() {
+                // a monkey testing divisibility by 0 would never throw anything
+                ["Test:", "divisible", "by", n] => n.parse().ok().filter(|&n| n > 0),
+                _ => None,
             }
+            .or_unexpected(test)?
         };
         let case_true = {
-            let case_true = lines.next().unwrap().split_whitespace().collect_vec();
-            match case_true.as_slice() {
-                ["If", "true:", "throw", "to", "monkey", name] => name.parse().unwrap(),
-                _ => panic!("what the heck is {case_true:?}"),
+            let case_true = lines.next().or_unexpected("a monkey's true case")?;
+            match case_true.split_whitespace().collect_vec().as_slice() {
+                ["If", "true:", "throw", "to", "monkey", name] => name.parse().ok(),
+                _ => None,
             }
+            .or_unexpected(case_true)?
         };
         let case_false = {
-            let case_false = lines.next().unwrap().split_whitespace().collect_vec();
-            match case_false.as_slice() {
-                ["If", "false:", "throw", "to", "monkey", name] => name.parse().unwrap(),
-                _ => panic!("what the heck is {case_false:?}"),
+            let case_false = lines.next().or_unexpected("a monkey's false case")?;
+            match case_false.split_whitespace().collect_vec().as_slice() {
+                ["If", "false:", "throw", "to", "monkey", name] => name.parse().ok(),
+                _ => None,
             }
+            .or_unexpected(case_false)?
         };
-        Self {
+        Ok(Self {
             test,
             case_true,
             case_false,
-        }
+        })
     }
     pub fn name_to_throw(&self, item: Item) -> usize {
-        if item % self.test == 0 {
+        if item.is_multiple_of(self.test) {
             self.case_true
         } else {
             self.case_false
@@ -140,15 +159,26 @@ impl Test {
     }
 }
 
-fn parse_input_1(input: &str) -> Vec<Monkey> {
-    input
+/// The monkeys, with the worry level left as is after an inspection
+pub fn parse(input: &str) -> Result<Vec<Monkey>, ParseError> {
+    let monkeys: Vec<_> = input
         .split("\n\n")
-        .map(|i| Monkey::from_input(i, 3))
-        .collect()
+        .map(|i| Monkey::from_input(i, 1))
+        .collect::<Result<_, _>>()?;
+    // monkeys only throw to each other
+    for monkey in &monkeys {
+        for name in [monkey.test.case_true, monkey.test.case_false] {
+            if name >= monkeys.len() {
+                return Err(ParseError(format!("no monkey {name}")));
+            }
+        }
+    }
+    Ok(monkeys)
 }
 
-pub fn day11_1(input: &str) -> usize {
-    let mut monkeys = parse_input_1(input);
+pub fn part1(monkeys: &[Monkey]) -> usize {
+    let mut monkeys = monkeys.to_vec();
+    monkeys.iter_mut().for_each(|m| m.worry_divider = 3);
 
     for _ in 0..20 {
         for curr_m in 0..monkeys.len() {
@@ -162,15 +192,8 @@ pub fn day11_1(input: &str) -> usize {
     inspections.into_iter().rev().take(2).product()
 }
 
-fn parse_input_2(input: &str) -> Vec<Monkey> {
-    input
-        .split("\n\n")
-        .map(|i| Monkey::from_input(i, 1))
-        .collect()
-}
-
-pub fn day11_2(input: &str) -> usize {
-    let mut monkeys = parse_input_2(input);
+pub fn part2(monkeys: &[Monkey]) -> usize {
+    let mut monkeys = monkeys.to_vec();
     let divisors_product = monkeys.iter().fold(1, |acc, m| acc * m.test.test);
     monkeys
         .iter_mut()
@@ -188,7 +211,57 @@ pub fn day11_2(input: &str) -> usize {
     inspections.into_iter().rev().take(2).product()
 }
 
-const _TEST_INPUT: &str = "Monkey 0:
+pub fn day11_1(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|monkeys| part1(&monkeys))
+}
+
+pub fn day11_2(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|monkeys| part2(&monkeys))
+}
+
+/// Generates eight monkeys holding `size` items between them, for the scaling
+/// benchmark. Like the puzzle's, they test divisibility by distinct primes. They
+/// only multiply by 2 or 3 so that worry levels don't grow past a `u64` in
+/// part 1, where they are divided by 3, and never throw to themselves
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    const MONKEYS: usize = 8;
+    let mut items = vec![vec![]; MONKEYS];
+    for _ in 0..size {
+        items[rng.gen_range(0..MONKEYS)].push(rng.gen_range(50..100).to_string());
+    }
+    let mut divisors = [2, 3, 5, 7, 11, 13, 17, 19];
+    divisors.shuffle(rng);
+    let mut other = |name| (name + rng.gen_range(1..MONKEYS)) % MONKEYS;
+    items
+        .into_iter()
+        .zip(divisors)
+        .enumerate()
+        .map(|(name, (items, divisor))| {
+            let operation = match name % 3 {
+                0 => format!("old * {}", 2 + name % 2),
+                _ => format!("old + {}", 1 + name),
+            };
+            format!(
+                "Monkey {name}:\n  Starting items: {}\n  Operation: new = {operation}\n  \
+                 Test: divisible by {divisor}\n    If true: throw to monkey {}\n    \
+                 If false: throw to monkey {}\n",
+                items.join(", "),
+                other(name),
+                other(name)
+            )
+        })
+        .join("\n")
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day11_1, day11_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "Monkey 0:
 Starting items: 79, 98
 Operation: new = old * 19
 Test: divisible by 23
@@ -216,3 +289,16 @@ Test: divisible by 17
   If true: throw to monkey 0
   If false: throw to monkey 1
 ";
+
+    example_tests! {
+        test_day11_1: day11_1(INPUT) => Ok(10605),
+        test_day11_2: day11_2(INPUT) => Ok(2713310158),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[Monkey:Startingitems,Operationew=old+*Testdivsblyhrwf 0-9\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
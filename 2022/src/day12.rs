@@ -1,15 +1,21 @@
-#![allow(dead_code)]
+//! Day 12: Hill Climbing Algorithm
+//!
+//! `parse` reads the [`Heightmap`].
 
 use arrayvec::ArrayVec;
+#[cfg(feature = "scaling")]
+use rand::{seq::index::sample, Rng};
 
 use crate::grid::Grid;
+use crate::parse::{OrUnexpected, ParseError};
 
 type CoordU = u32;
 type GridU = i32;
+type Coord = (CoordU, CoordU);
 
-fn find_start_end(grid: &Grid<char>) -> ((CoordU, CoordU), (CoordU, CoordU)) {
-    let mut start: Option<(CoordU, CoordU)> = None;
-    let mut end: Option<(CoordU, CoordU)> = None;
+fn find_start_end(grid: &Grid<char>) -> Result<(Coord, Coord), ParseError> {
+    let mut start: Option<Coord> = None;
+    let mut end: Option<Coord> = None;
     for x in 0..grid.width {
         for y in 0..grid.height {
             if grid.get(x, y) == &'S' {
@@ -19,14 +25,14 @@ fn find_start_end(grid: &Grid<char>) -> ((CoordU, CoordU), (CoordU, CoordU)) {
             }
         }
     }
-    (start.unwrap(), end.unwrap())
+    Ok((
+        start.or_unexpected("no start S")?,
+        end.or_unexpected("no end E")?,
+    ))
 }
 
-fn input_to_grid(input: &str) -> Grid<char> {
-    let width = input.lines().next().unwrap().len();
-    let height = input.lines().count();
-    let grid_raw = input.lines().flat_map(|line| line.chars()).collect();
-    Grid::from_vec(width, height, grid_raw)
+fn input_to_grid(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::from_lines(input, |c| matches!(c, 'S' | 'E' | 'a'..='z').then_some(c))
 }
 
 fn neighbors(grid: &Grid<GridU>, (x, y): (CoordU, CoordU)) -> ArrayVec<(CoordU, CoordU), 4> {
@@ -92,17 +98,28 @@ fn char_grid_to_int(grid: Grid<char>) -> Grid<GridU> {
                 'S' => 'a' as GridU,
                 'E' => 'z' as GridU,
                 'a'..='z' => c as GridU,
-                _ => unimplemented!(),
+                _ => unreachable!("input_to_grid only keeps heights"),
             })
             .collect(),
     )
 }
 
-pub fn day12_1(input: &str) -> usize {
-    let grid = input_to_grid(input);
-    let (start, end) = find_start_end(&grid);
+/// The heights, and where the path starts and ends
+pub struct Heightmap {
+    grid: Grid<GridU>,
+    start: (CoordU, CoordU),
+    end: (CoordU, CoordU),
+}
+
+pub fn parse(input: &str) -> Result<Heightmap, ParseError> {
+    let grid = input_to_grid(input)?;
+    let (start, end) = find_start_end(&grid)?;
     let grid = char_grid_to_int(grid);
-    bfs_length(&grid, start, end)
+    Ok(Heightmap { grid, start, end })
+}
+
+pub fn part1(map: &Heightmap) -> usize {
+    bfs_length(&map.grid, map.start, map.end)
 }
 
 fn neighbors_rev(grid: &Grid<GridU>, (x, y): (CoordU, CoordU)) -> ArrayVec<(CoordU, CoordU), 4> {
@@ -158,16 +175,182 @@ fn bfs_length_rev(grid: &Grid<GridU>, end: (CoordU, CoordU)) -> usize {
     }
 }
 
-pub fn day12_2(input: &str) -> usize {
-    let grid = input_to_grid(input);
-    let (_, end) = find_start_end(&grid);
-    let grid = char_grid_to_int(grid);
-    bfs_length_rev(&grid, end)
+pub fn part2(map: &Heightmap) -> usize {
+    bfs_length_rev(&map.grid, map.end)
+}
+
+pub fn day12_1(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|map| part1(&map))
+}
+
+pub fn day12_2(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|map| part2(&map))
+}
+
+/// Generates a `size` by `size` heightmap, for the scaling benchmark. It is a
+/// single path snaking from the start at the top left to the end, going up at
+/// 25 random steps, so that the end can always be reached
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let steps = size * size;
+    let mut climbs = vec![false; steps];
+    for step in sample(rng, steps - 1, 25) {
+        climbs[step] = true;
+    }
+    let mut lines = vec![vec![b'a'; size]; size];
+    let mut height = b'a';
+    for (step, climb) in climbs.into_iter().enumerate() {
+        let y = step / size;
+        // every other line goes back to the left
+        let x = if y.is_multiple_of(2) {
+            step % size
+        } else {
+            size - 1 - step % size
+        };
+        lines[y][x] = height;
+        if climb {
+            height += 1;
+        }
+    }
+    lines[0][0] = b'S';
+    let end = if size.is_multiple_of(2) { 0 } else { size - 1 };
+    lines[size - 1][end] = b'E';
+    lines
+        .into_iter()
+        .map(|line| String::from_utf8(line).unwrap() + "\n")
+        .collect()
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    use std::collections::VecDeque;
+
+    fn parse(input: &str) -> Vec<Vec<u8>> {
+        input.lines().map(|line| line.bytes().collect()).collect()
+    }
+
+    fn height(square: u8) -> u8 {
+        match square {
+            b'S' => b'a',
+            b'E' => b'z',
+            square => square,
+        }
+    }
+
+    /// The fewest steps from `start` to the end, by a breadth first search
+    fn steps(map: &[Vec<u8>], start: (usize, usize)) -> Option<usize> {
+        let mut steps = vec![vec![None; map[0].len()]; map.len()];
+        steps[start.1][start.0] = Some(0);
+        let mut queue = VecDeque::from([start]);
+        while let Some((x, y)) = queue.pop_front() {
+            if map[y][x] == b'E' {
+                return steps[y][x];
+            }
+            for (nx, ny) in [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ] {
+                let Some(&square) = map.get(ny).and_then(|line| line.get(nx)) else {
+                    continue;
+                };
+                if height(square) <= height(map[y][x]) + 1 && steps[ny][nx].is_none() {
+                    steps[ny][nx] = Some(steps[y][x].unwrap() + 1);
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        None
+    }
+
+    fn squares(map: &[Vec<u8>]) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..map.len()).flat_map(move |y| (0..map[y].len()).map(move |x| (x, y)))
+    }
+
+    pub fn day12_1(input: &str) -> usize {
+        let map = parse(input);
+        let start = squares(&map).find(|&(x, y)| map[y][x] == b'S').unwrap();
+        steps(&map, start).unwrap()
+    }
+
+    /// Searches from every lowest square
+    pub fn day12_2(input: &str) -> usize {
+        let map = parse(input);
+        squares(&map)
+            .filter(|&(x, y)| height(map[y][x]) == b'a')
+            .filter_map(|start| steps(&map, start))
+            .min()
+            .unwrap()
+    }
 }
 
-const _TEST_INPUT: &str = "Sabqponm
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day12_1, day12_2, reference};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "Sabqponm
 abcryxxl
 accszExk
 acctuvwj
 abdefghi
 ";
+
+    example_tests! {
+        test_day12_1: day12_1(INPUT) => Ok(31),
+        test_day12_2: day12_2(INPUT) => Ok(29),
+    }
+
+    /// Heightmaps made of a single path snaking from the start on the first
+    /// line to the end, going up at most one at each step so that the end can
+    /// always be reached. Shorter paths cut across its turns
+    fn heightmaps() -> impl Strategy<Value = String> {
+        (3..8usize)
+            .prop_flat_map(|width| (Just(width), 26usize.div_ceil(width)..10))
+            .prop_flat_map(|(width, height)| {
+                let steps: Vec<usize> = (0..width * height - 1).collect();
+                (Just((width, height)), prop::sample::subsequence(steps, 25))
+            })
+            .prop_map(|((width, height), climbs)| {
+                let mut lines = vec![vec![b'a'; width]; height];
+                let mut height = b'a';
+                for i in 0..lines.len() * width {
+                    let (x, y) = (i % width, i / width);
+                    // every other line goes back to the left
+                    let x = if y.is_multiple_of(2) {
+                        x
+                    } else {
+                        width - 1 - x
+                    };
+                    lines[y][x] = height;
+                    if climbs.contains(&i) {
+                        height += 1;
+                    }
+                }
+                let last = lines.len() - 1;
+                let end = if last.is_multiple_of(2) { width - 1 } else { 0 };
+                lines[0][0] = b'S';
+                lines[last][end] = b'E';
+                lines
+                    .into_iter()
+                    .map(|line| String::from_utf8(line).unwrap() + "\n")
+                    .collect()
+            })
+    }
+
+    differential_tests! {
+        test_day12_1_reference(heightmaps()): day12_1 == reference::day12_1,
+        test_day12_2_reference(heightmaps()): day12_2 == reference::day12_2,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[SEa-e\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,17 +1,26 @@
-#![allow(dead_code)]
+//! Day 13: Distress Signal
+//!
+//! `parse` reads every [`Packet`]. Packets compare in the order the puzzle
+//! defines, so they can be sorted like any other value.
 
 use std::str::FromStr;
 
 use itertools::Itertools;
+#[cfg(feature = "scaling")]
+use rand::Rng;
 
+/// A packet of the distress signal, which is an integer or a list of packets.
+/// Packets are ordered by the puzzle's rules, and parse from and print to the
+/// puzzle's notation
 #[derive(Debug, Clone)]
-enum Packet {
+pub enum Packet {
     Int(i32),
     List(Vec<Packet>),
 }
 
+/// The text is not a packet
 #[derive(Debug, PartialEq, Eq)]
-struct ParsePacketError;
+pub struct ParsePacketError;
 
 struct SplitPackets<'a> {
     s: &'a str,
@@ -71,6 +80,15 @@ impl std::str::FromStr for Packet {
     }
 }
 
+impl std::fmt::Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packet::Int(n) => write!(f, "{n}"),
+            Packet::List(packets) => write!(f, "[{}]", packets.iter().join(",")),
+        }
+    }
+}
+
 impl PartialEq for Packet {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -116,7 +134,7 @@ impl Ord for Packet {
 }
 
 impl Packet {
-    fn split_packets(s: &str) -> SplitPackets {
+    fn split_packets(s: &str) -> SplitPackets<'_> {
         SplitPackets { s }
     }
     fn list_wrap(n: Packet) -> Packet {
@@ -127,38 +145,92 @@ impl Packet {
     // }
 }
 
-pub fn day13_1(input: &str) -> usize {
+/// Every packet, in order
+pub fn parse(input: &str) -> Result<Vec<Packet>, ParsePacketError> {
     input
         .lines()
         .filter(|s| !s.is_empty())
+        .map(Packet::from_str)
+        .collect()
+}
+
+pub fn part1(packets: &[Packet]) -> usize {
+    packets
+        .iter()
         .tuples()
-        .map(|(s1, s2)| {
-            let p1 = Packet::from_str(s1).unwrap();
-            let p2 = Packet::from_str(s2).unwrap();
-            p1 < p2
-        })
+        .map(|(p1, p2)| p1 < p2)
         .enumerate()
         .filter(|(_, b)| *b)
         .fold(0, |acc, (i, _)| acc + i + 1)
 }
 
-pub fn day13_2(input: &str) -> usize {
+pub fn part2(packets: &[Packet]) -> usize {
     let div1 = Packet::from_str("[[2]]").unwrap();
     let div2 = Packet::from_str("[[6]]").unwrap();
-    let mut packets: Vec<_> = input
-        .lines()
-        .filter(|s| !s.is_empty())
-        .map(|line| Packet::from_str(line).unwrap())
+    let mut packets: Vec<_> = packets
+        .iter()
+        .cloned()
         .chain([div1.clone(), div2.clone()])
         .collect();
     packets.sort_unstable();
     let pos1 = packets.iter().position(|p| p == &div1).unwrap() + 1;
-    let pos2 = packets.iter().skip(pos1).position(|p| p == &div2).unwrap() + 1;
+    let pos2 = pos1 + packets.iter().skip(pos1).position(|p| p == &div2).unwrap() + 1;
 
     pos1 * pos2
 }
 
-const _TEST_INPUT: &str = "[1,1,3,1,1]
+pub fn day13_1(input: &str) -> Result<usize, ParsePacketError> {
+    parse(input).map(|packets| part1(&packets))
+}
+
+pub fn day13_2(input: &str) -> Result<usize, ParsePacketError> {
+    parse(input).map(|packets| part2(&packets))
+}
+
+/// Generates `size` pairs of packets, for the scaling benchmark. Packets nest
+/// lists up to four deep, and the pairs often share a start like the puzzle's
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    fn packet(rng: &mut impl Rng, depth: usize) -> Packet {
+        if depth == 0 || rng.gen_bool(0.4) {
+            return Packet::Int(rng.gen_range(0..=10));
+        }
+        let len = rng.gen_range(0..=4);
+        Packet::List((0..len).map(|_| packet(rng, depth - 1)).collect())
+    }
+    (0..size)
+        .map(|_| {
+            let left = Packet::List(vec![packet(rng, 4), packet(rng, 4)]);
+            let right = match &left {
+                Packet::List(start) if rng.gen_bool(0.5) => {
+                    Packet::List(vec![start[0].clone(), packet(rng, 4)])
+                }
+                _ => Packet::List(vec![packet(rng, 4), packet(rng, 4)]),
+            };
+            format!("{left}\n{right}\n")
+        })
+        .join("\n")
+}
+
+/// Checks that a packet parsed from arbitrary text prints back to an equal one,
+/// and that `parse` doesn't panic
+#[cfg(any(test, fuzzing))]
+pub(crate) fn check_parsers(input: &str) {
+    let _ = parse(input);
+    if let Ok(packet) = Packet::from_str(input) {
+        assert_eq!(Packet::from_str(&packet.to_string()), Ok(packet));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use proptest::prelude::*;
+
+    use super::{check_parsers, day13_1, day13_2, Packet};
+
+    const INPUT: &str = "[1,1,3,1,1]
 [1,1,5,1,1]
 
 [[1],[2,3,4]]
@@ -182,3 +254,28 @@ const _TEST_INPUT: &str = "[1,1,3,1,1]
 [1,[2,[3,[4,[5,6,7]]]],8,9]
 [1,[2,[3,[4,[5,6,0]]]],8,9]
 ";
+
+    example_tests! {
+        test_day13_1: day13_1(INPUT) => Ok(13),
+        test_day13_2: day13_2(INPUT) => Ok(140),
+    }
+
+    fn packet() -> impl Strategy<Value = Packet> {
+        let int = any::<i32>().prop_map(Packet::Int);
+        int.prop_recursive(4, 64, 8, |inner| {
+            prop::collection::vec(inner, 0..8).prop_map(Packet::List)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[\\[\\],0-9 -]{0,64}") {
+            check_parsers(&input);
+        }
+
+        #[test]
+        fn packets_round_trip(packet in packet()) {
+            prop_assert_eq!(Packet::from_str(&packet.to_string()), Ok(packet));
+        }
+    }
+}
@@ -1,11 +1,16 @@
-#![allow(dead_code)]
+//! Day 14: Regolith Reservoir
+//!
+//! `parse` draws the rocks of the cave on a grid of [`Tile`]s.
 
 use itertools::Itertools;
 
 use crate::grid::Grid;
+use crate::parse::{OrUnexpected, ParseError};
+#[cfg(feature = "scaling")]
+use rand::Rng;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Tile {
+pub enum Tile {
     Air,
     Rock,
     Sand,
@@ -38,19 +43,39 @@ fn add_rocks(grid: &mut Grid<Tile>, line: Line) {
     }
 }
 
-fn parse_input(input: &str) -> (Grid<Tile>, usize) {
-    let lines = input.lines().flat_map(|line| {
-        line.split("->")
-            .map(|coord| {
-                let (x, y) = coord.trim().split_once(',').unwrap();
-                (x.parse().unwrap(), y.parse().unwrap())
-            })
-            .tuple_windows()
-            .map(|(c1, c2)| Line::new(c1, c2))
-    });
+/// Reads a corner of a rock path. It must be in the cave, with room for the
+/// floor of part 2 under it
+fn parse_coord(coord: &str) -> Result<Coord, ParseError> {
+    let (x, y) = coord.trim().split_once(',').or_unexpected(coord)?;
+    let (x, y) = (x.parse().or_unexpected(x)?, y.parse().or_unexpected(y)?);
+    if x >= 1000 || y >= 200 - 2 {
+        return Err(ParseError::new(coord));
+    }
+    Ok((x, y))
+}
+
+/// Reads the straight lines of rock of a path
+fn parse_path(path: &str) -> Result<Vec<Line>, ParseError> {
+    let corners: Vec<Coord> = path.split("->").map(parse_coord).try_collect()?;
+    corners
+        .into_iter()
+        .tuple_windows()
+        .map(|(c1 @ (x1, y1), c2 @ (x2, y2))| {
+            if x1 == x2 || y1 == y2 {
+                Ok(Line::new(c1, c2))
+            } else {
+                Err(ParseError::new(path))
+            }
+        })
+        .collect()
+}
+
+/// The cave with its rocks, and the depth of the lowest rock
+pub fn parse(input: &str) -> Result<(Grid<Tile>, usize), ParseError> {
+    let lines: Vec<Vec<Line>> = input.lines().map(parse_path).try_collect()?;
     let mut grid = Grid::from_vec(1000, 200, vec![Tile::Air; 200_000]);
     let mut lowest = 0;
-    for l in lines {
+    for l in lines.into_iter().flatten() {
         add_rocks(&mut grid, l);
         if l.a.1 > lowest {
             lowest = l.a.1;
@@ -59,7 +84,7 @@ fn parse_input(input: &str) -> (Grid<Tile>, usize) {
             lowest = l.b.1;
         }
     }
-    (grid, lowest)
+    Ok((grid, lowest))
 }
 
 fn pour_sand(grid: &mut Grid<Tile>) {
@@ -91,8 +116,8 @@ fn next_sand_spot(grid: &Grid<Tile>) -> Option<Coord> {
     None
 }
 
-pub fn day14_1(input: &str) -> usize {
-    let (mut grid, _) = parse_input(input);
+pub fn part1((grid, _): &(Grid<Tile>, usize)) -> usize {
+    let mut grid = grid.clone();
     pour_sand(&mut grid);
     grid.grid_raw
         .into_iter()
@@ -106,8 +131,8 @@ fn add_floor(grid: &mut Grid<Tile>, y: usize) {
     }
 }
 
-pub fn day14_2(input: &str) -> usize {
-    let (mut grid, lowest) = parse_input(input);
+pub fn part2((grid, lowest): &(Grid<Tile>, usize)) -> usize {
+    let mut grid = grid.clone();
     add_floor(&mut grid, lowest + 2);
     pour_sand(&mut grid);
     grid.grid_raw
@@ -116,6 +141,57 @@ pub fn day14_2(input: &str) -> usize {
         .count()
 }
 
-const _TEST_INPUT: &str = "498,4 -> 498,6 -> 496,6
+pub fn day14_1(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|cave| part1(&cave))
+}
+
+pub fn day14_2(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|cave| part2(&cave))
+}
+
+/// Generates a cave with `size` paths of rock, for the scaling benchmark. The
+/// cave is the solver's fixed 1000 by 200 grid, so the paths stay in the part
+/// of it sand can reach, and with no room left larger inputs mostly add rock
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let (mut x, mut y) = (rng.gen_range(320..=680), rng.gen_range(10..=180));
+            let mut path = vec![format!("{x},{y}")];
+            // the corners turn at every step, between horizontal and vertical
+            for i in 0..rng.gen_range(1..=5) {
+                if i % 2 == 0 {
+                    x = rng.gen_range(x - 10..=x + 10).clamp(320, 680);
+                } else {
+                    y = rng.gen_range(y - 10..=y + 10).clamp(10, 180);
+                }
+                path.push(format!("{x},{y}"));
+            }
+            path.join(" -> ") + "\n"
+        })
+        .collect()
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day14_1, day14_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "498,4 -> 498,6 -> 496,6
 503,4 -> 502,4 -> 502,9 -> 494,9
 ";
+
+    example_tests! {
+        test_day14_1: day14_1(INPUT) => Ok(24),
+        test_day14_2: day14_2(INPUT) => Ok(93),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[0-9,> \n-]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
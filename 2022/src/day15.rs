@@ -1,10 +1,16 @@
-#![allow(dead_code)]
+//! Day 15: Beacon Exclusion Zone
+//!
+//! `parse` reads the area each sensor covers as a [`Circle`] in manhattan
+//! distance, and where the beacons are.
 
+use crate::parse::{OrUnexpected, ParseError};
 use itertools::Itertools;
+#[cfg(feature = "scaling")]
+use rand::Rng;
 use std::collections::{hash_map::Entry, HashMap, HashSet};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Circle {
+pub struct Circle {
     pub center: (i64, i64),
     pub radius: usize,
 }
@@ -32,21 +38,27 @@ impl Circle {
     }
 }
 
-fn parse_input(input: &str) -> (Vec<Circle>, HashMap<i64, HashSet<i64>>) {
-    let parsed = input
+/// The area each sensor covers, and the beacons' columns by row
+pub type Sensors = (Vec<Circle>, HashMap<i64, HashSet<i64>>);
+
+/// Reads every sensor and the beacon closest to it
+pub fn parse(input: &str) -> Result<Sensors, ParseError> {
+    // coordinates fit in an i32, so that distances between them fit in an i64
+    let coord = |n: &str| n.parse::<i32>().map(i64::from).or_unexpected(n);
+    let parsed: Vec<(i64, i64, i64, i64)> = input
         .lines()
-        .map(|line| {
-            let line = line.strip_prefix("Sensor at x=").unwrap();
-            let (x, line) = line.split_once(", y=").unwrap();
-            let (y, line) = line.split_once(": closest beacon is at x=").unwrap();
-            let (rx, ry) = line.split_once(", y=").unwrap();
-            let x = x.parse().unwrap();
-            let y = y.parse().unwrap();
-            let rx: i64 = rx.parse().unwrap();
-            let ry: i64 = ry.parse().unwrap();
-            (x, y, rx, ry)
+        .map(|full_line| {
+            let line = full_line
+                .strip_prefix("Sensor at x=")
+                .or_unexpected(full_line)?;
+            let (x, line) = line.split_once(", y=").or_unexpected(full_line)?;
+            let (y, line) = line
+                .split_once(": closest beacon is at x=")
+                .or_unexpected(full_line)?;
+            let (rx, ry) = line.split_once(", y=").or_unexpected(full_line)?;
+            Ok((coord(x)?, coord(y)?, coord(rx)?, coord(ry)?))
         })
-        .collect_vec();
+        .try_collect()?;
     let circles = parsed
         .iter()
         .map(|&(x, y, rx, ry)| Circle {
@@ -70,11 +82,11 @@ fn parse_input(input: &str) -> (Vec<Circle>, HashMap<i64, HashSet<i64>>) {
             acc
         },
     );
-    (circles, beacons)
+    Ok((circles, beacons))
 }
 
 fn count_beacont_at(sensors: &[Circle], beacons: Option<&HashSet<i64>>, y: i64) -> usize {
-    let mut ranges = sensors
+    let ranges = sensors
         .iter()
         .filter_map(|c| {
             let distance = (c.center.1 - y).abs();
@@ -87,50 +99,47 @@ fn count_beacont_at(sensors: &[Circle], beacons: Option<&HashSet<i64>>, y: i64)
         })
         .sorted_by(|(x1, _), (x2, _)| x1.cmp(x2));
 
-    let (prex, mut prey) = ranges.next().unwrap();
-    let mut sum = prey - prex + 1;
-
-    let mut beacons = match beacons {
-        Some(b) => b.iter().sorted().collect_vec().into_iter().peekable(),
-        None => vec![].into_iter().peekable(),
-    };
-    if let Some(&&bx) = beacons.peek() {
-        if bx <= prey && bx >= prex {
-            sum -= 1;
-            beacons.next();
-        }
-    }
+    // merge the overlapping ranges, then drop the beacons they cover
+    let mut merged: Vec<(i64, i64)> = vec![];
     for (x, y) in ranges {
-        if y <= prey {
-            continue;
+        match merged.last_mut() {
+            Some((_, prey)) if x <= *prey + 1 => *prey = (*prey).max(y),
+            _ => merged.push((x, y)),
         }
-        if x <= prey {
-            sum += y - prey;
-            if let Some(&&bx) = beacons.peek() {
-                if bx > prey && bx <= y {
-                    sum -= 1;
-                    beacons.next();
-                }
-            }
-        } else {
-            sum += y - x + 1;
-            if let Some(&&bx) = beacons.peek() {
-                if bx >= x && bx <= y {
-                    sum -= 1;
-                    beacons.next();
-                }
-            }
+    }
+    let covered = merged.iter().map(|(x, y)| y - x + 1).sum::<i64>() as usize;
+    let beacons = beacons.map_or(0, |b| {
+        b.iter()
+            .filter(|&&bx| merged.iter().any(|&(x, y)| (x..=y).contains(&bx)))
+            .count()
+    });
+    covered - beacons
+}
+
+/// The puzzle constants, which are smaller in the example
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    /// Row where part 1 counts the positions that can't hold a beacon
+    pub row: i64,
+    /// Part 2 looks for the distress beacon from 0 to `limit` on both axes
+    pub limit: i64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            row: 2_000_000,
+            limit: 4_000_000,
         }
-        prey = y;
     }
-    sum as usize
 }
 
-pub fn day15_1(input: &str) -> usize {
-    // let (input, line) = (_TEST_INPUT.to_owned(), 10);
-    let line = 2_000_000;
-    let (sensors, beacons) = parse_input(input);
-    count_beacont_at(&sensors, beacons.get(&line), line)
+pub fn part1(parsed: &Sensors) -> usize {
+    part1_with(parsed, &Params::default())
+}
+
+pub fn part1_with((sensors, beacons): &Sensors, params: &Params) -> usize {
+    count_beacont_at(sensors, beacons.get(&params.row), params.row)
 }
 
 fn find_empty(sensors: &[Circle], y: i64) -> Option<i64> {
@@ -204,13 +213,111 @@ fn find_distress(sensors: &[Circle], limit: i64) -> Option<usize> {
     None
 }
 
-pub fn day15_2(input: &str) -> usize {
-    let limit = 4_000_000;
-    let (sensors, _) = parse_input(input);
-    find_distress(&sensors, limit).unwrap()
+pub fn part2(parsed: &Sensors) -> usize {
+    part2_with(parsed, &Params::default())
+}
+
+pub fn part2_with((sensors, _): &Sensors, params: &Params) -> usize {
+    find_distress(sensors, params.limit).unwrap()
+}
+
+pub fn day15_1(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|sensors| part1(&sensors))
+}
+
+pub fn day15_1_with(input: &str, params: &Params) -> Result<usize, ParseError> {
+    parse(input).map(|sensors| part1_with(&sensors, params))
+}
+
+pub fn day15_2(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|sensors| part2(&sensors))
+}
+
+pub fn day15_2_with(input: &str, params: &Params) -> Result<usize, ParseError> {
+    parse(input).map(|sensors| part2_with(&sensors, params))
+}
+
+/// Generates `size` sensors, plus four more, for the scaling benchmark. Part 2
+/// needs a single spot no sensor covers, so the four extra sensors sit on the
+/// diagonals around a random distress beacon and reach just short of it,
+/// covering the rest of the search area. The `size` others are placed anywhere
+/// with a beacon closer than the distress one
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let Params { limit, .. } = Params::default();
+    let distress = (rng.gen_range(0..=limit), rng.gen_range(0..=limit));
+    let line = |(x, y): (i64, i64), (bx, by): (i64, i64)| {
+        format!("Sensor at x={x}, y={y}: closest beacon is at x={bx}, y={by}\n")
+    };
+    let mut input = String::new();
+    // far enough for the diagonal sensors to cover the whole search area
+    let far = 2 * limit;
+    for (dx, dy) in [(-1, -1), (-1, 1), (1, -1), (1, 1)] {
+        let sensor = (distress.0 + dx * far, distress.1 + dy * far);
+        // one step closer to the sensor than the distress beacon
+        let beacon = (sensor.0, sensor.1 - dy * (2 * far - 1));
+        input += &line(sensor, beacon);
+    }
+    for _ in 0..size {
+        let sensor = (rng.gen_range(0..=limit), rng.gen_range(0..=limit));
+        if sensor == distress {
+            continue;
+        }
+        let reach = (sensor.0 - distress.0).abs() + (sensor.1 - distress.1).abs() - 1;
+        let dx = rng.gen_range(0..=reach.min(limit / 10));
+        let dy = rng.gen_range(0..=reach.min(limit / 10) - dx);
+        input += &line(sensor, (sensor.0 + dx, sensor.1 - dy));
+    }
+    input
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    fn parse(input: &str) -> Vec<((i64, i64), (i64, i64))> {
+        input
+            .lines()
+            .map(|line| {
+                let numbers: Vec<i64> = line
+                    .split(|c: char| c != '-' && !c.is_ascii_digit())
+                    .filter(|n| !n.is_empty())
+                    .map(|n| n.parse().unwrap())
+                    .collect();
+                ((numbers[0], numbers[1]), (numbers[2], numbers[3]))
+            })
+            .collect()
+    }
+
+    fn distance(a: (i64, i64), b: (i64, i64)) -> i64 {
+        (a.0 - b.0).abs() + (a.1 - b.1).abs()
+    }
+
+    pub fn count_line(input: &str, line: i64) -> usize {
+        let sensors = parse(input);
+        let reach = sensors.iter().map(|&(s, b)| distance(s, b)).max().unwrap();
+        let min_x = sensors.iter().map(|(s, _)| s.0).min().unwrap() - reach;
+        let max_x = sensors.iter().map(|(s, _)| s.0).max().unwrap() + reach;
+        (min_x..=max_x)
+            .filter(|&x| {
+                let p = (x, line);
+                sensors.iter().all(|&(_, b)| b != p)
+                    && sensors
+                        .iter()
+                        .any(|&(s, b)| distance(s, p) <= distance(s, b))
+            })
+            .count()
+    }
 }
 
-const _TEST_INPUT: &str = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::{check_parsers, day15_1_with, day15_2_with, reference, Params, ParseError};
+
+    const INPUT: &str = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
 Sensor at x=9, y=16: closest beacon is at x=10, y=16
 Sensor at x=13, y=2: closest beacon is at x=15, y=3
 Sensor at x=12, y=14: closest beacon is at x=10, y=16
@@ -225,3 +332,45 @@ Sensor at x=16, y=7: closest beacon is at x=15, y=3
 Sensor at x=14, y=3: closest beacon is at x=15, y=3
 Sensor at x=20, y=1: closest beacon is at x=15, y=3
 ";
+
+    const EXAMPLE: Params = Params { row: 10, limit: 20 };
+
+    example_tests! {
+        test_day15_1: day15_1_with(INPUT, &EXAMPLE) => Ok(26),
+        test_day15_2: day15_2_with(INPUT, &EXAMPLE) => Ok(56000011),
+    }
+
+    fn day15_1_example(input: &str) -> Result<usize, ParseError> {
+        day15_1_with(input, &EXAMPLE)
+    }
+
+    fn reference_day15_1_example(input: &str) -> usize {
+        reference::count_line(input, EXAMPLE.row)
+    }
+
+    fn sensors() -> impl Strategy<Value = String> {
+        let sensor = ((0..20i64, 0..20i64), (-6..=6i64, -6..=6i64));
+        prop::collection::vec(sensor, 1..6).prop_map(|sensors| {
+            sensors
+                .into_iter()
+                .map(|((x, y), (dx, dy))| {
+                    // a beacon is never on its sensor
+                    let dx = if (dx, dy) == (0, 0) { 1 } else { dx };
+                    let (bx, by) = (x + dx, y + dy);
+                    format!("Sensor at x={x}, y={y}: closest beacon is at x={bx}, y={by}\n")
+                })
+                .collect()
+        })
+    }
+
+    differential_tests! {
+        test_day15_1_reference(sensors()): day15_1_example == reference_day15_1_example,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[Sensorat x=,y:closestbeaconis0-9\n-]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,15 +1,23 @@
-#![allow(dead_code)]
+//! Day 16: Proboscidea Volcanium
+//!
+//! `parse` reduces the tunnels to a graph of the working [`Valve`]s.
 
+use crate::parse::{OrUnexpected, ParseError};
+use harness::runner::cancelled;
 use itertools::Itertools;
 use petgraph::{algo::dijkstra, graph::EdgeReference, prelude::*};
+#[cfg(feature = "scaling")]
+use rand::{seq::SliceRandom, Rng};
 
 use std::{collections::HashMap, fmt::Debug, vec};
 
 type MyGraphIx = u8;
-type MyGraph = Graph<Valve, i32, Undirected, MyGraphIx>;
+/// The valves, connected by the minutes it takes to walk between them
+pub type MyGraph = Graph<Valve, i32, Undirected, MyGraphIx>;
 
+/// A valve and how much pressure it releases each minute once open
 #[derive(Clone, PartialEq, Eq)]
-struct Valve {
+pub struct Valve {
     name: String,
     flow_rate: i32,
 }
@@ -27,17 +35,20 @@ impl Valve {
 }
 
 #[derive(Debug)]
-struct VolcanoPath<const M: i32> {
+struct VolcanoPath {
     visited: Vec<NodeIndex<MyGraphIx>>,
+    /// Time before the volcano erupts
+    minutes: i32,
     minute: i32,
     pressure: i32,
     pressure_release: i32,
 }
 
-impl<const M: i32> VolcanoPath<M> {
-    pub fn new(start: NodeIndex<MyGraphIx>) -> Self {
+impl VolcanoPath {
+    pub fn new(start: NodeIndex<MyGraphIx>, minutes: i32) -> Self {
         Self {
             visited: vec![start],
+            minutes,
             minute: 0,
             pressure: 0,
             pressure_release: 0,
@@ -49,7 +60,7 @@ impl<const M: i32> VolcanoPath<M> {
     pub fn possible_next_edges<'a>(&self, graph: &'a MyGraph) -> Vec<EdgeReference<'a, i32, u8>> {
         graph
             .edges(self.current())
-            .filter(|e| e.weight() + self.minute < M)
+            .filter(|e| e.weight() + self.minute < self.minutes)
             .filter(|e| !self.visited.contains(&e.target()))
             .collect_vec()
     }
@@ -58,52 +69,69 @@ impl<const M: i32> VolcanoPath<M> {
         visited.push(edge.target());
         Self {
             visited,
+            minutes: self.minutes,
             minute: edge.weight() + self.minute,
             pressure: self.pressure_release * edge.weight() + self.pressure,
             pressure_release: self.pressure_release + graph[edge.target()].flow_rate,
         }
     }
     pub fn final_pressure(&self) -> i32 {
-        self.pressure + self.pressure_release * (M - self.minute)
+        self.pressure + self.pressure_release * (self.minutes - self.minute)
     }
 }
 
-fn parse_input(input: &str) -> MyGraph {
-    let parsed = input
+/// The working valves and AA, with the time to go from each to the others.
+/// Every tunnel must lead to a valve of the input, and there can be at most 23
+/// working valves, as [`MyGraph`] numbers its edges with a `u8`
+pub fn parse(input: &str) -> Result<MyGraph, ParseError> {
+    let parsed: Vec<_> = input
         .lines()
-        .map(|line| {
-            let line = line.strip_prefix("Valve ").unwrap();
-            let (valve_name, line) = line.split_once(" has flow rate=").unwrap();
-            let (flow_rate, line) = line.split_once("; tunnel").unwrap();
-            let flow_rate = flow_rate.parse::<i32>().unwrap();
+        .map(|full_line| {
+            let line = full_line.strip_prefix("Valve ").or_unexpected(full_line)?;
+            let (valve_name, line) = line
+                .split_once(" has flow rate=")
+                .or_unexpected(full_line)?;
+            let (flow_rate, line) = line.split_once("; tunnel").or_unexpected(full_line)?;
+            let flow_rate = flow_rate.parse::<u16>().or_unexpected(flow_rate)?;
             let line = match line.strip_prefix('s') {
-                Some(line) => line.strip_prefix(" lead to valves ").unwrap(),
-                None => line.strip_prefix(" leads to valve ").unwrap(),
-            };
+                Some(line) => line.strip_prefix(" lead to valves "),
+                None => line.strip_prefix(" leads to valve "),
+            }
+            .or_unexpected(full_line)?;
             let connected = line.split(", ");
-            (valve_name, flow_rate, connected)
+            Ok((valve_name, i32::from(flow_rate), connected))
         })
-        .collect_vec();
+        .try_collect()?;
 
     let mut valves = HashMap::<&str, _>::new();
     let mut raw_graph = Graph::default();
     for (valve_name, flow_rate, _) in &parsed {
         let node = raw_graph.add_node(Valve::new(valve_name.to_string(), *flow_rate));
-        valves.insert(valve_name, node);
+        if valves.insert(*valve_name, node).is_some() {
+            return Err(ParseError::new(valve_name));
+        }
     }
     for (curr, _, connected) in parsed {
         for c in connected {
-            raw_graph.update_edge(valves[curr], valves[c], 1);
+            let other = *valves.get(c).or_unexpected(c)?;
+            raw_graph.update_edge(valves[curr], other, 1);
         }
     }
-    connect_positives(&raw_graph)
+    if !valves.contains_key("AA") {
+        return Err(ParseError::new("no valve AA"));
+    }
+    let good_valves = raw_graph.node_weights().filter(|v| good_valve(v)).count();
+    if good_valves * (good_valves - 1) / 2 >= MyGraphIx::MAX as usize {
+        return Err(ParseError::new("too many working valves"));
+    }
+    Ok(connect_positives(&raw_graph))
 }
 
 fn good_valve(valve: &Valve) -> bool {
     valve.flow_rate > 0 || valve.name == "AA"
 }
 
-fn connect_positives(original_graph: &MyGraph) -> MyGraph {
+fn connect_positives(original_graph: &Graph<Valve, i32, Undirected>) -> MyGraph {
     let mut graph = Graph::default();
     for valve in original_graph.node_weights().filter(|v| good_valve(v)) {
         graph.add_node(valve.clone());
@@ -133,14 +161,18 @@ fn connect_positives(original_graph: &MyGraph) -> MyGraph {
     graph
 }
 
-fn find_optimal_path(graph: &MyGraph) -> VolcanoPath<30> {
+fn find_optimal_path(graph: &MyGraph, minutes: i32) -> VolcanoPath {
     let start = graph
         .node_indices()
         .find(|i| graph[*i].name == "AA")
         .unwrap();
-    let mut paths = vec![VolcanoPath::new(start)];
-    let mut best_path: Option<VolcanoPath<30>> = None;
+    let mut paths = vec![VolcanoPath::new(start, minutes)];
+    let mut best_path: Option<VolcanoPath> = None;
     while let Some(path) = paths.pop() {
+        // the runner gave up on us, keep the best path so far
+        if best_path.is_some() && cancelled() {
+            break;
+        }
         let next_edges = path.possible_next_edges(graph);
         if next_edges.is_empty() {
             // dbg!(&best_path, &path);
@@ -163,71 +195,260 @@ fn find_optimal_path(graph: &MyGraph) -> VolcanoPath<30> {
     best_path.unwrap()
 }
 
-pub fn day16_1(input: &str) -> i32 {
-    let graph = parse_input(input);
+/// The time before the eruption in each part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub part1_minutes: i32,
+    /// Shorter, as teaching the elephant takes some of it
+    pub part2_minutes: i32,
+}
 
-    let best_path = find_optimal_path(&graph);
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_minutes: 30,
+            part2_minutes: 26,
+        }
+    }
+}
+
+pub fn part1(graph: &MyGraph) -> i32 {
+    part1_with(graph, &Params::default())
+}
+
+pub fn part1_with(graph: &MyGraph, params: &Params) -> i32 {
+    let best_path = find_optimal_path(graph, params.part1_minutes);
     best_path.final_pressure()
 }
 
-fn all_complete_paths(graph: &MyGraph) -> Vec<VolcanoPath<26>> {
+/// The most pressure a single walker can release by opening each set of valves,
+/// as a bit mask of the valves and that pressure, from the highest. Every path
+/// counts, even one that could still go on, as the other walker may open the
+/// rest
+fn best_per_valves(graph: &MyGraph, minutes: i32) -> Vec<(u32, i32)> {
     let start = graph
         .node_indices()
         .find(|i| graph[*i].name == "AA")
         .unwrap();
-    let mut paths = vec![VolcanoPath::<26>::new(start)];
-    let mut complete_paths = vec![];
+    let mut paths = vec![VolcanoPath::new(start, minutes)];
+    let mut best: HashMap<u32, i32> = HashMap::new();
     while let Some(path) = paths.pop() {
-        let next_edges = path.possible_next_edges(graph);
-        if next_edges.is_empty() {
-            // dbg!(&best_path, &path);
-            complete_paths.push(path);
-            continue;
+        if cancelled() {
+            break;
         }
-        for edge in next_edges {
+        for edge in path.possible_next_edges(graph) {
             paths.push(path.goto(edge, graph))
         }
+        let valves = path.visited[1..]
+            .iter()
+            .fold(0, |valves, valve| valves | 1 << valve.index());
+        let pressure = best.entry(valves).or_default();
+        *pressure = (*pressure).max(path.final_pressure());
     }
-    complete_paths.sort_unstable_by_key(|p1| -p1.final_pressure());
-    complete_paths
+    let mut best = best.into_iter().collect_vec();
+    best.sort_unstable_by_key(|&(_, pressure)| -pressure);
+    best
 }
 
-fn no_overlap(path: &VolcanoPath<26>, other: &VolcanoPath<26>) -> bool {
-    let (_, other_visited_without_start) = other.visited.split_first().unwrap();
-    path.visited
-        .iter()
-        .all(|p| !other_visited_without_start.iter().contains(p))
+/// The most pressure two walkers opening different valves release together
+fn best_pair(best: &[(u32, i32)]) -> i32 {
+    let mut best_pressure = 0;
+    for (i, &(valves, pressure)) in best.iter().enumerate() {
+        if 2 * pressure < best_pressure || cancelled() {
+            break;
+        }
+        // the sets are sorted, so the first one apart is the best partner
+        if let Some((_, other)) = best[i..].iter().find(|(other, _)| other & valves == 0) {
+            best_pressure = best_pressure.max(pressure + other);
+        }
+    }
+    best_pressure
 }
 
-fn find_best_path_duo(paths: &[VolcanoPath<26>]) -> (&VolcanoPath<26>, &VolcanoPath<26>) {
-    let (mut best, mut rest) = paths.split_first().unwrap();
-    let mut best_duo = rest.iter().find(|p| no_overlap(best, p)).unwrap();
-    let mut best_pressure = best.final_pressure() + best_duo.final_pressure();
-    loop {
-        let (path1, r) = rest.split_first().unwrap();
-        rest = r;
+pub fn part2(graph: &MyGraph) -> i32 {
+    part2_with(graph, &Params::default())
+}
 
-        if 2 * path1.final_pressure() < best_pressure {
-            return (best, best_duo);
-        }
-        let path2 = rest.iter().find(|p| no_overlap(path1, p)).unwrap();
-        let this_pressure = path1.final_pressure() + path2.final_pressure();
-        if this_pressure > best_pressure {
-            best_pressure = this_pressure;
-            best = path1;
-            best_duo = path2;
+pub fn part2_with(graph: &MyGraph, params: &Params) -> i32 {
+    best_pair(&best_per_valves(graph, params.part2_minutes))
+}
+
+pub fn day16_1(input: &str) -> Result<i32, ParseError> {
+    parse(input).map(|graph| part1(&graph))
+}
+
+pub fn day16_1_with(input: &str, params: &Params) -> Result<i32, ParseError> {
+    parse(input).map(|graph| part1_with(&graph, params))
+}
+
+pub fn day16_2(input: &str) -> Result<i32, ParseError> {
+    parse(input).map(|graph| part2(&graph))
+}
+
+pub fn day16_2_with(input: &str, params: &Params) -> Result<i32, ParseError> {
+    parse(input).map(|graph| part2_with(&graph, params))
+}
+
+/// Generates a cave with `size` working valves, for the scaling benchmark.
+/// Like the puzzle inputs there are three broken valves for each working one,
+/// all connected by a random tree of tunnels plus a few extra ones
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let count = 1 + 4 * size;
+    // AA comes first, as valve 0
+    let name = |i: usize| {
+        let letter = |n: usize| (b'A' + n as u8) as char;
+        format!("{}{}", letter(i / 26), letter(i % 26))
+    };
+    let mut tunnels = vec![vec![]; count];
+    let mut connect = |a: usize, b: usize| {
+        if a != b && !tunnels[a].contains(&b) {
+            tunnels[a].push(b);
+            tunnels[b].push(a);
         }
+    };
+    for valve in 1..count {
+        connect(valve, rng.gen_range(0..valve));
     }
+    for _ in 0..size {
+        connect(rng.gen_range(0..count), rng.gen_range(0..count));
+    }
+    let mut working: Vec<usize> = (1..count).collect();
+    working.shuffle(rng);
+    working.truncate(size);
+    (0..count)
+        .map(|valve| {
+            let flow_rate = if working.contains(&valve) {
+                rng.gen_range(1..=25)
+            } else {
+                0
+            };
+            let others = tunnels[valve].iter().map(|&other| name(other)).join(", ");
+            let tunnels = if tunnels[valve].len() == 1 {
+                "tunnel leads to valve"
+            } else {
+                "tunnels lead to valves"
+            };
+            format!(
+                "Valve {} has flow rate={flow_rate}; {tunnels} {others}\n",
+                name(valve)
+            )
+        })
+        .collect()
 }
 
-pub fn day16_2(input: &str) -> i32 {
-    let graph = parse_input(input);
-    let paths = all_complete_paths(&graph);
-    let (p1, p2) = find_best_path_duo(&paths);
-    p1.final_pressure() + p2.final_pressure()
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    use std::collections::{HashMap, VecDeque};
+
+    struct Cave {
+        /// Flow rate of each valve
+        flows: Vec<i32>,
+        /// Minutes to walk between any two valves, if one can be reached from
+        /// the other
+        distances: Vec<Vec<Option<i32>>>,
+        start: usize,
+    }
+
+    fn parse(input: &str) -> Cave {
+        let valves: Vec<(&str, i32, Vec<&str>)> = input
+            .lines()
+            .map(|line| {
+                let words: Vec<&str> = line
+                    .split([' ', '=', ';', ','])
+                    .filter(|word| !word.is_empty())
+                    .collect();
+                (words[1], words[5].parse().unwrap(), words[10..].to_vec())
+            })
+            .collect();
+        let index: HashMap<&str, usize> = valves
+            .iter()
+            .enumerate()
+            .map(|(i, &(name, _, _))| (name, i))
+            .collect();
+        let distances = (0..valves.len())
+            .map(|from| {
+                let mut distances = vec![None; valves.len()];
+                distances[from] = Some(0);
+                let mut queue = VecDeque::from([from]);
+                while let Some(valve) = queue.pop_front() {
+                    for next in &valves[valve].2 {
+                        let next = index[next];
+                        if distances[next].is_none() {
+                            distances[next] = Some(distances[valve].unwrap() + 1);
+                            queue.push_back(next);
+                        }
+                    }
+                }
+                distances
+            })
+            .collect();
+        Cave {
+            flows: valves.iter().map(|&(_, flow, _)| flow).collect(),
+            distances,
+            start: index["AA"],
+        }
+    }
+
+    /// Tries opening the `closed` valves in every order, from `at` with
+    /// `minutes` left
+    fn most_pressure(cave: &Cave, at: usize, minutes: i32, closed: &[usize]) -> i32 {
+        closed
+            .iter()
+            .filter_map(|&valve| {
+                let minutes = minutes - cave.distances[at][valve]? - 1;
+                let rest: Vec<usize> = closed.iter().copied().filter(|&v| v != valve).collect();
+                (minutes > 0).then(|| {
+                    cave.flows[valve] * minutes + most_pressure(cave, valve, minutes, &rest)
+                })
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn working_valves(cave: &Cave) -> Vec<usize> {
+        (0..cave.flows.len())
+            .filter(|&valve| valve != cave.start && cave.flows[valve] > 0)
+            .collect()
+    }
+
+    pub fn day16_1(input: &str) -> i32 {
+        let cave = parse(input);
+        most_pressure(&cave, cave.start, 30, &working_valves(&cave))
+    }
+
+    /// Tries every way to split the valves between us and the elephant
+    pub fn day16_2(input: &str) -> i32 {
+        let cave = parse(input);
+        let working = working_valves(&cave);
+        (0..1 << working.len())
+            .map(|ours: usize| {
+                let mine: Vec<usize> = (0..working.len())
+                    .filter(|i| ours & 1 << i != 0)
+                    .map(|i| working[i])
+                    .collect();
+                let elephant: Vec<usize> = working
+                    .iter()
+                    .copied()
+                    .filter(|valve| !mine.contains(valve))
+                    .collect();
+                most_pressure(&cave, cave.start, 26, &mine)
+                    + most_pressure(&cave, cave.start, 26, &elephant)
+            })
+            .max()
+            .unwrap()
+    }
 }
 
-const _TEST_INPUT: &str = "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day16_1, day16_2, reference};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
 Valve BB has flow rate=13; tunnels lead to valves CC, AA
 Valve CC has flow rate=2; tunnels lead to valves DD, BB
 Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
@@ -238,3 +459,68 @@ Valve HH has flow rate=22; tunnel leads to valve GG
 Valve II has flow rate=0; tunnels lead to valves AA, JJ
 Valve JJ has flow rate=21; tunnel leads to valve II
 ";
+
+    example_tests! {
+        test_day16_1: day16_1(INPUT) => Ok(1651),
+        test_day16_2: day16_2(INPUT) => Ok(1707),
+    }
+
+    /// Caves of a few valves after AA, connected by a random tree of tunnels
+    /// plus a few extra ones, at least one of them working
+    fn caves() -> impl Strategy<Value = String> {
+        (2..8usize)
+            .prop_flat_map(|valves| {
+                (
+                    prop::collection::vec(prop_oneof![Just(0), 1..25i32], valves - 1),
+                    (1..valves).map(|valve| 0..valve).collect::<Vec<_>>(),
+                    prop::collection::vec((0..valves, 0..valves), 0..3),
+                )
+            })
+            .prop_map(|(mut flows, tree, extra)| {
+                if flows.iter().all(|&flow| flow == 0) {
+                    flows[0] = 1;
+                }
+                flows.insert(0, 0);
+                let mut tunnels = vec![vec![]; flows.len()];
+                let edges = tree
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, other)| (i + 1, other));
+                for (a, b) in edges.chain(extra) {
+                    if a != b && !tunnels[a].contains(&b) {
+                        tunnels[a].push(b);
+                        tunnels[b].push(a);
+                    }
+                }
+                let name = |valve: usize| ((b'A' + valve as u8) as char).to_string().repeat(2);
+                (0..flows.len())
+                    .map(|valve| {
+                        let others: Vec<String> = tunnels[valve].iter().map(|&v| name(v)).collect();
+                        let tunnels = if others.len() == 1 {
+                            "tunnel leads to valve"
+                        } else {
+                            "tunnels lead to valves"
+                        };
+                        format!(
+                            "Valve {} has flow rate={}; {tunnels} {}\n",
+                            name(valve),
+                            flows[valve],
+                            others.join(", ")
+                        )
+                    })
+                    .collect()
+            })
+    }
+
+    differential_tests! {
+        test_day16_1_reference(caves()): day16_1 == reference::day16_1,
+        test_day16_2_reference(caves()): day16_2 == reference::day16_2,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[Valve hsflowrate=;tunelead,AB 0-9\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,9 +1,18 @@
-#![allow(dead_code)]
+//! Day 17: Pyroclastic Flow
+//!
+//! `parse` reads the jets of hot gas, as [`Wind`].
 
 use crate::grid::Grid;
+use crate::parse::ParseError;
 use lazy_static::lazy_static;
+#[cfg(feature = "scaling")]
+use rand::Rng;
 
-use std::{collections::HashMap, fmt::Debug, hash::Hash};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    hash::Hash,
+};
 
 #[derive(Debug, Clone)]
 struct Rock {
@@ -51,7 +60,7 @@ lazy_static! {
     ];
 }
 #[derive(Debug)]
-enum Wind {
+pub enum Wind {
     Left,
     Right,
 }
@@ -97,9 +106,18 @@ impl<'a> Debug for Field<'a> {
     }
 }
 
+/// How deep below the top of the tower the surface of a field goes. A column
+/// the jets keep rocks out of would otherwise make it deeper at each rock, so
+/// that no state ever repeats
+const SURFACE_DEPTH: usize = 64;
+
+/// Lines the field starts with, enough for a few thousand rocks before it has
+/// to grow
+const INITIAL_HEIGHT: usize = 10000;
+
 impl<'a> Field<'a> {
     pub fn new(wind: &'a [Wind]) -> Self {
-        let field = Grid::from_vec(7, 10000, vec![false; 7 * 10000]);
+        let field = Grid::from_vec(7, INITIAL_HEIGHT, vec![false; 7 * INITIAL_HEIGHT]);
         Self {
             rock_cycle: Cycler::new(FIELD_ROCKS.as_slice()),
             wind_cycle: Cycler::new(wind),
@@ -115,10 +133,21 @@ impl<'a> Field<'a> {
         }
         unreachable!()
     }
+    /// Doubles the field until it has at least `height` lines
+    fn grow_to(&mut self, height: usize) {
+        let mut new_height = self.field.height;
+        while new_height < height {
+            new_height *= 2;
+        }
+        self.field.grid_raw.resize(7 * new_height, false);
+        self.field.height = new_height;
+    }
     pub fn drop_rock(&mut self) {
         let mut x: usize = 2;
         let mut y: usize = self.height() + 3;
         let rock = self.rock_cycle.next();
+        // room for the rock, and an empty line above it for `height`
+        self.grow_to(y + rock.shape.height + 1);
         loop {
             {
                 let nx = match self.wind_cycle.next() {
@@ -160,28 +189,31 @@ impl<'a> Field<'a> {
             }
         }
     }
-    pub fn height_line(&self) -> [u8; 7] {
-        let height = self.height() - 1;
-        let mut res: [Option<u8>; 7] = [None; 7];
-        for line_index in 0.. {
-            if height < line_index {
-                res.iter_mut()
-                    .filter(|r| r.is_none())
-                    .for_each(|r| *r = Some(line_index as u8));
-                break;
+    /// The empty cells a falling rock can still reach, as their column and
+    /// depth below the top of the tower, down to [`SURFACE_DEPTH`]. Nothing
+    /// below them can change any more, so two fields with the same surface grow
+    /// the same way
+    pub fn surface(&self) -> Vec<(usize, usize)> {
+        let height = self.height();
+        let mut reached = HashSet::new();
+        let mut todo: Vec<(usize, usize)> = (0..7).map(|x| (x, height)).collect();
+        while let Some((x, y)) = todo.pop() {
+            if *self.field.get(x, y) || !reached.insert((x, height - y)) {
+                continue;
             }
-            let line = self.field.get_line(height - line_index);
-            for i in 0..line.len() {
-                if res[i].is_none() && line[i] {
-                    res[i] = Some(line_index as u8)
-                }
+            if x > 0 {
+                todo.push((x - 1, y));
+            }
+            if x < 6 {
+                todo.push((x + 1, y));
+            }
+            if y > 0 && height - y < SURFACE_DEPTH {
+                todo.push((x, y - 1));
             }
         }
-        let mut real_res = [0; 7];
-        for (i, r) in res.into_iter().enumerate() {
-            real_res[i] = r.unwrap()
-        }
-        real_res
+        let mut surface: Vec<_> = reached.into_iter().collect();
+        surface.sort_unstable();
+        surface
     }
     pub fn state(&self) -> FieldState {
         FieldState::new(self)
@@ -192,7 +224,7 @@ impl<'a> Field<'a> {
 struct FieldState {
     rock_index: usize,
     wind_index: usize,
-    height_line: [u8; 7],
+    surface: Vec<(usize, usize)>,
 }
 
 impl FieldState {
@@ -200,32 +232,60 @@ impl FieldState {
         Self {
             rock_index: field.rock_cycle.index,
             wind_index: field.wind_cycle.index,
-            height_line: field.height_line(),
+            surface: field.surface(),
         }
     }
 }
 
-fn parse_input(input: &str) -> Vec<Wind> {
+/// The jets of hot gas, in the order they push
+pub fn parse(input: &str) -> Result<Vec<Wind>, ParseError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(ParseError::new("no jet of gas"));
+    }
     input
-        .trim()
         .chars()
         .map(|c| match c {
-            '>' => Wind::Right,
-            '<' => Wind::Left,
-            _ => unimplemented!(),
+            '>' => Ok(Wind::Right),
+            '<' => Ok(Wind::Left),
+            c => Err(ParseError(c.to_string())),
         })
         .collect()
 }
 
-pub fn day17_1(input: &str) -> usize {
-    let wind = parse_input(input);
-    let mut field = Field::new(&wind);
-    for _ in 0..2022 {
+/// How many rocks fall in each part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    /// Every one of these is simulated
+    pub part1_rocks: usize,
+    pub part2_rocks: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_rocks: 2022,
+            part2_rocks: 1_000_000_000_000,
+        }
+    }
+}
+
+fn tower_height(wind: &[Wind], rocks: usize) -> usize {
+    let mut field = Field::new(wind);
+    for _ in 0..rocks {
         field.drop_rock();
     }
     field.height()
 }
 
+pub fn part1(wind: &[Wind]) -> usize {
+    part1_with(wind, &Params::default())
+}
+
+pub fn part1_with(wind: &[Wind], params: &Params) -> usize {
+    tower_height(wind, params.part1_rocks)
+}
+
 fn get_first_repetition(field: &mut Field) -> ((usize, usize), (usize, usize)) {
     let mut found_states: HashMap<FieldState, Vec<(usize, usize)>> = HashMap::new();
 
@@ -233,36 +293,181 @@ fn get_first_repetition(field: &mut Field) -> ((usize, usize), (usize, usize)) {
         field.drop_rock();
         match found_states.entry(field.state()) {
             std::collections::hash_map::Entry::Occupied(mut e) => {
-                e.get_mut().push((i, field.aprox_height));
+                e.get_mut().push((i, field.height()));
                 let rep = e.get();
                 if rep.len() >= 2 {
-                    let ((i1, h1), (i2, h2)) = (rep[0], rep[1]);
-                    let adjust = field.height() - h2;
-                    return ((i1, h1 + adjust), (i2, h2 + adjust));
+                    return (rep[0], rep[1]);
                 }
             }
             std::collections::hash_map::Entry::Vacant(e) => {
-                e.insert(vec![(i, field.aprox_height)]);
+                e.insert(vec![(i, field.height())]);
             }
         }
     }
     unreachable!()
 }
 
-pub fn day17_2(input: &str) -> usize {
-    let wind = parse_input(input);
-    let mut field = Field::new(&wind);
+pub fn part2(wind: &[Wind]) -> usize {
+    part2_with(wind, &Params::default())
+}
+
+pub fn part2_with(wind: &[Wind], params: &Params) -> usize {
+    let mut field = Field::new(wind);
     let ((i1, h1), (i2, h2)) = get_first_repetition(&mut field);
+    let rocks = params.part2_rocks;
+    if rocks <= i2 {
+        // the tower is done before it starts repeating
+        return tower_height(wind, rocks);
+    }
 
-    let cycles_remaining = (1_000_000_000_000 - i2) / (i2 - i1);
+    // i2 is the index of the rock that repeated, so i2 + 1 rocks have fallen
+    let cycles_remaining = (rocks - i2 - 1) / (i2 - i1);
     let height_skip = cycles_remaining * (h2 - h1);
     let i_skip = cycles_remaining * (i2 - i1);
 
-    for _ in (i2 + i_skip + 1)..1_000_000_000_000 {
+    for _ in (i2 + i_skip + 1)..rocks {
         field.drop_rock();
     }
 
     field.height() + height_skip
-} // 1525364434259 wrong
+}
+
+pub fn day17_1(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|jets| part1(&jets))
+}
+
+pub fn day17_1_with(input: &str, params: &Params) -> Result<usize, ParseError> {
+    parse(input).map(|jets| part1_with(&jets, params))
+}
+
+pub fn day17_2(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|jets| part2(&jets))
+}
+
+pub fn day17_2_with(input: &str, params: &Params) -> Result<usize, ParseError> {
+    parse(input).map(|jets| part2_with(&jets, params))
+}
+
+/// Generates `size` random jets, for the scaling benchmark
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let mut jets: String = (0..size)
+        .map(|_| if rng.gen() { '<' } else { '>' })
+        .collect();
+    jets.push('\n');
+    jets
+}
 
-const _TEST_INPUT: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    use std::collections::HashSet;
+
+    /// The cells of each rock, from its bottom left corner
+    const ROCKS: [&[(i64, i64)]; 5] = [
+        &[(0, 0), (1, 0), (2, 0), (3, 0)],
+        &[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)],
+        &[(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)],
+        &[(0, 0), (0, 1), (0, 2), (0, 3)],
+        &[(0, 0), (1, 0), (0, 1), (1, 1)],
+    ];
+
+    fn fits(stopped: &HashSet<(i64, i64)>, rock: &[(i64, i64)], (x, y): (i64, i64)) -> bool {
+        rock.iter().all(|&(dx, dy)| {
+            (0..7).contains(&(x + dx)) && y + dy >= 0 && !stopped.contains(&(x + dx, y + dy))
+        })
+    }
+
+    /// Drops every one of the rocks
+    pub fn tower_height(input: &str, rocks: usize) -> usize {
+        let mut jets = input
+            .trim()
+            .bytes()
+            .map(|jet| if jet == b'<' { -1 } else { 1 })
+            .cycle();
+        let mut stopped = HashSet::new();
+        let mut height = 0;
+        for rock in ROCKS.iter().cycle().take(rocks) {
+            let (mut x, mut y) = (2, height + 3);
+            loop {
+                let pushed = x + jets.next().unwrap();
+                if fits(&stopped, rock, (pushed, y)) {
+                    x = pushed;
+                }
+                if !fits(&stopped, rock, (x, y - 1)) {
+                    break;
+                }
+                y -= 1;
+            }
+            for &(dx, dy) in rock.iter() {
+                stopped.insert((x + dx, y + dy));
+                height = height.max(y + dy + 1);
+            }
+        }
+        height as usize
+    }
+
+    pub fn day17_1(input: &str) -> usize {
+        tower_height(input, 2022)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_parsers, day17_1, day17_1_with, day17_2, day17_2_with, reference, Params, ParseError,
+    };
+    use proptest::prelude::*;
+
+    const INPUT: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
+
+    const FEW_ROCKS: Params = Params {
+        part1_rocks: 2022,
+        part2_rocks: 50,
+    };
+
+    example_tests! {
+        test_day17_1: day17_1(INPUT) => Ok(3068),
+        test_day17_2: day17_2(INPUT) => Ok(1514285714288),
+        test_day17_2_before_repeating: day17_2_with(INPUT, &FEW_ROCKS) => Ok(78),
+        test_day17_2_like_part1: day17_2_with(INPUT, &Params { part2_rocks: 2022, ..FEW_ROCKS }) => Ok(3068),
+        // a whole number of cycles after the repetition, checked by dropping every rock
+        test_day17_2_after_repeating: day17_2_with(INPUT, &Params { part2_rocks: 3562, ..FEW_ROCKS }) => Ok(5400),
+        // taller than the field starts, so it has to grow
+        test_day17_1_growing: day17_1_with(INPUT, &Params { part1_rocks: 10000, ..FEW_ROCKS }) => Ok(15148),
+    }
+
+    /// Few enough rocks for the reference to drop them all, but enough for the
+    /// tower to repeat
+    const MANY_ROCKS: Params = Params {
+        part1_rocks: 2022,
+        part2_rocks: 5000,
+    };
+
+    fn day17_2_many_rocks(input: &str) -> Result<usize, ParseError> {
+        day17_2_with(input, &MANY_ROCKS)
+    }
+
+    fn reference_day17_2_many_rocks(input: &str) -> usize {
+        reference::tower_height(input, MANY_ROCKS.part2_rocks)
+    }
+
+    fn jets() -> impl Strategy<Value = String> {
+        "[<>]{1,40}"
+    }
+
+    differential_tests! {
+        cases = 16;
+        test_day17_1_reference(jets()): day17_1 == reference::day17_1,
+        test_day17_2_reference(jets()): day17_2_many_rocks == reference_day17_2_many_rocks,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[<>\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,9 +1,14 @@
-#![allow(dead_code)]
+//! Day 18: Boiling Boulders
+//!
+//! `parse` reads the [`Coord3d`] of each cube of lava.
 
-use std::{collections::HashSet, num::ParseIntError, str::FromStr};
+use std::{collections::HashSet, str::FromStr};
+
+#[cfg(feature = "scaling")]
+use rand::Rng;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
-struct Coord3d {
+pub struct Coord3d {
     x: usize,
     y: usize,
     z: usize,
@@ -47,33 +52,43 @@ impl<T> Grid3d<T> {
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseCoordError;
+
 impl FromStr for Coord3d {
-    type Err = ParseIntError;
+    type Err = ParseCoordError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut nums = s.split(',').map(|n| n.parse::<usize>());
-        Ok(Self {
-            x: nums.next().unwrap()? + 1,
-            y: nums.next().unwrap()? + 1,
-            z: nums.next().unwrap()? + 1,
-        })
+        // shifted by one so that every cube has neighbours in the grid
+        let mut nums = s.split(',').map(|n| {
+            n.parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_add(1))
+                .ok_or(ParseCoordError)
+        });
+        let mut next = || nums.next().ok_or(ParseCoordError)?;
+        let coord = Self {
+            x: next()?,
+            y: next()?,
+            z: next()?,
+        };
+        match nums.next() {
+            None => Ok(coord),
+            Some(_) => Err(ParseCoordError),
+        }
     }
 }
 
-fn parse_input(input: &str) -> Vec<Coord3d> {
-    input
-        .lines()
-        .map(Coord3d::from_str)
-        .collect::<Result<_, _>>()
-        .unwrap()
+/// The position of every cube of the droplet
+pub fn parse(input: &str) -> Result<Vec<Coord3d>, ParseCoordError> {
+    input.lines().map(Coord3d::from_str).collect()
 }
 
-pub fn day18_1(input: &str) -> usize {
-    let coords = parse_input(input);
+pub fn part1(coords: &[Coord3d]) -> usize {
     let mut field = Grid3d::from_vec(22, 22, 22, vec![false; 22 * 22 * 22]);
     let mut surface_area = 0;
     for c in coords {
-        *field.get_mut_unchecked(&c) = true;
+        *field.get_mut_unchecked(c) = true;
         surface_area += 6;
         for x in [c.x - 1, c.x + 1] {
             if *field.get_unchecked(&Coord3d { x, y: c.y, z: c.z }) {
@@ -94,11 +109,10 @@ pub fn day18_1(input: &str) -> usize {
     surface_area
 }
 
-pub fn day18_2(input: &str) -> usize {
-    let coords = parse_input(input);
+pub fn part2(coords: &[Coord3d]) -> usize {
     let mut field = Grid3d::from_vec(22, 22, 22, vec![false; 22 * 22 * 22]);
     for c in coords {
-        *field.get_mut_unchecked(&c) = true;
+        *field.get_mut_unchecked(c) = true;
     }
     let mut surface_area = 0;
     let mut coords_next = Vec::with_capacity(20 * 20 * 20);
@@ -161,7 +175,114 @@ pub fn day18_2(input: &str) -> usize {
     surface_area
 }
 
-const _TEST_INPUT: &str = "2,2,2
+pub fn day18_1(input: &str) -> Result<usize, ParseCoordError> {
+    parse(input).map(|droplet| part1(&droplet))
+}
+
+pub fn day18_2(input: &str) -> Result<usize, ParseCoordError> {
+    parse(input).map(|droplet| part2(&droplet))
+}
+
+/// Generates a droplet of `size` cubes, for the scaling benchmark. The solver
+/// works on a fixed 22 wide grid, so at most 20 * 20 * 20 cubes fit
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let mut cubes = HashSet::new();
+    while cubes.len() < size.min(20 * 20 * 20) {
+        cubes.insert((
+            rng.gen_range(0..20),
+            rng.gen_range(0..20),
+            rng.gen_range(0..20),
+        ));
+    }
+    cubes
+        .iter()
+        .map(|(x, y, z)| format!("{x},{y},{z}\n"))
+        .collect()
+}
+
+/// Checks that a cube parsed from arbitrary text prints back to an equal one,
+/// and that `parse` doesn't panic
+#[cfg(any(test, fuzzing))]
+pub(crate) fn check_parsers(input: &str) {
+    let _ = parse(input);
+    if let Ok(c) = Coord3d::from_str(input) {
+        let printed = format!("{},{},{}", c.x - 1, c.y - 1, c.z - 1);
+        assert_eq!(Coord3d::from_str(&printed), Ok(c));
+    }
+}
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    use std::collections::HashSet;
+
+    type Cube = (i32, i32, i32);
+
+    fn parse(input: &str) -> HashSet<Cube> {
+        input
+            .lines()
+            .map(|line| {
+                let mut coords = line.split(',').map(|n| n.parse().unwrap());
+                let mut next = || coords.next().unwrap();
+                (next(), next(), next())
+            })
+            .collect()
+    }
+
+    fn neighbours((x, y, z): Cube) -> [Cube; 6] {
+        [
+            (x - 1, y, z),
+            (x + 1, y, z),
+            (x, y - 1, z),
+            (x, y + 1, z),
+            (x, y, z - 1),
+            (x, y, z + 1),
+        ]
+    }
+
+    pub fn day18_1(input: &str) -> usize {
+        let cubes = parse(input);
+        cubes
+            .iter()
+            .flat_map(|&cube| neighbours(cube))
+            .filter(|n| !cubes.contains(n))
+            .count()
+    }
+
+    pub fn day18_2(input: &str) -> usize {
+        let cubes = parse(input);
+        // flood the air in the droplet's bounding box, grown by one so it surrounds it
+        let max = cubes
+            .iter()
+            .map(|&(x, y, z)| x.max(y).max(z))
+            .max()
+            .unwrap()
+            + 1;
+        let in_box = |&(x, y, z): &Cube| [x, y, z].iter().all(|c| (-1..=max).contains(c));
+        let mut outside = HashSet::from([(-1, -1, -1)]);
+        let mut todo = vec![(-1, -1, -1)];
+        let mut faces = 0;
+        while let Some(air) = todo.pop() {
+            for n in neighbours(air).into_iter().filter(in_box) {
+                if cubes.contains(&n) {
+                    faces += 1;
+                } else if outside.insert(n) {
+                    todo.push(n);
+                }
+            }
+        }
+        faces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::{check_parsers, day18_1, day18_2, reference};
+
+    const INPUT: &str = "2,2,2
 1,2,2
 3,2,2
 2,1,2
@@ -175,3 +296,30 @@ const _TEST_INPUT: &str = "2,2,2
 2,1,5
 2,3,5
 ";
+
+    example_tests! {
+        test_day18_1: day18_1(INPUT) => Ok(64),
+        test_day18_2: day18_2(INPUT) => Ok(58),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,32}|[0-9,]{0,32}") {
+            check_parsers(&input);
+        }
+    }
+
+    fn droplet() -> impl Strategy<Value = String> {
+        prop::collection::btree_set((0..8, 0..8, 0..8), 1..60).prop_map(|cubes| {
+            cubes
+                .iter()
+                .map(|(x, y, z)| format!("{x},{y},{z}\n"))
+                .collect()
+        })
+    }
+
+    differential_tests! {
+        test_day18_1_reference(droplet()): day18_1 == reference::day18_1,
+        test_day18_2_reference(droplet()): day18_2 == reference::day18_2,
+    }
+}
@@ -1,6 +1,11 @@
-#![allow(dead_code)]
+//! Day 19: Not Enough Minerals
+//!
+//! `parse` reads every [`Blueprint`], which finds the most geodes it can open
+//! in a given time.
 
+use crate::parse::{OrUnexpected, ParseError};
 use arrayvec::ArrayVec;
+use harness::runner::cancelled;
 
 #[derive(Debug, Default, Clone, Copy)]
 struct Resources {
@@ -32,8 +37,10 @@ impl Resources {
 }
 
 #[derive(Debug)]
-struct BlueprintSim<'a, const N: usize> {
+struct BlueprintSim<'a> {
     blueprint: &'a Blueprint,
+    /// Time before the elephants get hungry
+    minutes: i32,
     resources: Resources,
     minute: i32,
     ore_robots: i32,
@@ -42,10 +49,11 @@ struct BlueprintSim<'a, const N: usize> {
     geode_robots: i32,
 }
 
-impl<'a, const N: usize> BlueprintSim<'a, N> {
-    pub fn new(blueprint: &'a Blueprint) -> Self {
+impl<'a> BlueprintSim<'a> {
+    pub fn new(blueprint: &'a Blueprint, minutes: i32) -> Self {
         Self {
             blueprint,
+            minutes,
             resources: Default::default(),
             minute: 0,
             ore_robots: 1,
@@ -65,7 +73,7 @@ impl<'a, const N: usize> BlueprintSim<'a, N> {
 
     pub fn next_states(&self) -> ArrayVec<Self, 4> {
         let mut next_states = ArrayVec::new();
-        if self.minute + 1 >= N as i32 {
+        if self.minute + 1 >= self.minutes {
             return next_states;
         }
         if self.obsidian_robots > 0 {
@@ -85,8 +93,7 @@ impl<'a, const N: usize> BlueprintSim<'a, N> {
                         .add_obsidian(-self.blueprint.geode_robot_obsidian_cost),
                     ..*self
                 });
-                return next_states;
-            } else if time_needed + self.minute < N as i32 {
+            } else if time_needed + self.minute < self.minutes {
                 next_states.push(Self {
                     minute: self.minute + time_needed,
                     geode_robots: self.geode_robots + 1,
@@ -98,7 +105,14 @@ impl<'a, const N: usize> BlueprintSim<'a, N> {
                 });
             }
         }
-        if self.clay_robots > 0 {
+        let bp = self.blueprint;
+        if self.clay_robots > 0
+            && self.needs_more(
+                self.obsidian_robots,
+                self.resources.obsidian,
+                bp.geode_robot_obsidian_cost,
+            )
+        {
             let ore_left_for_obs = self.blueprint.obisidian_robot_ore_cost - self.resources.ores;
             let time_for_ore = (ore_left_for_obs - 1) / self.ore_robots + 2;
             let clay_left_for_obs = self.blueprint.obisidian_robot_clay_cost - self.resources.clay;
@@ -114,8 +128,7 @@ impl<'a, const N: usize> BlueprintSim<'a, N> {
                         .add_clay(-self.blueprint.obisidian_robot_clay_cost),
                     ..*self
                 });
-                return next_states;
-            } else if time_needed + self.minute < N as i32 {
+            } else if time_needed + self.minute < self.minutes {
                 next_states.push(Self {
                     minute: self.minute + time_needed,
                     obsidian_robots: self.obsidian_robots + 1,
@@ -127,7 +140,11 @@ impl<'a, const N: usize> BlueprintSim<'a, N> {
                 })
             }
         }
-        {
+        if self.needs_more(
+            self.clay_robots,
+            self.resources.clay,
+            bp.obisidian_robot_clay_cost,
+        ) {
             let ore_left_for_clay = self.blueprint.clay_robot_ore_cost - self.resources.ores;
             let time_for_ore = (ore_left_for_clay - 1) / self.ore_robots + 2;
             if ore_left_for_clay <= 0 {
@@ -139,8 +156,7 @@ impl<'a, const N: usize> BlueprintSim<'a, N> {
                         .add_ores(-self.blueprint.clay_robot_ore_cost),
                     ..*self
                 });
-                return next_states;
-            } else if time_for_ore + self.minute < N as i32 {
+            } else if time_for_ore + self.minute < self.minutes {
                 next_states.push(Self {
                     minute: self.minute + time_for_ore,
                     clay_robots: self.clay_robots + 1,
@@ -151,7 +167,7 @@ impl<'a, const N: usize> BlueprintSim<'a, N> {
                 })
             }
         }
-        {
+        if self.needs_more(self.ore_robots, self.resources.ores, bp.max_ore_cost()) {
             let ore_left_for_ore = self.blueprint.ore_robot_ore_cost - self.resources.ores;
             let time_for_ore = (ore_left_for_ore - 1) / self.ore_robots + 2;
             if ore_left_for_ore <= 0 {
@@ -163,7 +179,7 @@ impl<'a, const N: usize> BlueprintSim<'a, N> {
                         .add_ores(-self.blueprint.ore_robot_ore_cost),
                     ..*self
                 })
-            } else if time_for_ore + self.minute < N as i32 {
+            } else if time_for_ore + self.minute < self.minutes {
                 next_states.push(Self {
                     minute: self.minute + time_for_ore,
                     ore_robots: self.ore_robots + 1,
@@ -178,15 +194,53 @@ impl<'a, const N: usize> BlueprintSim<'a, N> {
         next_states
     }
     const fn time_left(&self) -> i32 {
-        N as i32 - self.minute
+        self.minutes - self.minute
     }
     pub const fn geodes_by_end(&self) -> i32 {
         self.resources.geodes + self.geode_robots * self.time_left()
     }
+    /// Whether another robot collecting a resource could still help: robots
+    /// can spend at most `max_cost` of it a minute, so once the robots and the
+    /// stock cover that until the end, more would be wasted
+    const fn needs_more(&self, robots: i32, stock: i32, max_cost: i32) -> bool {
+        let time_left = self.time_left();
+        robots * time_left + stock < max_cost * time_left
+    }
+    /// Geodes by the end if ore were free and every robot the clay and
+    /// obsidian allow were built each minute, all at once
+    pub fn geodes_upper_bound(&self) -> i32 {
+        let bp = self.blueprint;
+        let Resources {
+            mut clay,
+            mut obsidian,
+            mut geodes,
+            ..
+        } = self.resources;
+        let (mut clay_robots, mut obsidian_robots, mut geode_robots) =
+            (self.clay_robots, self.obsidian_robots, self.geode_robots);
+        for _ in 0..self.time_left() {
+            let builds_obsidian = clay >= bp.obisidian_robot_clay_cost;
+            let builds_geode = obsidian >= bp.geode_robot_obsidian_cost;
+            clay += clay_robots;
+            obsidian += obsidian_robots;
+            geodes += geode_robots;
+            if builds_obsidian {
+                clay -= bp.obisidian_robot_clay_cost;
+                obsidian_robots += 1;
+            }
+            if builds_geode {
+                obsidian -= bp.geode_robot_obsidian_cost;
+                geode_robots += 1;
+            }
+            clay_robots += 1;
+        }
+        geodes
+    }
 }
 
+/// What each robot costs
 #[derive(Debug)]
-struct Blueprint {
+pub struct Blueprint {
     ore_robot_ore_cost: i32,
     clay_robot_ore_cost: i32,
     obisidian_robot_ore_cost: i32,
@@ -196,76 +250,259 @@ struct Blueprint {
 }
 
 impl Blueprint {
-    pub fn simulate_most_geodes<const N: usize>(&self) -> i32 {
-        let mut states = vec![BlueprintSim::<N>::new(self)];
+    /// No robot needs more ore than this, so more ore robots would be wasted
+    fn max_ore_cost(&self) -> i32 {
+        self.ore_robot_ore_cost
+            .max(self.clay_robot_ore_cost)
+            .max(self.obisidian_robot_ore_cost)
+            .max(self.geode_robot_ore_cost)
+    }
+    /// The most geodes a factory following this blueprint can open in
+    /// `minutes`, starting with a single ore robot
+    pub fn simulate_most_geodes(&self, minutes: i32) -> i32 {
+        let mut states = vec![BlueprintSim::new(self, minutes)];
         let mut most_geodes = 0;
-        let mut best_records = [(0, 0); N];
         while let Some(state) = states.pop() {
+            // the runner gave up on us, what we found so far will do
+            if cancelled() {
+                break;
+            }
+            if state.geodes_upper_bound() <= most_geodes {
+                continue;
+            }
             let next_states = state.next_states();
             if next_states.is_empty() && state.geodes_by_end() > most_geodes {
                 most_geodes = state.geodes_by_end();
                 continue;
             }
-            for stt in next_states {
-                let (geo_record, robo_record) = best_records[stt.minute as usize];
-                if stt.resources.geodes >= geo_record || stt.geode_robots >= robo_record {
-                    best_records[stt.minute as usize] = (
-                        stt.resources.geodes.max(geo_record),
-                        stt.geode_robots.max(robo_record),
-                    );
-                    states.push(stt);
-                }
-            }
+            states.extend(next_states);
         }
         most_geodes
     }
 }
 
-fn parse_input(input: &str) -> Vec<Blueprint> {
+/// The robot costs of each blueprint, in order
+pub fn parse(input: &str) -> Result<Vec<Blueprint>, ParseError> {
+    // small enough that the resources piling up can't overflow
+    let cost = |n: &str| n.parse::<u16>().map(i32::from).or_unexpected(n);
     input
         .lines()
         .map(|line| {
-            let rest = line.strip_prefix("Blueprint ").unwrap();
-            let (_id, rest) = rest.split_once(": Each ore robot costs ").unwrap();
-            let (ore_robot_cost, rest) = rest.split_once(" ore. Each clay robot costs ").unwrap();
-            let (clay_robot_cost, rest) =
-                rest.split_once(" ore. Each obsidian robot costs ").unwrap();
-            let (obsidian_robot_ore_cost, rest) = rest.split_once(" ore and ").unwrap();
-            let (obsidian_robot_clay_cost, rest) =
-                rest.split_once(" clay. Each geode robot costs ").unwrap();
-            let (geode_robot_ore_cost, rest) = rest.split_once(" ore and ").unwrap();
-            let (geode_robot_obsidian_cost, _) = rest.split_once(" obsidian").unwrap();
-            Blueprint {
-                ore_robot_ore_cost: ore_robot_cost.parse().unwrap(),
-                clay_robot_ore_cost: clay_robot_cost.parse().unwrap(),
-                obisidian_robot_ore_cost: obsidian_robot_ore_cost.parse().unwrap(),
-                obisidian_robot_clay_cost: obsidian_robot_clay_cost.parse().unwrap(),
-                geode_robot_ore_cost: geode_robot_ore_cost.parse().unwrap(),
-                geode_robot_obsidian_cost: geode_robot_obsidian_cost.parse().unwrap(),
-            }
+            let rest = line.strip_prefix("Blueprint ").or_unexpected(line)?;
+            let (_id, rest) = rest
+                .split_once(": Each ore robot costs ")
+                .or_unexpected(line)?;
+            let (ore_robot_cost, rest) = rest
+                .split_once(" ore. Each clay robot costs ")
+                .or_unexpected(line)?;
+            let (clay_robot_cost, rest) = rest
+                .split_once(" ore. Each obsidian robot costs ")
+                .or_unexpected(line)?;
+            let (obsidian_robot_ore_cost, rest) =
+                rest.split_once(" ore and ").or_unexpected(line)?;
+            let (obsidian_robot_clay_cost, rest) = rest
+                .split_once(" clay. Each geode robot costs ")
+                .or_unexpected(line)?;
+            let (geode_robot_ore_cost, rest) = rest.split_once(" ore and ").or_unexpected(line)?;
+            let (geode_robot_obsidian_cost, _) =
+                rest.split_once(" obsidian").or_unexpected(line)?;
+            Ok(Blueprint {
+                ore_robot_ore_cost: cost(ore_robot_cost)?,
+                clay_robot_ore_cost: cost(clay_robot_cost)?,
+                obisidian_robot_ore_cost: cost(obsidian_robot_ore_cost)?,
+                obisidian_robot_clay_cost: cost(obsidian_robot_clay_cost)?,
+                geode_robot_ore_cost: cost(geode_robot_ore_cost)?,
+                geode_robot_obsidian_cost: cost(geode_robot_obsidian_cost)?,
+            })
         })
         .collect()
 }
 
-pub fn day19_1(input: &str) -> usize {
-    let blueprints = parse_input(input);
+/// The time and blueprints of each part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub part1_minutes: i32,
+    pub part2_minutes: i32,
+    /// Part 2 only uses the first blueprints, the rest were eaten
+    pub part2_blueprints: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_minutes: 24,
+            part2_minutes: 32,
+            part2_blueprints: 3,
+        }
+    }
+}
+
+pub fn part1(blueprints: &[Blueprint]) -> usize {
+    part1_with(blueprints, &Params::default())
+}
+
+pub fn part1_with(blueprints: &[Blueprint], params: &Params) -> usize {
     blueprints
-        .into_iter()
-        .map(|bp| bp.simulate_most_geodes::<24>())
+        .iter()
+        .map(|bp| bp.simulate_most_geodes(params.part1_minutes))
         .enumerate()
         .fold(0, |acc, (i, geo)| acc + (i + 1) * geo as usize)
 }
 
-pub fn day19_2(input: &str) -> i32 {
-    let blueprints = parse_input(input);
+pub fn part2(blueprints: &[Blueprint]) -> i32 {
+    part2_with(blueprints, &Params::default())
+}
+
+pub fn part2_with(blueprints: &[Blueprint], params: &Params) -> i32 {
     blueprints
-        .into_iter()
-        .take(3)
-        .map(|bp| bp.simulate_most_geodes::<32>())
+        .iter()
+        .take(params.part2_blueprints)
+        .map(|bp| bp.simulate_most_geodes(params.part2_minutes))
         .product()
     // .sum()
 }
 
-const _TEST_INPUT: &str = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
+pub fn day19_1(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|blueprints| part1(&blueprints))
+}
+
+pub fn day19_1_with(input: &str, params: &Params) -> Result<usize, ParseError> {
+    parse(input).map(|blueprints| part1_with(&blueprints, params))
+}
+
+pub fn day19_2(input: &str) -> Result<i32, ParseError> {
+    parse(input).map(|blueprints| part2(&blueprints))
+}
+
+pub fn day19_2_with(input: &str, params: &Params) -> Result<i32, ParseError> {
+    parse(input).map(|blueprints| part2_with(&blueprints, params))
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    struct Blueprint {
+        /// Cost of each robot in ore, clay and obsidian
+        costs: [[i32; 3]; 4],
+    }
+
+    fn parse(input: &str) -> Vec<Blueprint> {
+        input
+            .lines()
+            .map(|line| {
+                let n: Vec<i32> = line
+                    .split(|c: char| !c.is_ascii_digit())
+                    .filter(|n| !n.is_empty())
+                    .map(|n| n.parse().unwrap())
+                    .collect();
+                Blueprint {
+                    costs: [[n[1], 0, 0], [n[2], 0, 0], [n[3], n[4], 0], [n[5], 0, n[6]]],
+                }
+            })
+            .collect()
+    }
+
+    /// Tries building every robot next, waiting as long as needed for its
+    /// resources, and also building nothing more. The only shortcut is never
+    /// collecting more of a resource per minute than a robot can spend.
+    /// Robots and resources are ore, clay, obsidian and geode
+    fn most_geodes(bp: &Blueprint, time_left: i32, robots: [i32; 4], stock: [i32; 4]) -> i32 {
+        let mut best = stock[3] + robots[3] * time_left;
+        for (robot, cost) in bp.costs.iter().enumerate() {
+            if robot < 3 && bp.costs.iter().all(|c| robots[robot] >= c[robot]) {
+                continue;
+            }
+            let mut wait = 0;
+            for r in 0..3 {
+                if cost[r] > stock[r] {
+                    if robots[r] == 0 {
+                        wait = time_left;
+                    } else {
+                        wait = wait.max((cost[r] - stock[r] + robots[r] - 1) / robots[r]);
+                    }
+                }
+            }
+            // a robot finished on the last minute doesn't have time to collect anything
+            let elapsed = wait + 1;
+            if elapsed >= time_left {
+                continue;
+            }
+            let mut next_stock = stock;
+            for r in 0..4 {
+                next_stock[r] += robots[r] * elapsed;
+            }
+            for r in 0..3 {
+                next_stock[r] -= cost[r];
+            }
+            let mut next_robots = robots;
+            next_robots[robot] += 1;
+            best = best.max(most_geodes(
+                bp,
+                time_left - elapsed,
+                next_robots,
+                next_stock,
+            ));
+        }
+        best
+    }
+
+    pub fn day19_1(input: &str) -> usize {
+        parse(input)
+            .iter()
+            .enumerate()
+            .map(|(i, bp)| (i + 1) * most_geodes(bp, 24, [1, 0, 0, 0], [0; 4]) as usize)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::{check_parsers, day19_1, day19_2, day19_2_with, reference, Params};
+
+    const INPUT: &str = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
 Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.
 ";
+
+    example_tests! {
+        test_day19_1: day19_1(INPUT) => Ok(33),
+        test_day19_2: day19_2(INPUT) => Ok(56 * 62),
+        test_day19_2_first_blueprint: day19_2_with(INPUT, &Params { part2_blueprints: 1, ..Params::default() }) => Ok(56),
+    }
+
+    fn blueprints() -> impl Strategy<Value = String> {
+        let blueprint = (2..5, 2..5, 2..5, 4..15, 2..5, 4..15);
+        prop::collection::vec(blueprint, 1..3).prop_map(|blueprints| {
+            blueprints
+                .iter()
+                .enumerate()
+                .map(
+                    |(i, (ore, clay, obsidian_ore, obsidian_clay, geode_ore, geode_obsidian))| {
+                        format!(
+                            "Blueprint {}: Each ore robot costs {ore} ore. \
+                        Each clay robot costs {clay} ore. \
+                        Each obsidian robot costs {obsidian_ore} ore and {obsidian_clay} clay. \
+                        Each geode robot costs {geode_ore} ore and {geode_obsidian} obsidian.\n",
+                            i + 1
+                        )
+                    },
+                )
+                .collect()
+        })
+    }
+
+    differential_tests! {
+        cases = 16;
+        test_day19_1_reference(blueprints()): day19_1 == reference::day19_1,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[Blueprint: Eachorbtcsly.dgn0-9\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,19 +1,26 @@
-#![allow(dead_code)]
+//! Day 2: Rock Paper Scissors
+//!
+//! `parse` reads each round as the opponent's [`Move`], and the second column
+//! as both a [`Move`] (part 1) and an [`RPSResult`] (part 2).
 
-enum Move {
+#[cfg(feature = "scaling")]
+use rand::Rng;
+use std::convert::Infallible;
+
+pub enum Move {
     Rock,
     Paper,
     Scissors,
 }
 
-enum RPSResult {
+pub enum RPSResult {
     Win,
     Draw,
     Loss,
 }
 
 impl Move {
-    pub fn from_str(c: &str) -> Option<Move> {
+    fn from_str(c: &str) -> Option<Move> {
         match c {
             "A" | "X" => Some(Move::Rock),
             "B" | "Y" => Some(Move::Paper),
@@ -69,7 +76,7 @@ impl Move {
 }
 
 impl RPSResult {
-    pub fn from_str(c: &str) -> Option<RPSResult> {
+    fn from_str(c: &str) -> Option<RPSResult> {
         match c {
             "X" => Some(RPSResult::Loss),
             "Y" => Some(RPSResult::Draw),
@@ -86,32 +93,87 @@ impl RPSResult {
     }
 }
 
-pub fn day2_1(input: &str) -> i32 {
-    input
+/// Each round's opponent move, and the second column read both as a move and
+/// as the result to get
+pub fn parse(input: &str) -> Result<Vec<(Move, Move, RPSResult)>, Infallible> {
+    Ok(input
         .lines()
         .filter_map(|line| {
             let mut chars = line.split_whitespace();
             let opponent_move = Move::from_str(chars.next()?)?;
-            let my_move = Move::from_str(chars.next()?)?;
+            let second = chars.next()?;
+            Some((
+                opponent_move,
+                Move::from_str(second)?,
+                RPSResult::from_str(second)?,
+            ))
+        })
+        .collect())
+}
 
-            let win_score = my_move.vs(&opponent_move).score();
+pub fn part1(rounds: &[(Move, Move, RPSResult)]) -> i32 {
+    rounds
+        .iter()
+        .map(|(opponent_move, my_move, _)| {
+            let win_score = my_move.vs(opponent_move).score();
             let move_score = my_move.move_score();
-            Some(win_score + move_score)
+            win_score + move_score
         })
         .sum()
 }
 
-pub fn day2_2(input: &str) -> i32 {
-    input
-        .lines()
-        .filter_map(|line| {
-            let mut chars = line.split_whitespace();
-            let opponent_move = Move::from_str(chars.next()?)?;
-            let game_result = RPSResult::from_str(chars.next()?)?;
-
-            let move_score = opponent_move.move_for(&game_result).move_score();
+pub fn part2(rounds: &[(Move, Move, RPSResult)]) -> i32 {
+    rounds
+        .iter()
+        .map(|(opponent_move, _, game_result)| {
+            let move_score = opponent_move.move_for(game_result).move_score();
             let win_score = game_result.score();
-            Some(win_score + move_score)
+            win_score + move_score
         })
         .sum()
 }
+
+pub fn day2_1(input: &str) -> Result<i32, Infallible> {
+    parse(input).map(|rounds| part1(&rounds))
+}
+
+pub fn day2_2(input: &str) -> Result<i32, Infallible> {
+    parse(input).map(|rounds| part2(&rounds))
+}
+
+/// Generates a strategy guide of `size` rounds, for the scaling benchmark
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let opponent = rng.gen_range('A'..='C');
+            let second = rng.gen_range('X'..='Z');
+            format!("{opponent} {second}\n")
+        })
+        .collect()
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day2_1, day2_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "A Y
+B X
+C Z
+";
+
+    example_tests! {
+        test_day2_1: day2_1(INPUT) => Ok(15),
+        test_day2_2: day2_2(INPUT) => Ok(12),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[ABCXYZ \n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,8 +1,34 @@
-#![allow(dead_code)]
+//! Day 20: Grove Positioning System
+//!
+//! `parse` reads the numbers of the encrypted file.
 
-pub fn day20_1(input: &str) -> i32 {
-    let numbers: Vec<i16> = input.lines().map(|l| l.parse::<i16>().unwrap()).collect();
+use crate::parse::{OrUnexpected, ParseError};
+#[cfg(feature = "scaling")]
+use rand::Rng;
 
+/// The most numbers a file can have, and the largest number in it: the
+/// solvers add a number to a position in an `i16`
+const MAX_NUMBERS: usize = 1 << 14;
+
+/// The encrypted file's numbers, in order. There must be a zero, and at least
+/// another number to move around it
+pub fn parse(input: &str) -> Result<Vec<i16>, ParseError> {
+    let numbers: Vec<i16> = input
+        .lines()
+        .map(|l| {
+            l.parse::<i16>()
+                .ok()
+                .filter(|n| n.unsigned_abs() < MAX_NUMBERS as u16)
+                .or_unexpected(l)
+        })
+        .collect::<Result<_, _>>()?;
+    if numbers.len() < 2 || numbers.len() > MAX_NUMBERS || !numbers.contains(&0) {
+        return Err(ParseError::new(input));
+    }
+    Ok(numbers)
+}
+
+pub fn part1(numbers: &[i16]) -> i32 {
     let mut indices: Vec<_> = (0..numbers.len()).map(|n| n as i16).collect();
 
     for (original_i, n) in numbers.iter().copied().enumerate() {
@@ -26,11 +52,8 @@ pub fn day20_1(input: &str) -> i32 {
         .sum()
 }
 
-pub fn day20_2(input: &str) -> i64 {
-    let orig_numbers: Vec<i64> = input
-        .lines()
-        .map(|l| l.parse::<i64>().unwrap() * 811589153)
-        .collect();
+pub fn part2(numbers: &[i16]) -> i64 {
+    let orig_numbers: Vec<i64> = numbers.iter().map(|&n| n as i64 * 811589153).collect();
 
     let numbers: Vec<_> = orig_numbers
         .iter()
@@ -71,7 +94,159 @@ pub fn day20_2(input: &str) -> i64 {
         .sum()
 }
 
-const _TEST_INPUT: &str = "1
+/// Mixes `numbers` `rounds` times in a circular linked list, walking each
+/// number to its new place the shorter way around instead of rotating a
+/// slice of the positions
+fn mix_linked(numbers: &[i64], rounds: usize) -> Vec<i64> {
+    let len = numbers.len();
+    let mut next: Vec<usize> = (0..len).map(|i| (i + 1) % len).collect();
+    let mut prev: Vec<usize> = (0..len).map(|i| (i + len - 1) % len).collect();
+    for _ in 0..rounds {
+        for (i, &n) in numbers.iter().enumerate() {
+            // the other numbers make a circle of len - 1
+            let steps = n.rem_euclid(len as i64 - 1) as usize;
+            if steps == 0 {
+                continue;
+            }
+            let mut after = prev[i];
+            next[after] = next[i];
+            prev[next[i]] = after;
+            if steps <= (len - 1) / 2 {
+                for _ in 0..steps {
+                    after = next[after];
+                }
+            } else {
+                for _ in steps..len - 1 {
+                    after = prev[after];
+                }
+            }
+            next[i] = next[after];
+            prev[i] = after;
+            prev[next[after]] = i;
+            next[after] = i;
+        }
+    }
+    std::iter::successors(Some(0), |&i| Some(next[i]))
+        .take(len)
+        .map(|i| numbers[i])
+        .collect()
+}
+
+fn grove_coordinates(mixed: &[i64]) -> i64 {
+    let zero = mixed.iter().position(|&n| n == 0).unwrap();
+    [1000, 2000, 3000]
+        .into_iter()
+        .map(|m| mixed[(zero + m) % mixed.len()])
+        .sum()
+}
+
+/// [`part1`] mixing with [`mix_linked`]
+pub fn part1_linked(numbers: &[i16]) -> i32 {
+    let numbers: Vec<i64> = numbers.iter().map(|&n| n as i64).collect();
+    grove_coordinates(&mix_linked(&numbers, 1)) as i32
+}
+
+/// [`part2`] mixing with [`mix_linked`]
+pub fn part2_linked(numbers: &[i16]) -> i64 {
+    let numbers: Vec<i64> = numbers.iter().map(|&n| n as i64 * 811589153).collect();
+    grove_coordinates(&mix_linked(&numbers, 10))
+}
+
+pub fn day20_1(input: &str) -> Result<i32, ParseError> {
+    parse(input).map(|numbers| part1(&numbers))
+}
+
+pub fn day20_2(input: &str) -> Result<i64, ParseError> {
+    parse(input).map(|numbers| part2(&numbers))
+}
+
+pub fn day20_1_linked(input: &str) -> Result<i32, ParseError> {
+    parse(input).map(|numbers| part1_linked(&numbers))
+}
+
+pub fn day20_2_linked(input: &str) -> Result<i64, ParseError> {
+    parse(input).map(|numbers| part2_linked(&numbers))
+}
+
+/// Generates an encrypted file of `size` numbers, for the scaling benchmark.
+/// The solver keeps positions in an `i16`, so it can't take more than
+/// [`MAX_NUMBERS`]
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let size = size.clamp(2, MAX_NUMBERS);
+    let zero = rng.gen_range(0..size);
+    (0..size)
+        .map(|i| {
+            let n = if i == zero {
+                0
+            } else {
+                // the puzzle inputs have no zero apart from the one
+                let n = rng.gen_range(1..=10_000);
+                if rng.gen() {
+                    n
+                } else {
+                    -n
+                }
+            };
+            format!("{n}\n")
+        })
+        .collect()
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    fn parse(input: &str) -> Vec<i64> {
+        input.lines().map(|line| line.parse().unwrap()).collect()
+    }
+
+    /// Mixes by moving each number around the circle one swap at a time
+    fn mix(numbers: &[i64], rounds: usize) -> Vec<i64> {
+        let len = numbers.len();
+        // (original index, number) in circle order
+        let mut circle: Vec<(usize, i64)> = numbers.iter().copied().enumerate().collect();
+        for _ in 0..rounds {
+            for original in 0..len {
+                let mut pos = circle.iter().position(|&(i, _)| i == original).unwrap();
+                // going around past the len - 1 other numbers changes nothing
+                let steps = circle[pos].1.rem_euclid(len as i64 - 1);
+                for _ in 0..steps {
+                    let next = (pos + 1) % len;
+                    circle.swap(pos, next);
+                    pos = next;
+                }
+            }
+        }
+        circle.into_iter().map(|(_, n)| n).collect()
+    }
+
+    fn grove_coordinates(mixed: &[i64]) -> i64 {
+        let zero = mixed.iter().position(|&n| n == 0).unwrap();
+        [1000, 2000, 3000]
+            .iter()
+            .map(|offset| mixed[(zero + offset) % mixed.len()])
+            .sum()
+    }
+
+    pub fn day20_1(input: &str) -> i32 {
+        grove_coordinates(&mix(&parse(input), 1)) as i32
+    }
+
+    pub fn day20_2(input: &str) -> i64 {
+        let numbers: Vec<i64> = parse(input).iter().map(|n| n * 811589153).collect();
+        grove_coordinates(&mix(&numbers, 10))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{prelude::*, sample::Index};
+
+    use super::{check_parsers, day20_1, day20_1_linked, day20_2, day20_2_linked, reference};
+
+    const INPUT: &str = "1
 2
 -3
 3
@@ -79,3 +254,33 @@ const _TEST_INPUT: &str = "1
 0
 4
 ";
+
+    example_tests! {
+        test_day20_1: day20_1(INPUT) => Ok(3),
+        test_day20_2: day20_2(INPUT) => Ok(1623178306),
+        test_day20_1_linked: day20_1_linked(INPUT) => Ok(3),
+        test_day20_2_linked: day20_2_linked(INPUT) => Ok(1623178306),
+    }
+
+    fn encrypted_file() -> impl Strategy<Value = String> {
+        let non_zero = (-30..30i64).prop_filter("zero is added once", |&n| n != 0);
+        (prop::collection::vec(non_zero, 2..20), any::<Index>()).prop_map(|(mut numbers, zero)| {
+            numbers.insert(zero.index(numbers.len() + 1), 0);
+            numbers.iter().map(|n| format!("{n}\n")).collect()
+        })
+    }
+
+    differential_tests! {
+        test_day20_1_reference(encrypted_file()): day20_1 == reference::day20_1,
+        test_day20_2_reference(encrypted_file()): day20_2 == reference::day20_2,
+        test_day20_1_linked_reference(encrypted_file()): day20_1_linked == reference::day20_1,
+        test_day20_2_linked_reference(encrypted_file()): day20_2_linked == reference::day20_2,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[0-9\n-]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
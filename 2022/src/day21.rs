@@ -1,10 +1,15 @@
-#![allow(dead_code)]
+//! Day 21: Monkey Math
+//!
+//! `parse` reads what each [`Monkey`] yells.
 
+use crate::parse::{OrUnexpected, ParseError};
+#[cfg(feature = "scaling")]
+use rand::Rng;
 use smallstr::SmallString;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Op {
+pub enum Op {
     Add,
     Sub,
     Mul,
@@ -12,13 +17,13 @@ enum Op {
 }
 
 impl Op {
-    pub fn from_str(s: &str) -> Self {
+    fn from_str(s: &str) -> Result<Self, ParseError> {
         match s {
-            "+" => Op::Add,
-            "-" => Op::Sub,
-            "*" => Op::Mul,
-            "/" => Op::Div,
-            _ => panic!("Op cant convert {s:?}"),
+            "+" => Ok(Op::Add),
+            "-" => Ok(Op::Sub),
+            "*" => Ok(Op::Mul),
+            "/" => Ok(Op::Div),
+            _ => Err(ParseError::new(s)),
         }
     }
     pub fn apply(&self, n1: i64, n2: i64) -> i64 {
@@ -31,35 +36,66 @@ impl Op {
     }
 }
 
-type MonkeyName = SmallString<[u8; 4]>;
+/// A monkey's four letter name
+pub type MonkeyName = SmallString<[u8; 4]>;
 
+/// What a monkey yells: a number, or the result of an operation on what two
+/// other monkeys yell. The human's number is kept apart for part 2
 #[derive(Debug, Clone)]
-enum Monkey {
+pub enum Monkey {
     Num(i64),
     Human(i64),
     Math(MonkeyName, Op, MonkeyName),
 }
 
-fn parse_input(input: &str) -> HashMap<MonkeyName, Monkey> {
-    input
+/// What each monkey yells, by name. There must be a root, and every monkey
+/// must be able to work out its number without waiting on itself
+pub fn parse(input: &str) -> Result<HashMap<MonkeyName, Monkey>, ParseError> {
+    let monkeys = input
         .lines()
         .map(|l| {
-            let (key, rest) = l.split_once(": ").unwrap();
+            let (key, rest) = l.split_once(": ").or_unexpected(l)?;
             let key = MonkeyName::from_str(key);
             if key == "humn" {
-                (key, Monkey::Human(rest.parse().unwrap()))
-            } else if rest.chars().next().unwrap().is_ascii_digit() {
-                (key, Monkey::Num(rest.parse().unwrap()))
+                Ok((key, Monkey::Human(rest.parse().or_unexpected(l)?)))
+            } else if rest.chars().next().or_unexpected(l)?.is_ascii_digit() {
+                Ok((key, Monkey::Num(rest.parse().or_unexpected(l)?)))
             } else {
                 let mut math = rest.split_whitespace();
-                let key1 = MonkeyName::from_str(math.next().unwrap());
-                let op = math.next().unwrap();
-                let op = Op::from_str(op);
-                let key2 = MonkeyName::from_str(math.next().unwrap());
-                (key, Monkey::Math(key1, op, key2))
+                let key1 = MonkeyName::from_str(math.next().or_unexpected(l)?);
+                let op = math.next().or_unexpected(l)?;
+                let op = Op::from_str(op)?;
+                let key2 = MonkeyName::from_str(math.next().or_unexpected(l)?);
+                Ok((key, Monkey::Math(key1, op, key2)))
             }
         })
-        .collect()
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    let mut known: HashSet<&MonkeyName> = HashSet::new();
+    loop {
+        let newly_known = monkeys
+            .iter()
+            .filter(|(name, monkey)| {
+                !known.contains(name)
+                    && match monkey {
+                        Monkey::Math(k1, _, k2) => known.contains(k1) && known.contains(k2),
+                        Monkey::Num(_) | Monkey::Human(_) => true,
+                    }
+            })
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        if newly_known.is_empty() {
+            break;
+        }
+        known.extend(newly_known);
+    }
+    if let Some(stuck) = monkeys.keys().find(|name| !known.contains(name)) {
+        return Err(ParseError::new(stuck));
+    }
+    if !monkeys.contains_key("root") {
+        return Err(ParseError::new("no root monkey"));
+    }
+    Ok(monkeys)
 }
 
 fn get_monkey_num(name: &MonkeyName, monkeys: &HashMap<MonkeyName, Monkey>) -> i64 {
@@ -72,9 +108,8 @@ fn get_monkey_num(name: &MonkeyName, monkeys: &HashMap<MonkeyName, Monkey>) -> i
     }
 }
 
-pub fn day21_1(input: &str) -> i64 {
-    let monkeys = parse_input(input);
-    get_monkey_num(&MonkeyName::from_str("root"), &monkeys)
+pub fn part1(monkeys: &HashMap<MonkeyName, Monkey>) -> i64 {
+    get_monkey_num(&MonkeyName::from_str("root"), monkeys)
 }
 
 fn get_monkey_num_opt_cache(
@@ -141,8 +176,8 @@ fn human_should_be(num: i64, curr: &MonkeyName, monkeys: &HashMap<MonkeyName, Mo
     }
 }
 
-pub fn day21_2(input: &str) -> i64 {
-    let mut monkeys = parse_input(input);
+pub fn part2(monkeys: &HashMap<MonkeyName, Monkey>) -> i64 {
+    let mut monkeys = monkeys.clone();
     let Monkey::Math(left, _, right) = monkeys[&MonkeyName::from_str("root")].clone() else {
         unimplemented!()
     };
@@ -156,7 +191,125 @@ pub fn day21_2(input: &str) -> i64 {
     }
 }
 
-const _TEST_INPUT: &str = "root: pppw + sjmn
+pub fn day21_1(input: &str) -> Result<i64, ParseError> {
+    parse(input).map(|monkeys| part1(&monkeys))
+}
+
+pub fn day21_2(input: &str) -> Result<i64, ParseError> {
+    parse(input).map(|monkeys| part2(&monkeys))
+}
+
+/// Generates `size` monkeys, rounded up to an odd number, for the scaling
+/// benchmark. They form a random tree under the root, built down from the
+/// number each monkey must yell so that every division is exact. The human is
+/// on one side of the root only and never a divisor, so that part 2 has a
+/// single answer
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    /// Names the next monkey with four letters, skipping the root and human's
+    fn name(next: &mut usize) -> String {
+        loop {
+            let n = *next;
+            *next += 1;
+            let name: String = (0..4)
+                .map(|i| char::from(b'a' + (n / 26usize.pow(i) % 26) as u8))
+                .collect();
+            if name != "root" && name != "humn" {
+                return name;
+            }
+        }
+    }
+
+    /// Writes a tree of `count` monkeys whose top one yells `value`, which is
+    /// the human if `count` is 1 and `human` is set. Returns the top's name
+    fn monkeys(
+        rng: &mut impl Rng,
+        next: &mut usize,
+        (count, value, human): (usize, i64, bool),
+        input: &mut String,
+    ) -> String {
+        if count == 1 {
+            let me = if human { "humn".to_owned() } else { name(next) };
+            *input += &format!("{me}: {value}\n");
+            return me;
+        }
+        let me = name(next);
+        let left_count = 2 * rng.gen_range(0..(count - 1) / 2) + 1;
+        let human_left = human && rng.gen_bool(0.5);
+        let small = rng.gen_range(1..=20);
+        let divisor = [2, 3, 5, 7].into_iter().find(|&d| value % d == 0);
+        // every number stays positive, the parser reads a leading `-` as math
+        let (op, left_value, right_value) = match (rng.gen_range(0..4), divisor) {
+            (0, Some(divisor)) => ('*', value / divisor, divisor),
+            (1, _) if value < 1 << 20 && human == human_left => ('/', value * small, small),
+            (2, _) if value > small => ('+', value - small, small),
+            _ => ('-', value + small, small),
+        };
+        let left = monkeys(rng, next, (left_count, left_value, human_left), input);
+        let right_count = count - 1 - left_count;
+        let right_human = human && !human_left;
+        let right = monkeys(rng, next, (right_count, right_value, right_human), input);
+        *input += &format!("{me}: {left} {op} {right}\n");
+        me
+    }
+
+    let count = (size | 1).max(3);
+    let (mut next, mut input) = (0, String::new());
+    let value = rng.gen_range(1..=1000);
+    let left_count = 2 * rng.gen_range(0..(count - 1) / 2) + 1;
+    let left = monkeys(rng, &mut next, (left_count, value, true), &mut input);
+    let right_count = count - 1 - left_count;
+    let right = monkeys(rng, &mut next, (right_count, value, false), &mut input);
+    input += &format!("root: {left} + {right}\n");
+    input
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    use std::collections::HashMap;
+
+    fn yelled(name: &str, monkeys: &HashMap<&str, Vec<&str>>, human: i64) -> i64 {
+        match monkeys[name][..] {
+            _ if name == "humn" => human,
+            [number] => number.parse().unwrap(),
+            [left, op, right] => {
+                let (left, right) = (yelled(left, monkeys, human), yelled(right, monkeys, human));
+                match op {
+                    "+" => left + right,
+                    "-" => left - right,
+                    "*" => left * right,
+                    _ => left / right,
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Tries every number from -1000 to 1000 for the human
+    pub fn day21_2(input: &str) -> i64 {
+        let monkeys: HashMap<&str, Vec<&str>> = input
+            .lines()
+            .map(|line| {
+                let (name, job) = line.split_once(": ").unwrap();
+                (name, job.split(' ').collect())
+            })
+            .collect();
+        let (left, right) = (monkeys["root"][0], monkeys["root"][2]);
+        (-1000..=1000)
+            .find(|&human| yelled(left, &monkeys, human) == yelled(right, &monkeys, human))
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day21_1, day21_2, reference};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "root: pppw + sjmn
 dbpl: 5
 cczh: sllz + lgvd
 zczc: 2
@@ -173,7 +326,7 @@ drzm: hmdt - zczc
 hmdt: 32
 ";
 
-const _TEST_INPUT2: &str = "root: juli + josi
+    const INPUT2: &str = "root: juli + josi
 juli: amee + alex
 amee: buki * abby
 buki: 5
@@ -184,3 +337,96 @@ benj: 360
 mark: emly - humn
 emly: 34
 humn: 0";
+
+    example_tests! {
+        test_day21_1: day21_1(INPUT) => Ok(152),
+        test_day21_2: day21_2(INPUT) => Ok(301),
+        test_day21_2_2: day21_2(INPUT2) => Ok(19),
+    }
+
+    fn apply(op: char, left: i64, right: i64) -> i64 {
+        match op {
+            '+' => left + right,
+            '-' => left - right,
+            '*' => left * right,
+            _ => left / right,
+        }
+    }
+
+    /// Monkeys whose root compares a number with a chain of `+`, `-` and `*`
+    /// starting from the human, so that a single number of the human makes
+    /// them equal. The other side of each operation is a number, or an
+    /// operation on two numbers
+    fn riddles() -> impl Strategy<Value = String> {
+        let other = (1..10i64, prop::option::of((0..4usize, 1..10i64)));
+        let step = (0..3usize, any::<bool>(), other);
+        (
+            -100..100i64,
+            0..100i64,
+            prop::collection::vec(step, 1..6),
+            any::<bool>(),
+        )
+            .prop_map(|(human, yelled, steps, swap)| {
+                let mut lines = vec![format!("humn: {yelled}")];
+                let (mut side, mut value) = ("humn".to_string(), human);
+                for (i, (op, human_left, (a, math))) in steps.into_iter().enumerate() {
+                    let op = ['+', '-', '*'][op];
+                    let other = format!("o{i:03}");
+                    let other_value = match math {
+                        Some((other_op, b)) => {
+                            let other_op = ['+', '-', '*', '/'][other_op];
+                            let other_value = apply(other_op, a, b);
+                            if op == '*' && other_value == 0 {
+                                lines.push(format!("{other}: {a}"));
+                                a
+                            } else {
+                                lines.push(format!("{other}: a{i:03} {other_op} b{i:03}"));
+                                lines.push(format!("a{i:03}: {a}"));
+                                lines.push(format!("b{i:03}: {b}"));
+                                other_value
+                            }
+                        }
+                        None => {
+                            lines.push(format!("{other}: {a}"));
+                            a
+                        }
+                    };
+                    let name = format!("s{i:03}");
+                    if human_left {
+                        lines.push(format!("{name}: {side} {op} {other}"));
+                        value = apply(op, value, other_value);
+                    } else {
+                        lines.push(format!("{name}: {other} {op} {side}"));
+                        value = apply(op, other_value, value);
+                    }
+                    side = name;
+                }
+                let (left, right) = if swap {
+                    ("goal".to_string(), side)
+                } else {
+                    (side, "goal".to_string())
+                };
+                // monkeys only yell positive numbers
+                if value < 0 {
+                    lines.push("goal: g000 - g001".to_string());
+                    lines.push("g000: 0".to_string());
+                    lines.push(format!("g001: {}", -value));
+                } else {
+                    lines.push(format!("goal: {value}"));
+                }
+                lines.push(format!("root: {left} + {right}"));
+                lines.join("\n")
+            })
+    }
+
+    differential_tests! {
+        test_day21_2_reference(riddles()): day21_2 == reference::day21_2,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[roothumnabcd: +*/0-9\n-]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,6 +1,9 @@
-#![allow(dead_code)]
+//! Day 22: Monkey Map
+//!
+//! `parse` reads the [`Board`] and the [`Path`] to follow on it.
 
 use crate::grid::Grid;
+use crate::parse::{OrUnexpected, ParseError};
 use either::Either;
 use itertools::Itertools;
 
@@ -12,12 +15,12 @@ enum Tile {
 }
 
 impl Tile {
-    pub fn from_char(c: char) -> Self {
+    pub fn from_char(c: char) -> Option<Self> {
         match c {
-            ' ' => Self::Empty,
-            '.' => Self::Open,
-            '#' => Self::Wall,
-            _ => unimplemented!(),
+            ' ' => Some(Self::Empty),
+            '.' => Some(Self::Open),
+            '#' => Some(Self::Wall),
+            _ => None,
         }
     }
     pub fn is_empty(&self) -> bool {
@@ -38,11 +41,11 @@ enum Turn {
 }
 
 impl Turn {
-    pub fn from_char(c: char) -> Self {
+    pub fn from_char(c: char) -> Option<Self> {
         match c {
-            'R' => Self::Clockwise,
-            'L' => Self::Counterclockwise,
-            _ => unimplemented!(),
+            'R' => Some(Self::Clockwise),
+            'L' => Some(Self::Counterclockwise),
+            _ => None,
         }
     }
 }
@@ -73,7 +76,8 @@ impl Dir {
 type Coord = (usize, usize);
 type Warp = (Coord, Dir);
 
-struct Board {
+#[derive(Clone)]
+pub struct Board {
     grid: Grid<Tile>,
     line_warp: Vec<(Option<Warp>, Option<Warp>)>,
     column_warp: Vec<(Option<Warp>, Option<Warp>)>,
@@ -82,16 +86,21 @@ struct Board {
 }
 
 impl Board {
-    pub fn from_str(s: &str) -> Self {
+    /// Reads the board, which must have a tile on every row and column
+    fn from_str(s: &str) -> Result<Self, ParseError> {
         let grid = {
-            let width = s.lines().map(|l| l.len()).max().unwrap();
+            let width = s
+                .lines()
+                .map(|l| l.chars().count())
+                .max()
+                .or_unexpected(s)?;
             let height = s.chars().filter(|c| c == &'\n').count() + 1;
             let raw_grid = vec![Tile::Empty; width * height];
 
             let mut grid = Grid::from_vec(width, height, raw_grid);
             for (y, line) in s.lines().enumerate() {
                 for (x, c) in line.chars().enumerate() {
-                    *grid.get_mut(x, y) = Tile::from_char(c)
+                    *grid.get_mut(x, y) = Tile::from_char(c).or_unexpected(line)?
                 }
             }
             grid
@@ -99,44 +108,50 @@ impl Board {
         let line_warp: Vec<_> = (0..grid.height)
             .map(|y| {
                 let line = grid.get_line(y);
-                let left = line.iter().position(|t| !t.is_empty()).unwrap();
-                let right = line.iter().rposition(|t| !t.is_empty()).unwrap();
+                let left = line.iter().position(|t| !t.is_empty());
+                let right = line.iter().rposition(|t| !t.is_empty());
+                let (Some(left), Some(right)) = (left, right) else {
+                    return Err(ParseError(format!("no tile on row {}", y + 1)));
+                };
                 if line[left] != Tile::Wall && line[right] != Tile::Wall {
-                    (Some(((left, y), Dir::Right)), Some(((right, y), Dir::Left)))
+                    Ok((Some(((left, y), Dir::Right)), Some(((right, y), Dir::Left))))
                 } else {
-                    (None, None)
+                    Ok((None, None))
                 }
             })
-            .collect();
+            .try_collect()?;
         let column_warp: Vec<_> = (0..grid.width)
             .map(|x| {
                 let mut column = grid.get_column_iter(x);
-                let (up, t1) = column.find_position(|t| !t.is_empty()).unwrap();
-                let (down, t2) = column.rev().find_position(|t| !t.is_empty()).unwrap();
+                let up = column.find_position(|t| !t.is_empty());
+                let down = column.rev().find_position(|t| !t.is_empty());
+                let (Some((up, t1)), Some((down, t2))) = (up, down) else {
+                    return Err(ParseError(format!("no tile on column {}", x + 1)));
+                };
                 if t1 != &Tile::Wall && t2 != &Tile::Wall {
-                    (
+                    Ok((
                         Some(((x, up), Dir::Down)),
                         Some(((x, grid.height - down - 1), Dir::Up)),
-                    )
+                    ))
                 } else {
-                    (None, None)
+                    Ok((None, None))
                 }
             })
-            .collect();
+            .try_collect()?;
         let x = grid
             .get_line(0)
             .iter()
             .position(|t| t != &Tile::Empty)
-            .unwrap();
-        Self {
+            .or_unexpected(s)?;
+        Ok(Self {
             grid,
             line_warp,
             column_warp,
             coord: (x, 0),
             dir: Dir::Right,
-        }
+        })
     }
-    pub fn turn(&mut self, t: Turn) {
+    fn turn(&mut self, t: Turn) {
         self.dir = self.dir.turn(t)
     }
     fn coord_forward(&self, x: usize, y: usize, dir: Dir) -> Option<(Coord, Dir)> {
@@ -231,6 +246,7 @@ impl Board {
     }
 }
 
+#[derive(Clone)]
 enum Alternate {
     Turn,
     Forward,
@@ -245,32 +261,33 @@ impl Alternate {
     }
 }
 
-struct Path {
+#[derive(Clone)]
+pub struct Path {
     turns: Vec<Turn>,
     forwards: Vec<i32>,
     alternate: Alternate,
 }
 
 impl Path {
-    pub fn from_str(s: &str) -> Self {
+    fn from_str(s: &str) -> Result<Self, ParseError> {
         let turns = s
             .split(|c: char| c.is_ascii_digit())
             .filter(|&s| s == "L" || s == "R")
-            .map(|s| Turn::from_char(s.chars().next().unwrap()))
+            .filter_map(|s| s.chars().next().and_then(Turn::from_char))
             .rev()
             .collect();
         let forwards = s
-            .split(|c| c == 'L' || c == 'R')
-            .map(|s| s.trim().parse().unwrap())
+            .split(['L', 'R'])
+            .map(|s| s.trim().parse().or_unexpected(s))
             .rev()
-            .collect();
-        Self {
+            .try_collect()?;
+        Ok(Self {
             turns,
             forwards,
             alternate: Alternate::Forward,
-        }
+        })
     }
-    pub fn next(&mut self) -> Option<Either<Turn, i32>> {
+    fn next(&mut self) -> Option<Either<Turn, i32>> {
         let res = match self.alternate {
             Alternate::Turn => Either::Left(self.turns.pop()),
             Alternate::Forward => Either::Right(self.forwards.pop()),
@@ -280,14 +297,15 @@ impl Path {
     }
 }
 
-fn parse_input(input: &str) -> (Board, Path) {
-    let (board_input, path_input) = input.split_once("\n\n").unwrap();
+/// The board at the start, and the path to follow on it
+pub fn parse(input: &str) -> Result<(Board, Path), ParseError> {
+    let (board_input, path_input) = input.split_once("\n\n").or_unexpected(input)?;
 
-    (Board::from_str(board_input), Path::from_str(path_input))
+    Ok((Board::from_str(board_input)?, Path::from_str(path_input)?))
 }
 
-pub fn day22_1(input: &str) -> usize {
-    let (mut board, mut path) = parse_input(input);
+pub fn part1((board, path): &(Board, Path)) -> usize {
+    let (mut board, mut path) = (board.clone(), path.clone());
     // dbg!(&board.line_warp, &board.column_warp);
     while let Some(command) = path.next() {
         match command {
@@ -305,8 +323,8 @@ pub fn day22_1(input: &str) -> usize {
         }
 }
 
-pub fn day22_2(input: &str) -> usize {
-    let (mut board, mut path) = parse_input(input);
+pub fn part2((board, path): &(Board, Path)) -> usize {
+    let (mut board, mut path) = (board.clone(), path.clone());
     board.column_warp = custom_column_warp(&board.grid);
     board.line_warp = custom_line_warp(&board.grid);
 
@@ -325,25 +343,14 @@ pub fn day22_2(input: &str) -> usize {
             Dir::Up => 3,
         }
 }
-// 155222 high
-// 40362 low
-// 64238 low
 
-const _TEST_INPUT: &str = "        ...#
-        .#..
-        #...
-        ....
-...#.......#
-........#...
-..#....#....
-..........#.
-        ...#....
-        .....#..
-        .#......
-        ......#.
+pub fn day22_1(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|notes| part1(&notes))
+}
 
-10R5L5R10L4R5L5
-";
+pub fn day22_2(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|notes| part2(&notes))
+}
 
 fn warp_leads_to_wall(grid: &Grid<Tile>, warp: Warp) -> bool {
     grid.get(warp.0 .0, warp.0 .1) == &Tile::Wall
@@ -425,3 +432,41 @@ fn custom_column_warp(grid: &Grid<Tile>) -> Vec<(Option<Warp>, Option<Warp>)> {
     schlong_warp_list(grid, &mut custom);
     custom
 }
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day22_1};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5
+";
+
+    example_tests! {
+        test_day22_1: day22_1(INPUT) => Ok(6032),
+    }
+
+    // day22_2 folds the cube with warps hard-coded for the real input's layout,
+    // so the example (5031) can't be checked
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[ .#LR0-9\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,6 +1,11 @@
-#![allow(dead_code)]
+//! Day 23: Unstable Diffusion
+//!
+//! `parse` reads where the elves stand.
 
 use crate::grid::Grid;
+use crate::parse::ParseError;
+#[cfg(feature = "scaling")]
+use rand::Rng;
 use std::collections::HashMap;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
@@ -39,28 +44,31 @@ impl Dir {
     }
 }
 
-fn parse_input(input: &str) -> (Grid<bool>, (Coord, Coord)) {
+/// Where the elves are, with room around them to spread out, and the corners
+/// of the area they start in
+pub fn parse(input: &str) -> Result<(Grid<bool>, (Coord, Coord)), ParseError> {
+    let elves = Grid::from_lines(input, |c| match c {
+        '.' => Some(false),
+        '#' => Some(true),
+        _ => None,
+    })?;
     let multiplier = 3;
-    let width = input.lines().next().unwrap().len() * multiplier;
-    let height = input.chars().filter(|c| c == &'\n').count() * multiplier;
+    let width = elves.width * multiplier;
+    let height = elves.height * multiplier;
     let raw_grid = vec![false; width * height];
 
     let mut grid = Grid::from_vec(width, height, raw_grid);
 
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            *grid.get_mut(x + width / 3, y + height / 3) = match c {
-                '.' => false,
-                '#' => true,
-                _ => unimplemented!(),
-            }
+    for y in 0..elves.height {
+        for x in 0..elves.width {
+            *grid.get_mut(x + width / 3, y + height / 3) = *elves.get(x, y);
         }
     }
 
-    (
+    Ok((
         grid,
         ((width / 3, height / 3), (2 * width / 3, 2 * height / 3)),
-    )
+    ))
 }
 
 fn around_coord((x, y): Coord) -> [Coord; 8] {
@@ -169,8 +177,8 @@ fn smallest_rectangle(elves: &Grid<bool>) -> (Coord, Coord) {
     ((min_x, min_y), (max_x, max_y))
 }
 
-pub fn day23_1(input: &str) -> usize {
-    let (mut elves, mut search_area) = parse_input(input);
+pub fn part1((elves, search_area): &(Grid<bool>, (Coord, Coord))) -> usize {
+    let (mut elves, mut search_area) = (elves.clone(), *search_area);
     let mut proposes: HashMap<Coord, Proposal> = HashMap::new();
     let mut directions = [Dir::North, Dir::South, Dir::West, Dir::East];
     for _ in 0..10 {
@@ -193,8 +201,8 @@ pub fn day23_1(input: &str) -> usize {
     empty_groud
 } // 3766
 
-pub fn day23_2(input: &str) -> usize {
-    let (mut elves, mut search_area) = parse_input(input);
+pub fn part2((elves, search_area): &(Grid<bool>, (Coord, Coord))) -> usize {
+    let (mut elves, mut search_area) = (elves.clone(), *search_area);
     let mut proposes: HashMap<Coord, Proposal> = HashMap::new();
     let mut directions = [Dir::North, Dir::South, Dir::West, Dir::East];
     for round in 1.. {
@@ -209,7 +217,37 @@ pub fn day23_2(input: &str) -> usize {
     unreachable!()
 } // 954
 
-const _TEST_INPUT: &str = "....#..
+pub fn day23_1(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|grove| part1(&grove))
+}
+
+pub fn day23_2(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|grove| part2(&grove))
+}
+
+/// Generates a `size` by `size` grove where about half the spots hold an elf,
+/// like the puzzle's, for the scaling benchmark
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let mut row: String = (0..size)
+                .map(|_| if rng.gen_bool(0.5) { '#' } else { '.' })
+                .collect();
+            row.push('\n');
+            row
+        })
+        .collect()
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day23_1, day23_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "....#..
 ..###.#
 #...#.#
 .#...##
@@ -217,3 +255,16 @@ const _TEST_INPUT: &str = "....#..
 ##.#.##
 .#..#..
 ";
+
+    example_tests! {
+        test_day23_1: day23_1(INPUT) => Ok(110),
+        test_day23_2: day23_2(INPUT) => Ok(20),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[.#\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,8 +1,15 @@
+//! Day 24: Blizzard Basin
+//!
+//! `parse` reads the [`Valley`] and its blizzards.
+
 #![allow(dead_code, clippy::from_over_into)]
 use crate::grid::Grid;
-use std::cell::RefCell;
+use crate::parse::ParseError;
+use harness::runner::cancelled;
+#[cfg(feature = "scaling")]
+use rand::Rng;
 use std::collections::{BinaryHeap, HashSet};
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
 type Coord = (usize, usize);
 
@@ -15,34 +22,34 @@ struct Blizzard {
 }
 
 impl Blizzard {
-    pub fn from_char(c: char) -> Self {
+    pub fn from_char(c: char) -> Option<Self> {
         match c {
-            '^' => Self {
+            '^' => Some(Self {
                 up: true,
                 down: false,
                 left: false,
                 right: false,
-            },
-            'v' => Self {
+            }),
+            'v' => Some(Self {
                 up: false,
                 down: true,
                 left: false,
                 right: false,
-            },
-            '<' => Self {
+            }),
+            '<' => Some(Self {
                 up: false,
                 down: false,
                 left: true,
                 right: false,
-            },
-            '>' => Self {
+            }),
+            '>' => Some(Self {
                 up: false,
                 down: false,
                 left: false,
                 right: true,
-            },
-            '.' => Self::empty(),
-            c => unimplemented!("the heck is a {c}"),
+            }),
+            '.' => Some(Self::empty()),
+            _ => None,
         }
     }
     pub fn empty() -> Self {
@@ -138,37 +145,51 @@ impl State {
 }
 
 #[derive(Debug)]
-struct Valley {
+pub struct Valley {
     start: Coord,
     end: Coord,
     repetition_num: usize,
-    states: RefCell<Vec<Rc<State>>>,
+    states: Mutex<Vec<Arc<State>>>,
 }
 
 impl Valley {
-    pub fn from_input(input: &str) -> Self {
-        let width = input.lines().map(|l| l.len()).max().unwrap();
-        let height = input.chars().filter(|c| c == &'\n').count();
-        let grid_raw = input
-            .chars()
-            .filter(|c| c != &'\n')
-            .map(|c| match c {
-                '#' => Tile::Wall,
-                c => Tile::Bliz(Blizzard::from_char(c)),
-            })
-            .collect();
-        let grid = Grid::from_vec(width, height, grid_raw);
+    /// Reads the valley, which must be walled in apart from the start at the
+    /// top left and the end at the bottom right
+    pub fn from_input(input: &str) -> Result<Self, ParseError> {
+        let grid = Grid::from_lines(input, |c| match c {
+            '#' => Some(Tile::Wall),
+            c => Blizzard::from_char(c).map(Tile::Bliz),
+        })?;
+        let (width, height) = (grid.width, grid.height);
+        if width < 3 || height < 3 {
+            return Err(ParseError::new(input));
+        }
         let start = (1, 0);
-        let repetition_num = (grid.width - 2) * (grid.height - 2);
         let end = (width - 2, height - 1);
-        Self {
+        for y in 0..height {
+            for x in 0..width {
+                let border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+                let wall = grid.get(x, y) == &Tile::Wall;
+                let open = grid.get(x, y) == &Tile::Bliz(Blizzard::empty());
+                let valid = if (x, y) == start || (x, y) == end {
+                    open
+                } else {
+                    !border || wall
+                };
+                if !valid {
+                    return Err(ParseError(format!("tile ({x}, {y})")));
+                }
+            }
+        }
+        let repetition_num = (grid.width - 2) * (grid.height - 2);
+        Ok(Self {
             start,
             end,
             repetition_num,
-            states: RefCell::new(vec![Rc::new(State { start, end, grid })]),
-        }
+            states: Mutex::new(vec![Arc::new(State { start, end, grid })]),
+        })
     }
-    pub fn get_next_coords(&self, path: &ValleyPath) -> Vec<Coord> {
+    fn get_next_coords(&self, path: &ValleyPath) -> Vec<Coord> {
         let next_state = self.get_state(path.state_index + 1);
         {
             let after_start = (self.start.0, self.start.1 + 1);
@@ -206,11 +227,11 @@ impl Valley {
         }
         nexts
     }
-    fn get_state(&self, state_index: usize) -> Rc<State> {
-        let mut states = self.states.borrow_mut();
+    fn get_state(&self, state_index: usize) -> Arc<State> {
+        let mut states = self.states.lock().unwrap();
         while state_index >= states.len() {
             let next = states[states.len() - 1].next_state();
-            states.push(Rc::new(next))
+            states.push(Arc::new(next))
         }
         states[state_index].clone()
     }
@@ -265,7 +286,7 @@ impl PartialEq for ValleyPathToEnd {
 impl Eq for ValleyPathToEnd {}
 impl PartialOrd for ValleyPathToEnd {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(other.p.cost.cmp(&self.p.cost))
+        Some(self.cmp(other))
     }
 }
 impl Ord for ValleyPathToEnd {
@@ -302,7 +323,7 @@ impl PartialEq for ValleyPathToStart {
 impl Eq for ValleyPathToStart {}
 impl PartialOrd for ValleyPathToStart {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(other.inv_cost.cmp(&self.inv_cost))
+        Some(self.cmp(other))
     }
 }
 impl Ord for ValleyPathToStart {
@@ -321,6 +342,10 @@ fn astar<O: From<ValleyPath> + Into<ValleyPath> + Eq + Ord>(
     let mut visited = HashSet::<((usize, usize), usize)>::new();
     while let Some(best) = paths.pop() {
         let best: ValleyPath = best.into();
+        // the runner gave up on us, any path will do
+        if cancelled() {
+            return best;
+        }
         if visited.contains(&best.state_hash()) {
             continue;
         }
@@ -338,29 +363,148 @@ fn astar<O: From<ValleyPath> + Into<ValleyPath> + Eq + Ord>(
     unreachable!()
 }
 
-pub fn day24_1(input: &str) -> usize {
-    let valley = Valley::from_input(input);
+/// The valley, which works out where the blizzards are at each minute as the
+/// parts ask for it
+pub fn parse(input: &str) -> Result<Valley, ParseError> {
+    Valley::from_input(input)
+}
+
+pub fn part1(valley: &Valley) -> usize {
     let init = ValleyPath::new(valley.start, 0, 0);
 
-    astar::<ValleyPathToEnd>(&valley, init, valley.end).walked
+    astar::<ValleyPathToEnd>(valley, init, valley.end).walked
 }
-// 296 low
-// 297 low
 
-pub fn day24_2(input: &str) -> usize {
-    let valley = Valley::from_input(input);
+pub fn part2(valley: &Valley) -> usize {
     let init = ValleyPath::new(valley.start, 0, 0);
 
-    let walk1 = astar::<ValleyPathToEnd>(&valley, init, valley.end);
-    let walk2 = astar::<ValleyPathToStart>(&valley, walk1, valley.start);
-    let walk3 = astar::<ValleyPathToEnd>(&valley, walk2, valley.end);
+    let walk1 = astar::<ValleyPathToEnd>(valley, init, valley.end);
+    let walk2 = astar::<ValleyPathToStart>(valley, walk1, valley.start);
+    let walk3 = astar::<ValleyPathToEnd>(valley, walk2, valley.end);
     walk3.walked
 }
 
-const _TEST_INPUT: &str = "#.######
+pub fn day24_1(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|valley| part1(&valley))
+}
+
+pub fn day24_2(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|valley| part2(&valley))
+}
+
+/// Generates a valley `size` wide and a quarter as high, for the scaling
+/// benchmark. Like the puzzle's, nine spots in ten start with a blizzard, and
+/// none go up or down the start and end columns. Valleys are drawn again until
+/// both ways across can be walked, which random ones often can't
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let (width, height) = (size.max(2), (size / 4).max(2));
+    loop {
+        let inside: Vec<Vec<u8>> = (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|x| {
+                        let dirs: &[u8] = if x == 0 || x == width - 1 {
+                            b"<>"
+                        } else {
+                            b"<>^v"
+                        };
+                        if rng.gen_bool(0.9) {
+                            dirs[rng.gen_range(0..dirs.len())]
+                        } else {
+                            b'.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        if !can_cross(&inside, false) || !can_cross(&inside, true) {
+            continue;
+        }
+        let wall = |open| {
+            let mut wall = vec![b'#'; width + 2];
+            wall[open] = b'.';
+            String::from_utf8(wall).unwrap() + "\n"
+        };
+        let rows = inside
+            .into_iter()
+            .map(|row| format!("#{}#\n", String::from_utf8(row).unwrap()));
+        return std::iter::once(wall(1))
+            .chain(rows)
+            .chain(std::iter::once(wall(width)))
+            .collect();
+    }
+}
+
+/// Whether the expedition can walk through the `inside` of a valley from its
+/// start to its end, or back if `back`. Waiting at the start makes when it
+/// leaves irrelevant, so this only looks for a way over a few cycles of the
+/// blizzards from the first minute
+#[cfg(feature = "scaling")]
+fn can_cross(inside: &[Vec<u8>], back: bool) -> bool {
+    let (width, height) = (inside[0].len(), inside.len());
+    let (first, last) = ((0, 0), (width - 1, height - 1));
+    let (from, to) = if back { (last, first) } else { (first, last) };
+    let clear = |(x, y): (usize, usize), minute: usize| {
+        inside[y][(x + width - minute % width) % width] != b'>'
+            && inside[y][(x + minute) % width] != b'<'
+            && inside[(y + height - minute % height) % height][x] != b'v'
+            && inside[(y + minute) % height][x] != b'^'
+    };
+    let cycle = (1..).map(|n| n * width).find(|n| n % height == 0).unwrap();
+    let mut reached = vec![false; width * height];
+    for minute in 1..=4 * cycle {
+        let mut next = vec![false; width * height];
+        for (i, _) in reached.iter().enumerate().filter(|(_, &r)| r) {
+            let (x, y) = (i % width, i / width);
+            let moves = [
+                (x, y),
+                (x + 1, y),
+                (x, y + 1),
+                (x.wrapping_sub(1), y),
+                (x, y.wrapping_sub(1)),
+            ];
+            for (x, y) in moves {
+                if x < width && y < height && clear((x, y), minute) {
+                    next[y * width + x] = true;
+                }
+            }
+        }
+        if clear(from, minute) {
+            next[from.1 * width + from.0] = true;
+        }
+        if next[to.1 * width + to.0] {
+            return true;
+        }
+        reached = next;
+    }
+    false
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day24_1, day24_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "#.######
 #>>.<^<#
 #.<..<<#
 #>v.><>#
 #<^v^^>#
 ######.#
 ";
+
+    example_tests! {
+        test_day24_1: day24_1(INPUT) => Ok(18),
+        test_day24_2: day24_2(INPUT) => Ok(54),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[#.<>^v\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,19 +1,26 @@
-fn parse_line(line: &str) -> i64 {
+//! Day 25: Full of Hot Air
+//!
+//! `parse` reads the SNAFU numbers as plain integers.
+
+use crate::parse::{OrUnexpected, ParseError};
+#[cfg(feature = "scaling")]
+use rand::Rng;
+
+/// Reads a SNAFU number, which must fit in an `i64`
+fn parse_line(line: &str) -> Result<i64, ParseError> {
     line.chars()
-        .rev()
-        .enumerate()
-        .map(|(i, c)| {
-            5_i64.pow(i as u32)
-                * match c {
-                    '=' => -2,
-                    '-' => -1,
-                    '0' => 0,
-                    '1' => 1,
-                    '2' => 2,
-                    _ => unimplemented!(),
-                }
+        .try_fold(0_i64, |n, c| {
+            let digit = match c {
+                '=' => -2,
+                '-' => -1,
+                '0' => 0,
+                '1' => 1,
+                '2' => 2,
+                _ => return None,
+            };
+            n.checked_mul(5)?.checked_add(digit)
         })
-        .sum()
+        .or_unexpected(line)
 }
 
 fn to_snafu(mut n: i64) -> String {
@@ -37,12 +44,36 @@ fn to_snafu(mut n: i64) -> String {
     snafu.to_string()
 }
 
-pub fn day25_1(input: &str) -> String {
-    let sum: i64 = input.lines().map(parse_line).sum();
-    to_snafu(sum)
+/// The fuel requirements, in decimal
+pub fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+    input.lines().map(parse_line).collect()
 }
 
-const _TEST_INPUT: &str = "1=-0-2
+pub fn part1(numbers: &[i64]) -> String {
+    to_snafu(numbers.iter().sum())
+}
+
+pub fn day25_1(input: &str) -> Result<String, ParseError> {
+    parse(input).map(|numbers| part1(&numbers))
+}
+
+/// Generates `size` SNAFU numbers, for the scaling benchmark
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| to_snafu(rng.gen_range(1..1_000_000_000_000)) + "\n")
+        .collect()
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::{check_parsers, day25_1, parse_line, to_snafu};
+
+    const INPUT: &str = "1=-0-2
 12111
 2=0=
 21
@@ -56,3 +87,22 @@ const _TEST_INPUT: &str = "1=-0-2
 1=
 122
 ";
+
+    example_tests! {
+        test_day25_1: day25_1(INPUT) => Ok("2=-1=0".to_string()),
+    }
+
+    proptest! {
+        #[test]
+        fn snafu_round_trip(n in 1..1_000_000_000_000_000_i64) {
+            prop_assert_eq!(parse_line(&to_snafu(n)), Ok(n));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[=012\n-]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
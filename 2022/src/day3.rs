@@ -1,6 +1,11 @@
-#![allow(dead_code)]
+//! Day 3: Rucksack Reorganization
+//!
+//! `parse` lists the rucksacks, each as the items it holds.
 
 use itertools::Itertools;
+#[cfg(feature = "scaling")]
+use rand::Rng;
+use std::convert::Infallible;
 
 fn priority(c: char) -> Option<u8> {
     match c {
@@ -10,9 +15,14 @@ fn priority(c: char) -> Option<u8> {
     }
 }
 
-pub fn day3_1(input: &str) -> i32 {
-    input
-        .lines()
+/// The items of each rucksack
+pub fn parse(input: &str) -> Result<Vec<&str>, Infallible> {
+    Ok(input.lines().collect())
+}
+
+pub fn part1(rucksacks: &[&str]) -> i32 {
+    rucksacks
+        .iter()
         .filter_map(|line| {
             let (frst, scnd) = line.split_at(line.len() / 2);
             // let map = std::collections::HashSet::<char>::from_iter(frst.chars());
@@ -22,9 +32,9 @@ pub fn day3_1(input: &str) -> i32 {
         .fold(0, |acc, n| acc + n as i32)
 }
 
-pub fn day3_2(input: &str) -> i32 {
-    input
-        .lines()
+pub fn part2(rucksacks: &[&str]) -> i32 {
+    rucksacks
+        .iter()
         .tuples()
         .filter_map(|(frst, scnd, thrd)| {
             // let frst_map = std::collections::HashSet::<char>::from_iter(frst.chars());
@@ -36,3 +46,62 @@ pub fn day3_2(input: &str) -> i32 {
         .filter_map(priority)
         .fold(0, |acc, n| acc + n as i32)
 }
+
+pub fn day3_1(input: &str) -> Result<i32, Infallible> {
+    parse(input).map(|rucksacks| part1(&rucksacks))
+}
+
+pub fn day3_2(input: &str) -> Result<i32, Infallible> {
+    parse(input).map(|rucksacks| part2(&rucksacks))
+}
+
+/// Generates `size` rucksacks, rounded up to whole groups of three, for the
+/// scaling benchmark. Both compartments of a rucksack share an item, and the
+/// rucksacks of a group share their badge
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    const ITEMS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let mut input = String::new();
+    for _ in 0..size.div_ceil(3) {
+        let badge = ITEMS[rng.gen_range(0..ITEMS.len())];
+        for _ in 0..3 {
+            let half = rng.gen_range(4..=24);
+            let mut items: Vec<u8> = (0..2 * half)
+                .map(|_| ITEMS[rng.gen_range(0..ITEMS.len())])
+                .collect();
+            items[rng.gen_range(0..2 * half)] = badge;
+            items[rng.gen_range(half..2 * half)] = items[rng.gen_range(0..half)];
+            input.extend(items.into_iter().map(char::from));
+            input.push('\n');
+        }
+    }
+    input
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day3_1, day3_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw
+";
+
+    example_tests! {
+        test_day3_1: day3_1(INPUT) => Ok(157),
+        test_day3_2: day3_2(INPUT) => Ok(70),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[a-zA-Z\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,6 +1,14 @@
-#![allow(dead_code)]
+//! Day 4: Camp Cleanup
+//!
+//! `parse` reads the section [`Range`]s assigned to each pair of elves.
 
 use itertools::Itertools;
+#[cfg(feature = "scaling")]
+use rand::Rng;
+use std::convert::Infallible;
+
+/// The first and last sections of a range
+pub type Range = (i32, i32);
 
 fn range_str_to_tuple(range: &str) -> Option<(i32, i32)> {
     range
@@ -10,7 +18,7 @@ fn range_str_to_tuple(range: &str) -> Option<(i32, i32)> {
         .next()
 }
 
-fn line_to_range_tuple(line: &str) -> Option<((i32, i32), (i32, i32))> {
+fn line_to_range_tuple(line: &str) -> Option<(Range, Range)> {
     line.split(',')
         .tuples()
         .find_map(|(r1, r2)| Some((range_str_to_tuple(r1)?, range_str_to_tuple(r2)?)))
@@ -24,18 +32,70 @@ fn completely_overlaps((x1, x2): (i32, i32), (y1, y2): (i32, i32)) -> bool {
     y1 <= x1 && x2 <= y2 || x1 <= y1 && y2 <= x2
 }
 
-pub fn day4_1(input: &str) -> usize {
-    input
-        .split('\n')
-        .filter_map(line_to_range_tuple)
-        .filter(|&(r1, r2)| completely_overlaps(r1, r2))
-        .count()
+/// The section ranges of each pair of elves
+pub fn parse(input: &str) -> Result<Vec<(Range, Range)>, Infallible> {
+    Ok(input.split('\n').filter_map(line_to_range_tuple).collect())
 }
 
-pub fn day4_2(input: &str) -> usize {
-    input
-        .split('\n')
-        .filter_map(line_to_range_tuple)
-        .filter(|&(r1, r2)| overlaps(r1, r2))
+pub fn part1(pairs: &[(Range, Range)]) -> usize {
+    pairs
+        .iter()
+        .filter(|&&(r1, r2)| completely_overlaps(r1, r2))
         .count()
 }
+
+pub fn part2(pairs: &[(Range, Range)]) -> usize {
+    pairs.iter().filter(|&&(r1, r2)| overlaps(r1, r2)).count()
+}
+
+pub fn day4_1(input: &str) -> Result<usize, Infallible> {
+    parse(input).map(|pairs| part1(&pairs))
+}
+
+pub fn day4_2(input: &str) -> Result<usize, Infallible> {
+    parse(input).map(|pairs| part2(&pairs))
+}
+
+/// Generates the section assignments of `size` pairs of elves, for the scaling
+/// benchmark
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let mut range = || {
+        let first = rng.gen_range(1..=99);
+        (first, rng.gen_range(first..=99))
+    };
+    (0..size)
+        .map(|_| {
+            let ((a, b), (c, d)) = (range(), range());
+            format!("{a}-{b},{c}-{d}\n")
+        })
+        .collect()
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day4_1, day4_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8
+";
+
+    example_tests! {
+        test_day4_1: day4_1(INPUT) => Ok(2),
+        test_day4_2: day4_2(INPUT) => Ok(4),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[0-9,\\-\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,16 +1,23 @@
-#![allow(dead_code)]
-
-use std::cell::RefCell;
+//! Day 5: Supply Stacks
+//!
+//! `parse` reads the stacks of crates and the crane's [`Move`]s.
 
 use transpose::transpose_inplace;
 
+use crate::parse::{OrUnexpected, ParseError};
 use itertools::Itertools;
+#[cfg(feature = "scaling")]
+use rand::Rng;
 
-fn parse_crates(crates: &str) -> Vec<RefCell<Vec<char>>> {
-    let mut crate_matrix = crates.chars().filter(|c| c != &'\n').collect_vec();
+fn parse_crates(crates: &str) -> Result<Vec<Vec<char>>, ParseError> {
+    let mut crate_matrix = crates.lines().flat_map(str::chars).collect_vec();
     let columns = {
-        let width = crates.chars().position(|c| c == '\n').unwrap();
-        let height = crates.chars().filter(|c| c == &'\n').count() + 1;
+        let width = crates.lines().next().or_unexpected(crates)?.chars().count();
+        let height = crates.lines().count();
+        // the drawing is padded with spaces into a rectangle
+        if crates.lines().any(|line| line.chars().count() != width) {
+            return Err(ParseError::new(crates));
+        }
         let mut scratch = vec!['\0'; std::cmp::max(width, height)];
         transpose_inplace(&mut crate_matrix, &mut scratch, width, height);
         crate_matrix.chunks(height)
@@ -24,61 +31,151 @@ fn parse_crates(crates: &str) -> Vec<RefCell<Vec<char>>> {
     for mut line in parsed_crates {
         let _key = line.pop();
         line.reverse();
-        hmap.push(RefCell::new(line));
+        hmap.push(line);
     }
-    hmap
+    Ok(hmap)
 }
 
-pub fn day5_1(input: &str) -> String {
-    let (crates, commands) = input.split_once("\n\n").unwrap();
+/// A rearrangement step, moving `quantity` crates between two stacks
+pub struct Move {
+    quantity: usize,
+    from: usize,
+    to: usize,
+}
 
-    let crates = parse_crates(crates);
+/// Reads a step moving crates between two of the `stacks`, nothing from an
+/// empty line
+fn parse_move(command: &str, stacks: usize) -> Result<Option<Move>, ParseError> {
+    // stacks are numbered from 1
+    let stack = |key: &str| {
+        key.parse::<usize>()
+            .ok()
+            .and_then(|key| key.checked_sub(1))
+            .filter(|&stack| stack < stacks)
+            .or_unexpected(key)
+    };
+    match command.split_whitespace().collect_vec().as_slice() {
+        ["move", qnt, "from", from_key, "to", to_key] => Ok(Some(Move {
+            quantity: qnt.parse().or_unexpected(qnt)?,
+            from: stack(from_key)?,
+            to: stack(to_key)?,
+        })),
+        [] => Ok(None),
+        _ => Err(ParseError::new(command)),
+    }
+}
 
-    for command in commands.split('\n') {
-        match command.split_whitespace().collect_vec().as_slice() {
-            ["move", qnt, "from", from_key, "to", to_key] => {
-                let qnt = qnt.parse::<i32>().unwrap();
-                let from_key = from_key.chars().next().unwrap() as u8 - b'1';
-                let to_key = to_key.chars().next().unwrap() as u8 - b'1';
+/// The starting stacks, bottom crate first, and the rearrangement steps
+pub fn parse(input: &str) -> Result<(Vec<Vec<char>>, Vec<Move>), ParseError> {
+    let (crates, commands) = input.split_once("\n\n").or_unexpected(input)?;
 
-                for _ in 0..qnt {
-                    let elem = crates[from_key as usize].borrow_mut().pop().unwrap();
-                    crates[to_key as usize].borrow_mut().push(elem);
-                }
-            }
-            [] => (),
-            _ => unimplemented!(),
-        }
-    }
+    let crates = parse_crates(crates)?;
 
-    let chars = crates.iter().filter_map(|c| c.borrow().last().copied());
+    let moves = commands
+        .split('\n')
+        .filter_map(|command| parse_move(command, crates.len()).transpose())
+        .collect::<Result<_, _>>()?;
+    Ok((crates, moves))
+}
 
-    String::from_iter(chars)
+fn top_crates(crates: &[Vec<char>]) -> String {
+    crates.iter().filter_map(|c| c.last().copied()).collect()
+}
+
+pub fn part1((crates, moves): &(Vec<Vec<char>>, Vec<Move>)) -> String {
+    let mut crates = crates.clone();
+    for m in moves {
+        for _ in 0..m.quantity {
+            let elem = crates[m.from].pop().unwrap();
+            crates[m.to].push(elem);
+        }
+    }
+    top_crates(&crates)
 }
 
-pub fn day5_2(input: &str) -> String {
-    let (crates, commands) = input.split_once("\n\n").unwrap();
+pub fn part2((crates, moves): &(Vec<Vec<char>>, Vec<Move>)) -> String {
+    let mut crates = crates.clone();
+    for m in moves {
+        let len = crates[m.from].len();
+        let elems = crates[m.from].split_off(len - m.quantity);
+        crates[m.to].extend(elems);
+    }
+    top_crates(&crates)
+}
 
-    let crates = parse_crates(crates);
+pub fn day5_1(input: &str) -> Result<String, ParseError> {
+    parse(input).map(|stacks| part1(&stacks))
+}
 
-    for command in commands.split('\n') {
-        match command.split_whitespace().collect_vec().as_slice() {
-            ["move", qnt, "from", from_key, "to", to_key] => {
-                let qnt = qnt.parse::<usize>().unwrap();
-                let from_key = from_key.chars().next().unwrap() as u8 - b'1';
-                let to_key = to_key.chars().next().unwrap() as u8 - b'1';
+pub fn day5_2(input: &str) -> Result<String, ParseError> {
+    parse(input).map(|stacks| part2(&stacks))
+}
 
-                let stack = &mut crates[from_key as usize].borrow_mut();
-                let len = stack.len();
-                let elems = stack.drain((len - qnt)..).collect_vec();
-                crates[to_key as usize].borrow_mut().extend(elems);
+/// Generates nine stacks of crates and `size` steps moving them, for the
+/// scaling benchmark. Steps never move more crates than their stack holds
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let mut heights: Vec<usize> = (0..9).map(|_| rng.gen_range(1..=8)).collect();
+    let mut input = String::new();
+    for row in (0..8).rev() {
+        let line = heights
+            .iter()
+            .map(|&height| {
+                if height > row {
+                    format!("[{}]", rng.gen_range('A'..='Z'))
+                } else {
+                    "   ".to_owned()
+                }
+            })
+            .join(" ");
+        input += &line;
+        input.push('\n');
+    }
+    input += &(1..=9).map(|key| format!(" {key} ")).join(" ");
+    input.push_str("\n\n");
+    for _ in 0..size {
+        let from = loop {
+            let from = rng.gen_range(0..9);
+            if heights[from] > 0 {
+                break from;
             }
-            [] => (),
-            _ => unimplemented!(),
-        }
+        };
+        let to = (from + rng.gen_range(1..9)) % 9;
+        let quantity = rng.gen_range(1..=heights[from]);
+        heights[from] -= quantity;
+        heights[to] += quantity;
+        input += &format!("move {quantity} from {} to {}\n", from + 1, to + 1);
     }
+    input
+}
 
-    let chars = crates.iter().filter_map(|c| c.borrow().last().copied());
+harness::check_parsers!(parse);
 
-    String::from_iter(chars)
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day5_1, day5_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2
+";
+
+    example_tests! {
+        test_day5_1: day5_1(INPUT) => Ok("CMZ".to_string()),
+        test_day5_2: day5_2(INPUT) => Ok("MCD".to_string()),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[movefrt0-9 \\[\\]A-Z\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
 }
@@ -1,8 +1,19 @@
+//! Day 6: Tuning Trouble
+//!
+//! `parse` gives the datastream's bytes.
+
 use itertools::Itertools;
+#[cfg(feature = "scaling")]
+use rand::Rng;
+use std::convert::Infallible;
+
+/// The datastream, one byte per character
+pub fn parse(input: &str) -> Result<&[u8], Infallible> {
+    Ok(input.as_bytes())
+}
 
-pub fn day6_1(input: &str) -> usize {
-    input
-        .as_bytes()
+pub fn part1(datastream: &[u8]) -> usize {
+    datastream
         .iter()
         .tuple_windows()
         .position(|(n1, n2, n3, n4)| [n1, n2, n3, n4].into_iter().all_unique())
@@ -10,11 +21,97 @@ pub fn day6_1(input: &str) -> usize {
         + 4
 }
 
-pub fn day6_2(input: &str) -> usize {
-    input
-        .as_bytes()
+pub fn part2(datastream: &[u8]) -> usize {
+    datastream
         .windows(14)
         .position(|i| i.iter().all_unique())
         .unwrap()
         + 14
 }
+
+pub fn day6_1(input: &str) -> Result<usize, Infallible> {
+    parse(input).map(part1)
+}
+
+pub fn day6_2(input: &str) -> Result<usize, Infallible> {
+    parse(input).map(part2)
+}
+
+/// Generates a datastream of `size` characters before the markers, for the
+/// scaling benchmark. Only three letters are used until the markers, so that
+/// neither can appear early
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let mut datastream: String = (0..size).map(|_| rng.gen_range('a'..='c')).collect();
+    datastream.push_str("defghijklmnopq");
+    datastream
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    use std::collections::HashSet;
+
+    fn marker_end(input: &str, len: usize) -> usize {
+        (len..=input.len())
+            .find(|&end| input[end - len..end].chars().collect::<HashSet<_>>().len() == len)
+            .unwrap()
+    }
+
+    pub fn day6_1(input: &str) -> usize {
+        marker_end(input, 4)
+    }
+
+    pub fn day6_2(input: &str) -> usize {
+        marker_end(input, 14)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::{check_parsers, day6_1, day6_2, reference};
+
+    const INPUT1: &str = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+
+    const INPUT2: &str = "bvwbjplbgvbhsrlpgdmjqwftvncz";
+
+    const INPUT3: &str = "nppdvjthqldpwncqszvftbrmjlhg";
+
+    const INPUT4: &str = "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg";
+
+    const INPUT5: &str = "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw";
+
+    example_tests! {
+        test_day6_1_1: day6_1(INPUT1) => Ok(7),
+        test_day6_1_2: day6_1(INPUT2) => Ok(5),
+        test_day6_1_3: day6_1(INPUT3) => Ok(6),
+        test_day6_1_4: day6_1(INPUT4) => Ok(10),
+        test_day6_1_5: day6_1(INPUT5) => Ok(11),
+        test_day6_2_1: day6_2(INPUT1) => Ok(19),
+        test_day6_2_2: day6_2(INPUT2) => Ok(23),
+        test_day6_2_3: day6_2(INPUT3) => Ok(23),
+        test_day6_2_4: day6_2(INPUT4) => Ok(29),
+        test_day6_2_5: day6_2(INPUT5) => Ok(26),
+    }
+
+    fn datastream() -> impl Strategy<Value = String> {
+        // ends with 14 different letters so that both markers exist
+        "[a-p]{0,64}".prop_map(|s| s + "abcdefghijklmn")
+    }
+
+    differential_tests! {
+        test_day6_1_reference(datastream()): day6_1 == reference::day6_1,
+        test_day6_2_reference(datastream()): day6_2 == reference::day6_2,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[a-z\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
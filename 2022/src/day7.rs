@@ -1,28 +1,41 @@
-#![allow(dead_code)]
+//! Day 7: No Space Left On Device
+//!
+//! `parse` rebuilds the filesystem from the terminal output, in memory.
 
+use crate::parse::{OrUnexpected, ParseError};
 use itertools::Itertools;
 use vfs::{MemoryFS, VfsError, VfsPath};
 
-fn generate_vfs(input: &str) -> Result<VfsPath, VfsError> {
+fn generate_vfs(input: &str) -> Result<VfsPath, ParseError> {
     let root: VfsPath = MemoryFS::new().into();
 
     let mut curr_dir = root.clone();
     for command in input.split('\n') {
         let a = command.split(' ').collect_vec();
-        match a.as_slice() {
-            ["$", "ls"] => (), // eewee
-            ["$", "cd", "/"] => curr_dir = curr_dir.root(),
-            ["$", "cd", ".."] => curr_dir = curr_dir.parent().unwrap_or(curr_dir),
-            ["$", "cd", dir] => curr_dir = curr_dir.join(dir)?,
-            ["dir", dir] => curr_dir.join(dir)?.create_dir()?,
-            [size, name] => curr_dir
-                .join(name)?
-                .create_file()?
-                .write_all(size.as_bytes())?,
-
-            [""] => (),
-            _ => unimplemented!(),
-        }
+        let done: Result<(), VfsError> = match a.as_slice() {
+            ["$", "ls"] => Ok(()), // eewee
+            ["$", "cd", "/"] => {
+                curr_dir = curr_dir.root();
+                Ok(())
+            }
+            ["$", "cd", ".."] => {
+                curr_dir = curr_dir.parent().unwrap_or(curr_dir);
+                Ok(())
+            }
+            ["$", "cd", dir] => curr_dir.join(dir).map(|dir| curr_dir = dir),
+            ["dir", dir] => curr_dir.join(dir).and_then(|dir| dir.create_dir()),
+            [size, name] => {
+                size.parse::<usize>().or_unexpected(size)?;
+                curr_dir
+                    .join(name)
+                    .and_then(|file| file.create_file())
+                    .and_then(|mut file| Ok(file.write_all(size.as_bytes())?))
+            }
+
+            [""] => Ok(()),
+            _ => return Err(ParseError::new(command)),
+        };
+        done.or_unexpected(command)?;
     }
 
     Ok(root)
@@ -59,10 +72,17 @@ fn sum_dirs(dir: VfsPath) -> Result<usize, VfsError> {
     Ok(total)
 }
 
-pub fn day7_1(input: &str) -> usize {
-    let root = generate_vfs(input).unwrap();
+/// The file system browsed by the commands, as an in-memory one
+pub fn parse(input: &str) -> Result<VfsPath, ParseError> {
+    generate_vfs(input)
+}
+
+pub fn part1(root: &VfsPath) -> usize {
+    sum_dirs(root.clone()).unwrap()
+}
 
-    sum_dirs(root).unwrap()
+pub fn day7_1(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|root| part1(&root))
 }
 
 fn dir_sizes(dir: VfsPath) -> Vec<usize> {
@@ -76,13 +96,60 @@ fn dir_sizes(dir: VfsPath) -> Vec<usize> {
     dir_sizes
 }
 
-pub fn day7_2(input: &str) -> usize {
-    let root = generate_vfs(input).unwrap();
-
+pub fn part2(root: &VfsPath) -> usize {
     let used = dir_size(root.clone());
     let needed_space = used + 30000000 - 70000000;
 
-    let mut sizes = dir_sizes(root);
+    let mut sizes = dir_sizes(root.clone());
     sizes.sort();
     sizes.into_iter().find(|&n| n > needed_space).unwrap()
 }
+
+pub fn day7_2(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|root| part2(&root))
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day7_1, day7_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k
+";
+
+    example_tests! {
+        test_day7_1: day7_1(INPUT) => Ok(95437),
+        test_day7_2: day7_2(INPUT) => Ok(24933642),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[$ cdlsir./a-z0-9\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
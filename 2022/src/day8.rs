@@ -1,25 +1,27 @@
-#![allow(dead_code)]
+//! Day 8: Treetop Tree House
+//!
+//! `parse` reads the heights of the [`Tree`]s.
+
+#[cfg(feature = "scaling")]
+use rand::Rng;
 
 use crate::grid::Grid;
+use crate::parse::ParseError;
 
-struct Tree {
+#[derive(Clone)]
+pub struct Tree {
     pub height: u8,
     pub seen: bool,
 }
 
-fn parse_trees(input: &str) -> Grid<Tree> {
-    let width = input.lines().next().unwrap().len();
-    let height = input.lines().count();
-    let grid_raw = input
-        .lines()
-        .flat_map(|line| {
-            line.chars().map(|c| Tree {
-                height: c as u8 - b'0',
-                seen: false,
-            })
+/// The forest, with no tree seen yet
+pub fn parse(input: &str) -> Result<Grid<Tree>, ParseError> {
+    Grid::from_lines(input, |c| {
+        Some(Tree {
+            height: c.to_digit(10)? as u8,
+            seen: false,
         })
-        .collect();
-    Grid::from_vec(width, height, grid_raw)
+    })
 }
 
 fn mark_trees(trees: &mut Grid<Tree>) {
@@ -87,8 +89,8 @@ fn mark_trees(trees: &mut Grid<Tree>) {
     }
 }
 
-pub fn day8_1(input: &str) -> usize {
-    let mut trees = parse_trees(input);
+pub fn part1(trees: &Grid<Tree>) -> usize {
+    let mut trees = trees.clone();
     mark_trees(&mut trees);
     trees.grid_raw.into_iter().filter(|tree| tree.seen).count()
 }
@@ -142,12 +144,11 @@ fn scenic_score(trees: &Grid<Tree>, xo: usize, yo: usize) -> usize {
     left * right * up * down
 }
 
-pub fn day8_2(input: &str) -> usize {
-    let trees = parse_trees(input);
+pub fn part2(trees: &Grid<Tree>) -> usize {
     let mut max_scenic = 0;
     for x in 0..trees.width {
         for y in 0..trees.height {
-            let scenic = scenic_score(&trees, x, y);
+            let scenic = scenic_score(trees, x, y);
             if scenic > max_scenic {
                 max_scenic = scenic
             }
@@ -156,8 +157,83 @@ pub fn day8_2(input: &str) -> usize {
     max_scenic
 }
 
+pub fn day8_1(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|trees| part1(&trees))
+}
+
+pub fn day8_2(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|trees| part2(&trees))
+}
+
+/// Generates a `size` by `size` forest, for the scaling benchmark
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let mut forest = String::with_capacity(size * (size + 1));
+    for _ in 0..size {
+        forest.extend((0..size).map(|_| rng.gen_range('0'..='9')));
+        forest.push('\n');
+    }
+    forest
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    fn parse(input: &str) -> Vec<Vec<u8>> {
+        input.lines().map(|line| line.bytes().collect()).collect()
+    }
+
+    fn coords(trees: &[Vec<u8>]) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..trees.len()).flat_map(move |y| (0..trees[y].len()).map(move |x| (x, y)))
+    }
+
+    /// The trees seen from (x, y) in each direction, nearest first
+    fn lines_of_sight(trees: &[Vec<u8>], x: usize, y: usize) -> [Vec<u8>; 4] {
+        let row = &trees[y];
+        let column: Vec<u8> = trees.iter().map(|row| row[x]).collect();
+        [
+            row[..x].iter().rev().copied().collect(),
+            row[x + 1..].to_vec(),
+            column[..y].iter().rev().copied().collect(),
+            column[y + 1..].to_vec(),
+        ]
+    }
+
+    pub fn day8_1(input: &str) -> usize {
+        let trees = parse(input);
+        coords(&trees)
+            .filter(|&(x, y)| {
+                lines_of_sight(&trees, x, y)
+                    .iter()
+                    .any(|line| line.iter().all(|&tree| tree < trees[y][x]))
+            })
+            .count()
+    }
+
+    pub fn day8_2(input: &str) -> usize {
+        let trees = parse(input);
+        coords(&trees)
+            .map(|(x, y)| {
+                lines_of_sight(&trees, x, y)
+                    .iter()
+                    .map(|line| {
+                        line.iter()
+                            .position(|&tree| tree >= trees[y][x])
+                            .map_or(line.len(), |blocked_at| blocked_at + 1)
+                    })
+                    .product()
+            })
+            .max()
+            .unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -169,4 +245,40 @@ mod tests {
         assert_eq!(m.get_mut(2, 2), &mut 9);
         assert_eq!(m.get_mut(0, 0), &mut 1);
     }
+
+    const INPUT: &str = "30373
+25512
+65332
+33549
+35390
+";
+
+    example_tests! {
+        test_day8_1: day8_1(INPUT) => Ok(21),
+        test_day8_2: day8_2(INPUT) => Ok(8),
+    }
+
+    fn forest() -> impl Strategy<Value = String> {
+        (1..8usize, 1..8usize)
+            .prop_flat_map(|(width, height)| {
+                prop::collection::vec(prop::collection::vec(0..10u8, width), height)
+            })
+            .prop_map(|rows| {
+                rows.iter()
+                    .map(|row| row.iter().map(|h| (b'0' + h) as char).collect::<String>() + "\n")
+                    .collect()
+            })
+    }
+
+    differential_tests! {
+        test_day8_1_reference(forest()): day8_1 == reference::day8_1,
+        test_day8_2_reference(forest()): day8_2 == reference::day8_2,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[0-9\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
 }
@@ -1,4 +1,10 @@
-#![allow(dead_code)]
+//! Day 9: Rope Bridge
+//!
+//! `parse` reads the head's moves, each a [`Direction`] and a number of steps.
+
+use crate::parse::{OrUnexpected, ParseError};
+#[cfg(feature = "scaling")]
+use rand::Rng;
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 struct Knot {
@@ -47,7 +53,7 @@ impl Knot {
 }
 
 #[derive(Debug, Copy, Clone)]
-enum Direction {
+pub enum Direction {
     Left,
     Right,
     Up,
@@ -55,29 +61,37 @@ enum Direction {
 }
 
 impl Direction {
-    pub fn from_char(c: char) -> Direction {
+    pub fn from_char(c: char) -> Option<Direction> {
         match c {
-            'L' => Direction::Left,
-            'R' => Direction::Right,
-            'U' => Direction::Up,
-            'D' => Direction::Down,
-            _ => unimplemented!(),
+            'L' => Some(Direction::Left),
+            'R' => Some(Direction::Right),
+            'U' => Some(Direction::Up),
+            'D' => Some(Direction::Down),
+            _ => None,
         }
     }
 }
 
-pub fn day9_1(input: &str) -> usize {
+/// The head's moves, as a direction and a number of steps
+pub fn parse(input: &str) -> Result<Vec<(Direction, i32)>, ParseError> {
+    input
+        .lines()
+        .map(|line| {
+            let mut chars = line.chars();
+            let dir = chars.next().and_then(Direction::from_char);
+            let _ = chars.next();
+            let qnt = chars.as_str().parse::<i32>().ok();
+            Ok((dir.or_unexpected(line)?, qnt.or_unexpected(line)?))
+        })
+        .collect()
+}
+
+pub fn part1(moves: &[(Direction, i32)]) -> usize {
     let mut visited = std::collections::HashSet::<Knot>::new();
     let mut head = Knot { x: 0, y: 0 };
     let mut tail = Knot { x: 0, y: 0 };
     visited.insert(tail);
-    for line in input.lines() {
-        let mut chars = line.chars();
-        let dir = chars.next().unwrap();
-        let dir = Direction::from_char(dir);
-        let _ = chars.next();
-        let qnt = chars.as_str().parse::<i32>().unwrap();
-
+    for &(dir, qnt) in moves {
         for _ in 0..qnt {
             head = head.follow_dir(dir);
             tail = tail.follow(head);
@@ -87,17 +101,11 @@ pub fn day9_1(input: &str) -> usize {
     visited.len()
 }
 
-pub fn day9_2(input: &str) -> usize {
+pub fn part2(moves: &[(Direction, i32)]) -> usize {
     let mut visited = std::collections::HashSet::<Knot>::new();
     let mut knots = [Knot { x: 0, y: 0 }; 10];
     visited.insert(*knots.last().unwrap());
-    for line in input.lines() {
-        let mut chars = line.chars();
-        let dir = chars.next().unwrap();
-        let dir = Direction::from_char(dir);
-        let _ = chars.next();
-        let qnt = chars.as_str().parse::<i32>().unwrap();
-
+    for &(dir, qnt) in moves {
         for _ in 0..qnt {
             knots[0] = knots[0].follow_dir(dir);
             for i in 1..knots.len() {
@@ -109,3 +117,63 @@ pub fn day9_2(input: &str) -> usize {
     }
     visited.len()
 }
+
+pub fn day9_1(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|moves| part1(&moves))
+}
+
+pub fn day9_2(input: &str) -> Result<usize, ParseError> {
+    parse(input).map(|moves| part2(&moves))
+}
+
+/// Generates `size` moves of the head, for the scaling benchmark
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let dir = ['L', 'R', 'U', 'D'][rng.gen_range(0..4)];
+            format!("{dir} {}\n", rng.gen_range(1..=20))
+        })
+        .collect()
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day9_1, day9_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "R 4
+U 4
+L 3
+D 1
+R 4
+D 1
+L 5
+R 2
+";
+
+    const INPUT2: &str = "R 5
+U 8
+L 8
+D 3
+R 17
+D 10
+L 25
+U 20
+";
+
+    example_tests! {
+        test_day9_1: day9_1(INPUT) => Ok(13),
+        test_day9_2: day9_2(INPUT) => Ok(1),
+        test_day9_2_2: day9_2(INPUT2) => Ok(36),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[LRUD 0-9\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,5 +1,10 @@
+//! A 2D grid of cells
+
 use std::fmt::Debug;
 
+use crate::parse::{OrUnexpected, ParseError};
+
+/// A grid owning its cells, stored line by line
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Grid<T> {
     pub width: usize,
@@ -16,6 +21,26 @@ impl<T> Grid<T> {
             grid_raw,
         }
     }
+    /// Reads a grid of one cell per character, `cell` telling what each
+    /// character is. The lines must all be as long, and there must be one
+    pub fn from_lines(
+        input: &str,
+        mut cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let width = input.lines().next().or_unexpected(input)?.chars().count();
+        let mut grid_raw = Vec::with_capacity(input.len());
+        let mut height = 0;
+        for line in input.lines() {
+            if line.chars().count() != width {
+                return Err(ParseError::new(line));
+            }
+            for c in line.chars() {
+                grid_raw.push(cell(c).or_unexpected(line)?);
+            }
+            height += 1;
+        }
+        Ok(Self::from_vec(width, height, grid_raw))
+    }
     pub fn get(&self, x: usize, y: usize) -> &T {
         debug_assert!(x < self.width);
         debug_assert!(y < self.height);
@@ -29,7 +54,7 @@ impl<T> Grid<T> {
     pub fn get_line(&self, y: usize) -> &[T] {
         &self.grid_raw[(y * self.width)..((y + 1) * self.width)]
     }
-    pub fn get_column_iter(&self, column: usize) -> ColumnIter<T> {
+    pub fn get_column_iter(&self, column: usize) -> ColumnIter<'_, T> {
         ColumnIter::from_grid(self, column)
     }
 }
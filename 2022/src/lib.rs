@@ -1,109 +1,154 @@
+//! Solutions to Advent of Code 2022, one module per day.
+//!
+//! Every day has the same API:
+//!
+//! - `parse` reads the puzzle input into the day's own types,
+//! - `part1` and `part2` solve each part from what `parse` returned,
+//! - `day<day>_1` and `day<day>_2` do both at once, straight from the input
+//!   text.
+//!
+//! Days whose puzzle constants differ between the example and the real input
+//! have a `Params` struct, with the real values as its `Default`, and take
+//! other values through `part<part>_with` and `day<day>_<part>_with`.
+//!
+//! Parsers return a `Result`, with a `parse::ParseError` unless the day has
+//! its own error type, so `day<day>_<part>` does too.
+
 use std::time::Duration;
 
-mod grid;
+use harness::runner::Report;
+
+#[cfg(test)]
+#[macro_use(example_tests, differential_tests)]
+#[cfg_attr(not(feature = "all"), allow(unused_imports))]
+extern crate harness;
+
+#[cfg(fuzzing)]
+pub mod fuzz;
+
+pub mod grid;
+pub mod parse;
+#[cfg(feature = "scaling")]
+pub mod scaling;
 
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+#[cfg(feature = "day1")]
+pub mod day1;
+#[cfg(feature = "day10")]
+pub mod day10;
+#[cfg(feature = "day11")]
+pub mod day11;
+#[cfg(feature = "day12")]
+pub mod day12;
+#[cfg(feature = "day13")]
+pub mod day13;
+#[cfg(feature = "day14")]
+pub mod day14;
+#[cfg(feature = "day15")]
+pub mod day15;
+#[cfg(feature = "day16")]
+pub mod day16;
+#[cfg(feature = "day17")]
+pub mod day17;
+#[cfg(feature = "day18")]
+pub mod day18;
+#[cfg(feature = "day19")]
+pub mod day19;
+#[cfg(feature = "day2")]
+pub mod day2;
+#[cfg(feature = "day20")]
+pub mod day20;
+#[cfg(feature = "day21")]
+pub mod day21;
+#[cfg(feature = "day22")]
+pub mod day22;
+#[cfg(feature = "day23")]
+pub mod day23;
+#[cfg(feature = "day24")]
+pub mod day24;
+#[cfg(feature = "day25")]
+pub mod day25;
+#[cfg(feature = "day3")]
+pub mod day3;
+#[cfg(feature = "day4")]
+pub mod day4;
+#[cfg(feature = "day5")]
+pub mod day5;
+#[cfg(feature = "day6")]
+pub mod day6;
+#[cfg(feature = "day7")]
+pub mod day7;
+#[cfg(feature = "day8")]
+pub mod day8;
+#[cfg(feature = "day9")]
+pub mod day9;
 
+/// `harness::run_day!` printing the steps under 2022
+// unused when no day is built
+#[cfg_attr(not(feature = "all"), allow(unused_macros))]
 macro_rules! run_day {
-    ($($file:expr),+ => $func:expr) => {{
-        use std::time;
-        let mut input = String::new();
-        $({
-            input.push_str($file);
-        })*
-        let input = std::fs::read_to_string(input).unwrap();
-        let start = time::Instant::now();
-        let result = $func(&input);
-        let time = start.elapsed();
-        println!(
-            "[{:>6}us] 2022 {:>16}: {}",
-            time.as_micros(),
-            stringify!($func),
-            result
-        );
-        time
-    }};
+    ($($args:tt)+) => {
+        harness::run_day!(2022, $($args)+)
+    };
 }
 
-pub fn time_all_days_2022(input_folder: &str) -> Duration {
-    let total: Duration = [
-        run_day!(input_folder, "/1.txt" => day1::day1_1),
-        run_day!(input_folder, "/1.txt" => day1::day1_2),
-        run_day!(input_folder, "/2.txt" => day2::day2_1),
-        run_day!(input_folder, "/2.txt" => day2::day2_2),
-        run_day!(input_folder, "/3.txt" => day3::day3_1),
-        run_day!(input_folder, "/3.txt" => day3::day3_2),
-        run_day!(input_folder, "/4.txt" => day4::day4_1),
-        run_day!(input_folder, "/4.txt" => day4::day4_2),
-        run_day!(input_folder, "/5.txt" => day5::day5_1),
-        run_day!(input_folder, "/5.txt" => day5::day5_2),
-        run_day!(input_folder, "/6.txt" => day6::day6_1),
-        run_day!(input_folder, "/6.txt" => day6::day6_2),
-        run_day!(input_folder, "/7.txt" => day7::day7_1),
-        run_day!(input_folder, "/7.txt" => day7::day7_2),
-        run_day!(input_folder, "/8.txt" => day8::day8_1),
-        run_day!(input_folder, "/8.txt" => day8::day8_2),
-        run_day!(input_folder, "/9.txt" => day9::day9_1),
-        run_day!(input_folder, "/9.txt" => day9::day9_2),
-        run_day!(input_folder, "/10.txt" => day10::day10_1),
-        run_day!(input_folder, "/10.txt" => day10::day10_2),
-        run_day!(input_folder, "/11.txt" => day11::day11_1),
-        run_day!(input_folder, "/11.txt" => day11::day11_2),
-        run_day!(input_folder, "/12.txt" => day12::day12_1),
-        run_day!(input_folder, "/12.txt" => day12::day12_2),
-        run_day!(input_folder, "/13.txt" => day13::day13_1),
-        run_day!(input_folder, "/13.txt" => day13::day13_2),
-        run_day!(input_folder, "/14.txt" => day14::day14_1),
-        run_day!(input_folder, "/14.txt" => day14::day14_2),
-        run_day!(input_folder, "/15.txt" => day15::day15_1),
-        run_day!(input_folder, "/15.txt" => day15::day15_2),
-        run_day!(input_folder, "/16.txt" => day16::day16_1),
-        run_day!(input_folder, "/16.txt" => day16::day16_2),
-        run_day!(input_folder, "/17.txt" => day17::day17_1),
-        run_day!(input_folder, "/17.txt" => day17::day17_2),
-        run_day!(input_folder, "/18.txt" => day18::day18_1),
-        run_day!(input_folder, "/18.txt" => day18::day18_2),
-        run_day!(input_folder, "/19.txt" => day19::day19_1),
-        run_day!(input_folder, "/19.txt" => day19::day19_2),
-        run_day!(input_folder, "/20.txt" => day20::day20_1),
-        run_day!(input_folder, "/20.txt" => day20::day20_2),
-        run_day!(input_folder, "/21.txt" => day21::day21_1),
-        run_day!(input_folder, "/21.txt" => day21::day21_2),
-        run_day!(input_folder, "/22.txt" => day22::day22_1),
-        run_day!(input_folder, "/22.txt" => day22::day22_2),
-        run_day!(input_folder, "/23.txt" => day23::day23_1),
-        run_day!(input_folder, "/23.txt" => day23::day23_2),
-        run_day!(input_folder, "/24.txt" => day24::day24_1),
-        run_day!(input_folder, "/24.txt" => day24::day24_2),
-        run_day!(input_folder, "/25.txt" => day25::day25_1),
-    ]
-    .into_iter()
-    .sum();
+#[cfg_attr(not(feature = "all"), allow(unused_variables))]
+/// Runs and times every day, giving up on any step that takes longer than
+/// `timeout`. Returns how long they took and how many answers were wrong
+pub fn time_all_days_2022(input_folder: &str, timeout: Duration) -> Report {
+    let reports: &[Report] = &[
+        #[cfg(feature = "day1")]
+        run_day!(timeout, input_folder, "/1.txt" => day1: part1, part2),
+        #[cfg(feature = "day2")]
+        run_day!(timeout, input_folder, "/2.txt" => day2: part1, part2),
+        #[cfg(feature = "day3")]
+        run_day!(timeout, input_folder, "/3.txt" => day3: part1, part2),
+        #[cfg(feature = "day4")]
+        run_day!(timeout, input_folder, "/4.txt" => day4: part1, part2),
+        #[cfg(feature = "day5")]
+        run_day!(timeout, input_folder, "/5.txt" => day5: part1, part2),
+        #[cfg(feature = "day6")]
+        run_day!(timeout, input_folder, "/6.txt" => day6: part1, part2),
+        #[cfg(feature = "day7")]
+        run_day!(timeout, input_folder, "/7.txt" => day7: part1, part2),
+        #[cfg(feature = "day8")]
+        run_day!(timeout, input_folder, "/8.txt" => day8: part1, part2),
+        #[cfg(feature = "day9")]
+        run_day!(timeout, input_folder, "/9.txt" => day9: part1, part2),
+        #[cfg(feature = "day10")]
+        run_day!(timeout, input_folder, "/10.txt" => day10: part1, part2),
+        #[cfg(feature = "day11")]
+        run_day!(timeout, input_folder, "/11.txt" => day11: part1, part2),
+        #[cfg(feature = "day12")]
+        run_day!(timeout, input_folder, "/12.txt" => day12: part1, part2),
+        #[cfg(feature = "day13")]
+        run_day!(timeout, input_folder, "/13.txt" => day13: part1, part2),
+        #[cfg(feature = "day14")]
+        run_day!(timeout, input_folder, "/14.txt" => day14: part1, part2),
+        #[cfg(feature = "day15")]
+        run_day!(timeout, input_folder, "/15.txt" => day15: part1, part2),
+        #[cfg(feature = "day16")]
+        run_day!(timeout, input_folder, "/16.txt" => day16: part1, part2),
+        #[cfg(feature = "day17")]
+        run_day!(timeout, input_folder, "/17.txt" => day17: part1, part2),
+        #[cfg(feature = "day18")]
+        run_day!(timeout, input_folder, "/18.txt" => day18: part1, part2),
+        #[cfg(feature = "day19")]
+        run_day!(timeout, input_folder, "/19.txt" => day19: part1, part2),
+        #[cfg(feature = "day20")]
+        run_day!(timeout, input_folder, "/20.txt" => day20: part1 | part1_linked, part2 | part2_linked),
+        #[cfg(feature = "day21")]
+        run_day!(timeout, input_folder, "/21.txt" => day21: part1, part2),
+        #[cfg(feature = "day22")]
+        run_day!(timeout, input_folder, "/22.txt" => day22: part1, part2),
+        #[cfg(feature = "day23")]
+        run_day!(timeout, input_folder, "/23.txt" => day23: part1, part2),
+        #[cfg(feature = "day24")]
+        run_day!(timeout, input_folder, "/24.txt" => day24: part1, part2),
+        #[cfg(feature = "day25")]
+        run_day!(timeout, input_folder, "/25.txt" => day25: part1),
+    ];
+    let total: Report = reports.iter().copied().sum();
 
-    println!("[{:>6}ms] 2022 total", total.as_millis());
+    println!("[{:>6}ms] 2022 total", total.time.as_millis());
     total
 }
@@ -1,5 +1,10 @@
+use std::{process::ExitCode, time::Duration};
+
 use adventofrust2022::time_all_days_2022;
 
-fn main() {
-    time_all_days_2022("inputs/");
+/// How long a solver may run before it is given up on
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn main() -> ExitCode {
+    time_all_days_2022("inputs/", TIMEOUT).exit_code()
 }
@@ -0,0 +1,30 @@
+//! What the parsers of every day share
+
+/// What a parser couldn't make sense of: the offending text, or what is
+/// missing from the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError(pub String);
+
+impl ParseError {
+    pub fn new(text: &str) -> Self {
+        Self(text.to_owned())
+    }
+}
+
+/// Turns a failed step of a parser into a [`ParseError`] on the text it was
+/// reading
+pub trait OrUnexpected<T> {
+    fn or_unexpected(self, text: &str) -> Result<T, ParseError>;
+}
+
+impl<T> OrUnexpected<T> for Option<T> {
+    fn or_unexpected(self, text: &str) -> Result<T, ParseError> {
+        self.ok_or_else(|| ParseError::new(text))
+    }
+}
+
+impl<T, E> OrUnexpected<T> for Result<T, E> {
+    fn or_unexpected(self, text: &str) -> Result<T, ParseError> {
+        self.map_err(|_| ParseError::new(text))
+    }
+}
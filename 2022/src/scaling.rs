@@ -0,0 +1,91 @@
+//! The days that can generate inputs for the `scale` command, and the parts
+//! timed on them
+
+// the days with a generator may not all be built
+#![cfg_attr(not(feature = "all"), allow(unused_imports))]
+
+use std::time::Duration;
+
+use harness::{
+    benchmark,
+    scaling::{doubling, Benchmark, Series},
+};
+
+fn benchmark(day: u8) -> Option<Benchmark> {
+    match day {
+        #[cfg(feature = "day1")]
+        1 => Some(benchmark!(day1: "elves", doubling(1 << 10, 1 << 20) => day1_1, day1_2)),
+        #[cfg(feature = "day2")]
+        2 => Some(benchmark!(day2: "rounds", doubling(1 << 10, 1 << 22) => day2_1, day2_2)),
+        #[cfg(feature = "day3")]
+        3 => Some(benchmark!(day3: "rucksacks", doubling(1 << 10, 1 << 20) => day3_1, day3_2)),
+        #[cfg(feature = "day4")]
+        4 => Some(benchmark!(day4: "pairs", doubling(1 << 10, 1 << 20) => day4_1, day4_2)),
+        #[cfg(feature = "day5")]
+        5 => Some(benchmark!(day5: "steps", doubling(1 << 10, 1 << 20) => day5_1, day5_2)),
+        #[cfg(feature = "day6")]
+        6 => Some(benchmark!(day6: "characters", doubling(1 << 10, 1 << 24) => day6_1, day6_2)),
+        #[cfg(feature = "day8")]
+        8 => Some(benchmark!(day8: "trees per side", doubling(16, 4096) => day8_1, day8_2)),
+        #[cfg(feature = "day9")]
+        9 => Some(benchmark!(day9: "moves", doubling(1 << 8, 1 << 18) => day9_1, day9_2)),
+        #[cfg(feature = "day10")]
+        10 => Some(benchmark!(day10: "instructions", doubling(256, 1 << 20) => day10_1, day10_2)),
+        #[cfg(feature = "day11")]
+        11 => Some(benchmark!(day11: "items", doubling(8, 1 << 12) => day11_1, day11_2)),
+        #[cfg(feature = "day12")]
+        12 => Some(benchmark!(day12: "squares per side", doubling(8, 2048) => day12_1, day12_2)),
+        #[cfg(feature = "day13")]
+        13 => Some(benchmark!(day13: "pairs", doubling(16, 1 << 14) => day13_1, day13_2)),
+        #[cfg(feature = "day14")]
+        14 => Some(benchmark!(day14: "rock paths", doubling(4, 1024) => day14_1, day14_2)),
+        #[cfg(feature = "day15")]
+        15 => Some(benchmark!(day15: "sensors", doubling(4, 1 << 14) => day15_1, day15_2)),
+        #[cfg(feature = "day16")]
+        16 => Some(benchmark!(day16: "working valves", 2..=15 => day16_1, day16_2)),
+        #[cfg(feature = "day17")]
+        17 => Some(benchmark!(day17: "jets", doubling(16, 1 << 14) => day17_1, day17_2)),
+        #[cfg(feature = "day18")]
+        18 => Some(benchmark!(day18: "cubes", doubling(64, 4096) => day18_1, day18_2)),
+        #[cfg(feature = "day20")]
+        20 => Some(
+            benchmark!(day20: "numbers", doubling(64, 1 << 14) => day20_1, day20_1_linked, day20_2, day20_2_linked),
+        ),
+        #[cfg(feature = "day21")]
+        21 => Some(benchmark!(day21: "monkeys", doubling(16, 1 << 16) => day21_1, day21_2)),
+        #[cfg(feature = "day23")]
+        23 => Some(benchmark!(day23: "spots per side", doubling(8, 256) => day23_1, day23_2)),
+        #[cfg(feature = "day24")]
+        24 => Some(benchmark!(day24: "valley width", doubling(8, 256) => day24_1, day24_2)),
+        #[cfg(feature = "day25")]
+        25 => Some(benchmark!(day25: "numbers", doubling(1 << 8, 1 << 20) => day25_1)),
+        _ => None,
+    }
+}
+
+/// Times each part of `day` on generated inputs of growing size, see
+/// `Benchmark::scale`.
+/// Returns what the sizes count, or `None` if the day has no generator
+pub fn scale_day(day: u8, seed: u64, budget: Duration) -> Option<(&'static str, Vec<Series>)> {
+    let benchmark = benchmark(day)?;
+    Some((benchmark.unit, benchmark.scale(seed, budget)))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::benchmark;
+
+    #[test]
+    fn generated_inputs_are_solved() {
+        for day in 1..=25 {
+            if let Some(benchmark) = benchmark(day) {
+                let input = (benchmark.generate)(&mut StdRng::seed_from_u64(0), benchmark.sizes[0]);
+                for (_, solve) in benchmark.parts {
+                    solve(&input);
+                }
+            }
+        }
+    }
+}
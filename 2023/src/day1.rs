@@ -1,3 +1,7 @@
+//! Day 1: Trebuchet?!
+//!
+//! `parse` splits the calibration document in lines.
+
 use std::{error::Error, fmt::Display};
 
 use itertools::Itertools;
@@ -20,11 +24,16 @@ fn first_digit(mut s: impl Iterator<Item = u8>) -> Option<u8> {
     Some(res)
 }
 
-pub fn day1_1(input: &str) -> Result<u32, Day1Error> {
-    input
-        .lines()
-        .map(|line| {
-            let line = line.as_bytes();
+/// The lines of the calibration document. Which lines are valid depends on
+/// the part, so this never fails
+pub fn parse(input: &str) -> Result<Vec<&[u8]>, Day1Error> {
+    Ok(input.lines().map(str::as_bytes).collect())
+}
+
+pub fn part1(lines: &[&[u8]]) -> Result<u32, Day1Error> {
+    lines
+        .iter()
+        .map(|&line| {
             let first = first_digit(line.iter().copied())? as u32;
             let last = first_digit(line.iter().copied().rev())? as u32;
             Some(first * 10 + last)
@@ -54,11 +63,10 @@ fn find_first_digit_with_spelling(
     Err(Day1Error::CouldNotFindDigitOrSpelling)
 }
 
-pub fn day1_2(input: &str) -> Result<u32, Day1Error> {
-    input
-        .lines()
-        .map(|line| {
-            let line = line.as_bytes();
+pub fn part2(lines: &[&[u8]]) -> Result<u32, Day1Error> {
+    lines
+        .iter()
+        .map(|&line| {
             let first = find_first_digit_with_spelling(line, 0..line.len())?;
             let last = find_first_digit_with_spelling(line, (0..line.len()).rev())?;
             Ok(first * 10 + last)
@@ -66,25 +74,28 @@ pub fn day1_2(input: &str) -> Result<u32, Day1Error> {
         .fold_ok(0, |acc, n| acc + n)
 }
 
+pub fn day1_1(input: &str) -> Result<u32, Day1Error> {
+    parse(input).and_then(|lines| part1(&lines))
+}
+
+pub fn day1_2(input: &str) -> Result<u32, Day1Error> {
+    parse(input).and_then(|lines| part2(&lines))
+}
+
+harness::check_parsers!(parse);
+
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use super::{check_parsers, day1_1, day1_2};
+    use proptest::prelude::*;
 
-    #[test]
-    fn test_day1_1() {
-        let input = "1abc2
+    const INPUT1: &str = "1abc2
 pqr3stu8vwx
 a1b2c3d4e5f
 treb7uchet
 ";
 
-        let res = day1_1(input);
-        assert_eq!(res, Ok(142));
-    }
-
-    #[test]
-    fn test_day1_2() {
-        let input = "two1nine
+    const INPUT2: &str = "two1nine
 eightwothree
 abcone2threexyz
 xtwone3four
@@ -92,7 +103,16 @@ xtwone3four
 zoneight234
 7pqrstsixteen
 ";
-        let res = day1_2(input);
-        assert_eq!(res, Ok(281));
+
+    example_tests! {
+        test_day1_1: day1_1(INPUT1) => Ok(142),
+        test_day1_2: day1_2(INPUT2) => Ok(281),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[a-z0-9\n]{0,64}") {
+            check_parsers(&input);
+        }
     }
 }
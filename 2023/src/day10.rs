@@ -1,9 +1,14 @@
+//! Day 10: Pipe Maze
+//!
+//! `parse` reads the sketch of the pipes as an [`AsciiGrid`].
+
 use crate::grid::{AsciiGrid, Coord, Direction, Grid, MutGrid, VecGrid};
 use itertools::Itertools;
 use strum::IntoEnumIterator;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Day10Error {
+    NotAGrid,
     StartNotFound,
     NextPipeFromStartNotFound,
     BothSidesTouchEdge,
@@ -99,9 +104,14 @@ impl<'a, G: Grid<Item = u8>> Iterator for LoopIterator<'a, G> {
     }
 }
 
-pub fn day10_1(input: &str) -> Result<usize, Day10Error> {
-    let grid = AsciiGrid::from_ascii(input.as_bytes());
-    let loop_length = LoopIterator::from_grid(&grid)?.count();
+/// The pipe sketch. Whether it has a loop is only known when following it, so
+/// this only fails when the lines aren't a grid
+pub fn parse(input: &str) -> Result<AsciiGrid<'_>, Day10Error> {
+    AsciiGrid::try_from_ascii(input.as_bytes()).map_err(|_| Day10Error::NotAGrid)
+}
+
+pub fn part1(grid: &AsciiGrid) -> Result<usize, Day10Error> {
+    let loop_length = LoopIterator::from_grid(grid)?.count();
     Ok(loop_length / 2)
 }
 
@@ -138,15 +148,14 @@ fn grid_fill(fill_grid: &mut impl MutGrid<Item = bool>, pos: Coord) -> Option<u3
     Some(total_filled)
 }
 
-pub fn day10_2(input: &str) -> Result<u32, Day10Error> {
-    let grid = AsciiGrid::from_ascii(input.as_bytes());
+pub fn part2(grid: &AsciiGrid) -> Result<u32, Day10Error> {
     let mut fill = VecGrid::new(grid.width(), grid.height(), false);
-    for LoopNavigator { pos, .. } in LoopIterator::from_grid(&grid)? {
+    for LoopNavigator { pos, .. } in LoopIterator::from_grid(grid)? {
         *fill.get_coord_mut(pos).unwrap() = true;
     }
     let mut left_count = Some(0);
     let mut right_count = Some(0);
-    for LoopNavigator { pos, dir, pipe } in LoopIterator::from_grid(&grid)? {
+    for LoopNavigator { pos, dir, pipe } in LoopIterator::from_grid(grid)? {
         let (left_dirs, right_dirs) = dirs_to_touch(pipe, dir);
         left_count = left_count.and_then(|n| {
             let fills = left_dirs
@@ -170,9 +179,106 @@ pub fn day10_2(input: &str) -> Result<u32, Day10Error> {
         .ok_or(Day10Error::BothSidesTouchEdge)
 }
 
+pub fn day10_1(input: &str) -> Result<usize, Day10Error> {
+    parse(input).and_then(|grid| part1(&grid))
+}
+
+pub fn day10_2(input: &str) -> Result<u32, Day10Error> {
+    parse(input).and_then(|grid| part2(&grid))
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    use std::collections::HashSet;
+
+    const DIRECTIONS: [(i64, i64); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+    /// Where a pipe leads, `y` going down
+    fn connections(pipe: u8) -> Vec<(i64, i64)> {
+        match pipe {
+            b'|' => vec![(0, -1), (0, 1)],
+            b'-' => vec![(-1, 0), (1, 0)],
+            b'L' => vec![(0, -1), (1, 0)],
+            b'J' => vec![(0, -1), (-1, 0)],
+            b'7' => vec![(0, 1), (-1, 0)],
+            b'F' => vec![(0, 1), (1, 0)],
+            _ => vec![],
+        }
+    }
+
+    /// The sketch with the start replaced by its pipe, and the tiles of the
+    /// loop in order
+    fn find_loop(input: &str) -> (Vec<Vec<u8>>, Vec<(i64, i64)>) {
+        let mut grid: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
+        let at = |grid: &[Vec<u8>], (x, y): (i64, i64)| {
+            let line = grid.get(usize::try_from(y).ok()?)?;
+            line.get(usize::try_from(x).ok()?).copied()
+        };
+        let start = (0..grid.len() as i64)
+            .flat_map(|y| (0..grid[0].len() as i64).map(move |x| (x, y)))
+            .find(|&tile| at(&grid, tile) == Some(b'S'))
+            .unwrap();
+        let to_start: Vec<(i64, i64)> = DIRECTIONS
+            .into_iter()
+            .filter(|&(dx, dy)| {
+                let pipe = at(&grid, (start.0 + dx, start.1 + dy)).unwrap_or(b'.');
+                connections(pipe).contains(&(-dx, -dy))
+            })
+            .collect();
+        let start_pipe = *b"|-LJ7F"
+            .iter()
+            .find(|&&pipe| connections(pipe).iter().all(|dir| to_start.contains(dir)))
+            .unwrap();
+        grid[start.1 as usize][start.0 as usize] = start_pipe;
+
+        let mut tiles = vec![start];
+        let (mut tile, mut dir) = (start, to_start[0]);
+        loop {
+            tile = (tile.0 + dir.0, tile.1 + dir.1);
+            if tile == start {
+                break;
+            }
+            tiles.push(tile);
+            let back = (-dir.0, -dir.1);
+            dir = connections(at(&grid, tile).unwrap())
+                .into_iter()
+                .find(|&next| next != back)
+                .unwrap();
+        }
+        (grid, tiles)
+    }
+
+    pub fn day10_1(input: &str) -> usize {
+        find_loop(input).1.len() / 2
+    }
+
+    /// Counts the tiles off the loop after an odd number of pipes going up,
+    /// from the left of their line
+    pub fn day10_2(input: &str) -> u32 {
+        let (grid, tiles) = find_loop(input);
+        let tiles: HashSet<(i64, i64)> = tiles.into_iter().collect();
+        let mut enclosed = 0;
+        for (y, line) in grid.iter().enumerate() {
+            let mut inside = false;
+            for (x, &pipe) in line.iter().enumerate() {
+                if !tiles.contains(&(x as i64, y as i64)) {
+                    enclosed += u32::from(inside);
+                } else if connections(pipe).contains(&(0, -1)) {
+                    inside = !inside;
+                }
+            }
+        }
+        enclosed
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{day10_1, day10_2};
+    use super::{check_parsers, day10_1, day10_2, reference};
+    use proptest::{prelude::*, sample::Index};
 
     const INPUT1: &str = "-L|F7
 7S-7|
@@ -209,27 +315,105 @@ L.L7LFJ|||||FJL7||LJ
 L7JLJL-JLJLJL--JLJ.L
 ";
 
-    #[test]
-    fn test_day10_1_1() {
-        let res = day10_1(INPUT1);
-        assert_eq!(res, Ok(4));
+    example_tests! {
+        test_day10_1_1: day10_1(INPUT1) => Ok(4),
+        test_day10_1_2: day10_1(INPUT2) => Ok(8),
+        test_day10_2_1: day10_2(INPUT3) => Ok(8),
+        test_day10_2_2: day10_2(INPUT4) => Ok(10),
     }
 
-    #[test]
-    fn test_day10_1_2() {
-        let res = day10_1(INPUT2);
-        assert_eq!(res, Ok(8));
+    /// Sketches with a loop around a region whose columns of cells are each a
+    /// single run of rows, overlapping the run of the previous column, so that
+    /// it has no hole and the loop never touches itself. The loop goes through
+    /// the corners of the cells. The other pipes are random, but never lead to
+    /// the start
+    fn sketches() -> impl Strategy<Value = String> {
+        (1..8usize, 1..8usize)
+            .prop_flat_map(|(width, height)| {
+                (
+                    prop::collection::vec((0..height, 0..height), width),
+                    prop::collection::vec(
+                        prop::sample::select(b"|-LJ7F..".to_vec()),
+                        (width + 1) * (height + 1),
+                    ),
+                    any::<Index>(),
+                )
+            })
+            .prop_map(|(runs, junk, start)| {
+                let mut columns: Vec<(usize, usize)> = vec![];
+                for (a, b) in runs {
+                    let (mut low, mut high) = (a.min(b), a.max(b));
+                    if let Some(&(previous_low, previous_high)) = columns.last() {
+                        low = low.min(previous_high);
+                        high = high.max(previous_low);
+                    }
+                    columns.push((low, high));
+                }
+                let inside = |x: i64, y: i64| {
+                    usize::try_from(x)
+                        .ok()
+                        .and_then(|x| columns.get(x))
+                        .is_some_and(|&(low, high)| (low as i64..=high as i64).contains(&y))
+                };
+                let width = columns.len() as i64 + 1;
+                let height = junk.len() as i64 / width;
+                let mut sketch: Vec<Vec<u8>> =
+                    junk.chunks(width as usize).map(<[u8]>::to_vec).collect();
+                let mut corners = vec![];
+                for y in 0..height {
+                    for x in 0..width {
+                        let edges = [
+                            inside(x - 1, y - 1) != inside(x, y - 1),
+                            inside(x - 1, y) != inside(x, y),
+                            inside(x - 1, y - 1) != inside(x - 1, y),
+                            inside(x, y - 1) != inside(x, y),
+                        ];
+                        let pipe = match edges {
+                            [true, true, false, false] => b'|',
+                            [false, false, true, true] => b'-',
+                            [true, false, false, true] => b'L',
+                            [true, false, true, false] => b'J',
+                            [false, true, true, false] => b'7',
+                            [false, true, false, true] => b'F',
+                            _ => continue,
+                        };
+                        sketch[y as usize][x as usize] = pipe;
+                        corners.push((x, y));
+                    }
+                }
+                let (x, y) = *start.get(&corners);
+                sketch[y as usize][x as usize] = b'S';
+                for (dx, dy, leading_back) in [
+                    (0, -1, b"|7F"),
+                    (0, 1, b"|LJ"),
+                    (-1, 0, b"-LF"),
+                    (1, 0, b"-J7"),
+                ] {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if !corners.contains(&(nx, ny))
+                        && (0..width).contains(&nx)
+                        && (0..height).contains(&ny)
+                        && leading_back.contains(&sketch[ny as usize][nx as usize])
+                    {
+                        sketch[ny as usize][nx as usize] = b'.';
+                    }
+                }
+                sketch
+                    .into_iter()
+                    .map(|line| String::from_utf8(line).unwrap() + "\n")
+                    .collect()
+            })
     }
 
-    #[test]
-    fn test_day10_2_1() {
-        let res = day10_2(INPUT3);
-        assert_eq!(res, Ok(8));
+    differential_tests! {
+        test_day10_1_reference(sketches()): day10_1 == reference::day10_1,
+        test_day10_2_reference(sketches()): day10_2 == reference::day10_2,
     }
 
-    #[test]
-    fn test_day10_2_2() {
-        let res = day10_2(INPUT4);
-        assert_eq!(res, Ok(10));
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[|\\-LJ7F.S\n]{0,64}") {
+            check_parsers(&input);
+        }
     }
 }
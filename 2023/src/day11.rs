@@ -1,4 +1,11 @@
-use crate::grid::{AsciiGrid, Grid};
+//! Day 11: Cosmic Expansion
+//!
+//! `parse` reads the image of the galaxies as an [`AsciiGrid`].
+
+#[cfg(feature = "scaling")]
+use rand::Rng;
+
+use crate::grid::{AsciiGrid, Grid, NotAGrid};
 
 fn sum_galaxy_distances(grid: &impl Grid<Item = u8>, expansion_size: usize) -> usize {
     let expanded_columns: Vec<_> = grid
@@ -32,23 +39,118 @@ fn sum_galaxy_distances(grid: &impl Grid<Item = u8>, expansion_size: usize) -> u
     distances
 }
 
-pub fn day11_1(input: &str) -> Result<usize, ()> {
-    let grid = AsciiGrid::from_ascii(input.as_bytes());
-    let distances = sum_galaxy_distances(&grid, 2);
-    Ok(distances)
+/// How many rows or columns each empty one becomes in each part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub part1_expansion: usize,
+    pub part2_expansion: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_expansion: 2,
+            part2_expansion: 1_000_000,
+        }
+    }
+}
+
+/// The image of the galaxies, before the expansion
+pub fn parse(input: &str) -> Result<AsciiGrid<'_>, NotAGrid> {
+    AsciiGrid::try_from_ascii(input.as_bytes())
+}
+
+pub fn part1(grid: &AsciiGrid) -> usize {
+    part1_with(grid, &Params::default())
+}
+
+pub fn part1_with(grid: &AsciiGrid, params: &Params) -> usize {
+    sum_galaxy_distances(grid, params.part1_expansion)
+}
+
+pub fn part2(grid: &AsciiGrid) -> usize {
+    part2_with(grid, &Params::default())
+}
+
+pub fn part2_with(grid: &AsciiGrid, params: &Params) -> usize {
+    sum_galaxy_distances(grid, params.part2_expansion)
+}
+
+pub fn day11_1(input: &str) -> Result<usize, NotAGrid> {
+    parse(input).map(|grid| part1(&grid))
+}
+
+pub fn day11_1_with(input: &str, params: &Params) -> Result<usize, NotAGrid> {
+    parse(input).map(|grid| part1_with(&grid, params))
 }
 
-pub fn day11_2(input: &str) -> Result<usize, ()> {
-    let grid = AsciiGrid::from_ascii(input.as_bytes());
-    let distances = sum_galaxy_distances(&grid, 1_000_000);
-    Ok(distances)
+pub fn day11_2(input: &str) -> Result<usize, NotAGrid> {
+    parse(input).map(|grid| part2(&grid))
+}
+
+pub fn day11_2_with(input: &str, params: &Params) -> Result<usize, NotAGrid> {
+    parse(input).map(|grid| part2_with(&grid, params))
+}
+
+/// Generates a `size` by `size` image, for the scaling benchmark. About one
+/// pixel in fifty is a galaxy, and there is always at least one
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut image: Vec<u8> = (0..size * size)
+        .map(|_| if rng.gen_ratio(1, 50) { b'#' } else { b'.' })
+        .collect();
+    image[rng.gen_range(0..size * size)] = b'#';
+    image
+        .chunks(size)
+        .map(|line| String::from_utf8_lossy(line) + "\n")
+        .collect()
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    /// Walks the rows and columns between every pair of galaxies, counting the
+    /// empty ones `expansion_size` times
+    fn sum_galaxy_distances(input: &str, expansion_size: usize) -> usize {
+        let image: Vec<&[u8]> = input.lines().map(str::as_bytes).collect();
+        let empty_row = |y: usize| image[y].iter().all(|&c| c == b'.');
+        let empty_column = |x: usize| image.iter().all(|row| row[x] == b'.');
+        let step = |empty: bool| if empty { expansion_size } else { 1 };
+        let galaxies: Vec<(usize, usize)> = (0..image.len())
+            .flat_map(|y| (0..image[y].len()).map(move |x| (x, y)))
+            .filter(|&(x, y)| image[y][x] == b'#')
+            .collect();
+        let mut sum = 0;
+        for (i, &(x0, y0)) in galaxies.iter().enumerate() {
+            for &(x1, y1) in &galaxies[i + 1..] {
+                sum += (x0.min(x1)..x0.max(x1))
+                    .map(|x| step(empty_column(x)))
+                    .sum::<usize>();
+                sum += (y0.min(y1)..y0.max(y1))
+                    .map(|y| step(empty_row(y)))
+                    .sum::<usize>();
+            }
+        }
+        sum
+    }
+
+    pub fn day11_1(input: &str) -> usize {
+        sum_galaxy_distances(input, 2)
+    }
+
+    pub fn day11_2(input: &str) -> usize {
+        sum_galaxy_distances(input, 1_000_000)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::grid::AsciiGrid;
+    use proptest::prelude::*;
 
-    use super::{day11_1, sum_galaxy_distances};
+    use super::{check_parsers, day11_1, day11_2, day11_2_with, reference, Params};
 
     const INPUT: &str = "...#......
 .......#..
@@ -62,18 +164,41 @@ mod tests {
 #...#.....
 ";
 
-    #[test]
-    fn test_day11_1() {
-        let res = day11_1(INPUT);
-        assert_eq!(res, Ok(374))
+    example_tests! {
+        test_day11_1: day11_1(INPUT) => Ok(374),
+        test_day11_2_10: day11_2_with(INPUT, &Params { part2_expansion: 10, ..Params::default() }) => Ok(1030),
+        test_day11_2_100: day11_2_with(INPUT, &Params { part2_expansion: 100, ..Params::default() }) => Ok(8410),
     }
 
-    #[test]
-    fn test_day11_2() {
-        let grid = AsciiGrid::from_ascii(INPUT.as_bytes());
-        let distances = sum_galaxy_distances(&grid, 10);
-        assert_eq!(distances, 1030);
-        let distances = sum_galaxy_distances(&grid, 100);
-        assert_eq!(distances, 8410);
+    fn image() -> impl Strategy<Value = String> {
+        (1..8usize, 1..8usize)
+            .prop_flat_map(|(width, height)| {
+                let pixels = prop::collection::vec(prop::bool::weighted(0.2), width * height);
+                (Just(width), pixels, any::<prop::sample::Index>())
+            })
+            .prop_map(|(width, mut pixels, galaxy)| {
+                // the solver needs at least one galaxy
+                let len = pixels.len();
+                pixels[galaxy.index(len)] = true;
+                pixels
+                    .chunks(width)
+                    .map(|row| {
+                        let row: String = row.iter().map(|&g| if g { '#' } else { '.' }).collect();
+                        row + "\n"
+                    })
+                    .collect()
+            })
+    }
+
+    differential_tests! {
+        test_day11_1_reference(image()): day11_1 == reference::day11_1,
+        test_day11_2_reference(image()): day11_2 == reference::day11_2,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[.#\n]{0,64}") {
+            check_parsers(&input);
+        }
     }
 }
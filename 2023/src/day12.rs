@@ -1,3 +1,7 @@
+//! Day 12: Hot Springs
+//!
+//! `parse` reads each [`Row`] of springs.
+
 use std::collections::HashMap;
 
 use nom::{
@@ -8,6 +12,8 @@ use nom::{
     sequence::preceded,
     IResult, InputTakeAtPosition,
 };
+#[cfg(feature = "scaling")]
+use rand::Rng;
 use strum::EnumIter;
 use strum::IntoEnumIterator;
 
@@ -16,10 +22,11 @@ use crate::parse::sp;
 pub type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
 
 #[derive(Debug, Clone, Copy, EnumIter, PartialEq, Eq)]
-enum Status {
-    Operational = b'.' as isize,
-    Damaged = b'#' as isize,
-    Unknown = b'?' as isize,
+#[repr(u8)]
+pub enum Status {
+    Operational = b'.',
+    Damaged = b'#',
+    Unknown = b'?',
 }
 
 impl Status {
@@ -51,7 +58,10 @@ impl Status {
         Some(res)
     }
 
+    /// # Safety
+    /// Every byte must be the discriminant of a status
     unsafe fn transmute_from_bytes_unchecked(bytes: &[u8]) -> &[Self] {
+        // SAFETY: `Status` is `repr(u8)`, so it has the layout of its discriminant
         unsafe { std::mem::transmute(bytes) }
     }
 
@@ -59,16 +69,23 @@ impl Status {
         Self::iter().any(|s| s as u8 == c)
     }
 
+    fn is_status_char(c: char) -> bool {
+        // `c as u8` alone would truncate non-ASCII chars into status bytes
+        c.is_ascii() && Self::is_status_byte(c as u8)
+    }
+
     fn parse_list(input: &str) -> IResult<&str, &[Status]> {
-        let (i, status_chars) =
-            input.split_at_position_complete(|c| !Status::is_status_byte(c as u8))?;
+        let (i, status_chars) = input.split_at_position_complete(|c| !Status::is_status_char(c))?;
         // SAFETY: we only took bytes which are valid statuses
         let statuses = unsafe { Status::transmute_from_bytes_unchecked(status_chars.as_bytes()) };
         Ok((i, statuses))
     }
 }
 
-fn parse_line(input: &str) -> IResult<&str, (&[Status], Vec<usize>)> {
+/// The condition of a row of springs, and the sizes of its damaged groups
+pub type Row<'a> = (&'a [Status], Vec<usize>);
+
+fn parse_line(input: &str) -> IResult<&str, Row<'_>> {
     let (i, statuses) = preceded(sp, Status::parse_list)(input)?;
     let (i, sizes) = preceded(sp, separated_list1(char(','), u64))(i)?;
     let sizes = sizes.into_iter().map(|n| n.try_into().unwrap()).collect();
@@ -130,28 +147,30 @@ fn status_arrangements(
     res
 }
 
-pub fn day12_1(input: &str) -> Result<u64, ParseError> {
+/// Every row of the condition records
+pub fn parse(input: &str) -> Result<Vec<Row<'_>>, ParseError<'_>> {
+    input.lines().map(|line| Ok(parse_line(line)?.1)).collect()
+}
+
+pub fn part1(rows: &[Row]) -> u64 {
     let mut res = 0;
 
     let mut cache = HashMap::new();
-    for line in input.lines() {
-        let (_, (statuses, sizes)) = parse_line(line)?;
-
-        res += status_arrangements(&mut cache, statuses, &sizes);
+    for (statuses, sizes) in rows {
+        res += status_arrangements(&mut cache, statuses, sizes);
 
         cache.clear();
     }
-    Ok(res)
+    res
 }
 
-pub fn day12_2(input: &str) -> Result<u64, ParseError> {
+pub fn part2(rows: &[Row]) -> u64 {
     let mut res = 0;
 
     let mut cache = HashMap::new();
     let mut unfolded_statuses = Vec::new();
     let mut unfolded_sizes = Vec::new();
-    for line in input.lines() {
-        let (_, (statuses, sizes)) = parse_line(line)?;
+    for (statuses, sizes) in rows {
         unfolded_statuses.extend(statuses.iter().copied());
         unfolded_sizes.extend(sizes.iter().copied());
         for _ in 0..4 {
@@ -166,12 +185,110 @@ pub fn day12_2(input: &str) -> Result<u64, ParseError> {
         unfolded_statuses.clear();
         unfolded_sizes.clear();
     }
-    Ok(res)
+    res
+}
+
+pub fn day12_1(input: &str) -> Result<u64, ParseError<'_>> {
+    parse(input).map(|rows| part1(&rows))
+}
+
+pub fn day12_2(input: &str) -> Result<u64, ParseError<'_>> {
+    parse(input).map(|rows| part2(&rows))
+}
+
+/// Generates `size` condition records, for the scaling benchmark. Each row is
+/// built from its group sizes before some springs are hidden, so that it has
+/// at least one arrangement
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let sizes: Vec<usize> = (0..rng.gen_range(1..=5))
+                .map(|_| rng.gen_range(1..=4))
+                .collect();
+            let mut row = ".".repeat(rng.gen_range(0..=2));
+            for (i, &size) in sizes.iter().enumerate() {
+                if i > 0 {
+                    row += &".".repeat(rng.gen_range(1..=2));
+                }
+                row += &"#".repeat(size);
+            }
+            row += &".".repeat(rng.gen_range(0..=2));
+            let row: String = row
+                .chars()
+                .map(|c| if rng.gen_ratio(2, 5) { '?' } else { c })
+                .collect();
+            let sizes: Vec<String> = sizes.iter().map(usize::to_string).collect();
+            format!("{row} {}\n", sizes.join(","))
+        })
+        .collect()
+}
+
+/// Checks that the statuses `parse_list` hands out for arbitrary text are
+/// exactly the bytes it consumed, and that `parse_line` doesn't panic
+#[cfg(any(test, fuzzing))]
+pub(crate) fn check_parsers(input: &str) {
+    if let Ok((rest, statuses)) = Status::parse_list(input) {
+        let consumed: String = statuses.iter().map(|&s| s as u8 as char).collect();
+        assert_eq!(consumed + rest, input);
+    }
+    let _ = parse_line(input);
+}
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    fn groups(row: &[u8]) -> Vec<usize> {
+        row.split(|&c| c == b'.')
+            .filter(|group| !group.is_empty())
+            .map(<[u8]>::len)
+            .collect()
+    }
+
+    /// Tries every assignment of the unknown springs
+    fn arrangements(row: &[u8], sizes: &[usize]) -> u64 {
+        match row.iter().position(|&c| c == b'?') {
+            None => u64::from(groups(row) == sizes),
+            Some(unknown) => [b'.', b'#']
+                .iter()
+                .map(|&status| {
+                    let mut row = row.to_vec();
+                    row[unknown] = status;
+                    arrangements(&row, sizes)
+                })
+                .sum(),
+        }
+    }
+
+    fn day12(input: &str, copies: usize) -> u64 {
+        input
+            .lines()
+            .map(|line| {
+                let (row, sizes) = line.split_once(' ').unwrap();
+                let row = vec![row; copies].join("?");
+                let sizes = vec![sizes; copies].join(",");
+                let sizes: Vec<usize> = sizes.split(',').map(|n| n.parse().unwrap()).collect();
+                arrangements(row.as_bytes(), &sizes)
+            })
+            .sum()
+    }
+
+    pub fn day12_1(input: &str) -> u64 {
+        day12(input, 1)
+    }
+
+    /// Only bearable for rows of very few unknown springs, as unfolding them
+    /// adds four more
+    pub fn day12_2(input: &str) -> u64 {
+        day12(input, 5)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{day12_1, day12_2};
+    use super::{check_parsers, day12_1, day12_2, reference, Status};
+    use proptest::prelude::*;
+    use strum::IntoEnumIterator;
 
     const INPUT: &str = "???.### 1,1,3
 .??..??...?##. 1,1,3
@@ -181,16 +298,81 @@ mod tests {
 ?###???????? 3,2,1
 ";
 
-    #[test]
-    fn test_day12_1() {
-        let res = day12_1(INPUT);
-        assert_eq!(res, Ok(21))
+    example_tests! {
+        test_day12_1: day12_1(INPUT) => Ok(21),
+        test_day12_2: day12_2(INPUT) => Ok(525152),
     }
 
-    #[test]
-    fn test_day12_2() {
-        let res = day12_2(INPUT);
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[.#?Į 0-9,\n]{0,64}") {
+            check_parsers(&input);
+        }
+
+        #[test]
+        fn statuses_round_trip(indices in prop::collection::vec(0..3usize, 0..32), rest in "[ 0-9,]{0,8}") {
+            let statuses: Vec<_> = indices.into_iter().map(|i| Status::iter().nth(i).unwrap()).collect();
+            let printed: String = statuses.iter().map(|&s| s as u8 as char).collect();
+            let input = printed + &rest;
+            let (i, parsed) = Status::parse_list(&input).unwrap();
+            prop_assert_eq!(parsed, &statuses[..]);
+            prop_assert_eq!(i, rest);
+        }
+    }
+
+    fn row(max_hidden: usize) -> impl Strategy<Value = String> {
+        // a valid row, built from its group sizes and the gaps between them,
+        // with some springs then hidden, up to `max_hidden`
+        prop::collection::vec((1..4usize, 1..3usize), 1..4)
+            .prop_flat_map(|groups| {
+                let len: usize = groups.iter().map(|(size, gap)| size + gap).sum();
+                (
+                    Just(groups),
+                    0..3usize,
+                    prop::collection::vec(prop::bool::weighted(0.6), len + 2),
+                )
+            })
+            .prop_map(move |(groups, leading, hidden)| {
+                let mut row = ".".repeat(leading);
+                for (i, &(size, gap)) in groups.iter().enumerate() {
+                    row += &"#".repeat(size);
+                    if i + 1 < groups.len() {
+                        row += &".".repeat(gap);
+                    }
+                }
+                let mut left = max_hidden;
+                let row: String = row
+                    .chars()
+                    .zip(hidden)
+                    .map(|(c, hide)| {
+                        if hide && left > 0 {
+                            left -= 1;
+                            '?'
+                        } else {
+                            c
+                        }
+                    })
+                    .collect();
+                let sizes: Vec<String> = groups.iter().map(|(size, _)| size.to_string()).collect();
+                format!("{row} {}\n", sizes.join(","))
+            })
+    }
+
+    fn records() -> impl Strategy<Value = String> {
+        prop::collection::vec(row(usize::MAX), 1..4).prop_map(|rows| rows.concat())
+    }
+
+    /// Records whose rows have at most two unknown springs before unfolding
+    fn few_unknowns() -> impl Strategy<Value = String> {
+        prop::collection::vec(row(2), 1..3).prop_map(|rows| rows.concat())
+    }
+
+    differential_tests! {
+        test_day12_1_reference(records()): day12_1 == reference::day12_1,
+    }
 
-        assert_eq!(res, Ok(525152))
+    differential_tests! {
+        cases = 16;
+        test_day12_2_reference(few_unknowns()): day12_2 == reference::day12_2,
     }
 }
@@ -1,8 +1,12 @@
+//! Day 13: Point of Incidence
+//!
+//! `parse` reads the patterns of ash and rocks.
+
 use std::fmt::Debug;
 
 use itertools::Itertools;
 
-use crate::grid::{AsciiGrid, Grid};
+use crate::grid::{AsciiGrid, Grid, NotAGrid};
 
 fn find_reflection<I>(items: &[I]) -> usize
 where
@@ -24,17 +28,23 @@ where
     0
 }
 
-pub fn day13_1(input: &str) -> Result<usize, ()> {
-    let res = input
+/// Every pattern of ash and rocks
+pub fn parse(input: &str) -> Result<Vec<AsciiGrid<'_>>, NotAGrid> {
+    input
         .split("\n\n")
-        .map(|chunk| AsciiGrid::from_ascii(chunk.as_bytes()))
+        .map(|chunk| AsciiGrid::try_from_ascii(chunk.as_bytes()))
+        .collect()
+}
+
+pub fn part1(patterns: &[AsciiGrid]) -> usize {
+    patterns
+        .iter()
         .map(|grid| {
             let v = find_reflection(&grid.columns().collect_vec());
             let h = find_reflection(&grid.lines().collect_vec());
             v + 100 * h
         })
-        .sum();
-    Ok(res)
+        .sum()
 }
 
 fn find_almost_reflection<I>(items: &[I]) -> usize
@@ -65,22 +75,31 @@ where
     0
 }
 
-pub fn day13_2(input: &str) -> Result<usize, ()> {
-    let res = input
-        .split("\n\n")
-        .map(|chunk| AsciiGrid::from_ascii(chunk.as_bytes()))
+pub fn part2(patterns: &[AsciiGrid]) -> usize {
+    patterns
+        .iter()
         .map(|grid| {
             let v = find_almost_reflection(&grid.columns().collect_vec());
             let h = find_almost_reflection(&grid.lines().collect_vec());
             v + 100 * h
         })
-        .sum();
-    Ok(res)
+        .sum()
+}
+
+pub fn day13_1(input: &str) -> Result<usize, NotAGrid> {
+    parse(input).map(|patterns| part1(&patterns))
 }
 
+pub fn day13_2(input: &str) -> Result<usize, NotAGrid> {
+    parse(input).map(|patterns| part2(&patterns))
+}
+
+harness::check_parsers!(parse);
+
 #[cfg(test)]
 mod tests {
-    use super::{day13_1, day13_2};
+    use super::{check_parsers, day13_1, day13_2};
+    use proptest::prelude::*;
 
     const INPUT: &str = "#.##..##.
 ..#.##.#.
@@ -99,16 +118,15 @@ mod tests {
 #....#..#
 ";
 
-    #[test]
-    fn test_day13_1() {
-        let res = day13_1(INPUT);
-        assert_eq!(res, Ok(405))
+    example_tests! {
+        test_day13_1: day13_1(INPUT) => Ok(405),
+        test_day13_2: day13_2(INPUT) => Ok(400),
     }
 
-    #[test]
-    fn test_day13_2() {
-        let res = day13_2(INPUT);
-
-        assert_eq!(res, Ok(400))
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[.#\n]{0,64}") {
+            check_parsers(&input);
+        }
     }
 }
@@ -1,24 +1,37 @@
+//! Day 14: Parabolic Reflector Dish
+//!
+//! `parse` reads the platform's [`Tile`]s.
+
 use std::{
     collections::HashMap,
     hash::{DefaultHasher, Hash, Hasher},
 };
 
+#[cfg(feature = "scaling")]
+use rand::Rng;
+
 use crate::grid::{AsciiGrid, ColumnIterator, Direction, Grid, LineIterator, MutGrid, VecGrid};
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum Day14Error {
+    NotAGrid,
+    UnknownTile(u8),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Tile {
+pub enum Tile {
     RoundedRock,
     CubeRock,
     Empty,
 }
 
 impl Tile {
-    fn from_ascii(c: u8) -> Self {
+    fn from_ascii(c: u8) -> Option<Self> {
         match c {
-            b'O' => Self::RoundedRock,
-            b'#' => Self::CubeRock,
-            b'.' => Self::Empty,
-            _ => unimplemented!(),
+            b'O' => Some(Self::RoundedRock),
+            b'#' => Some(Self::CubeRock),
+            b'.' => Some(Self::Empty),
+            _ => None,
         }
     }
 }
@@ -51,9 +64,9 @@ fn slide(platform: &mut VecGrid<Tile>, dir: Direction) {
     for line in traversal_range {
         ranges.clear();
         if dir.is_vertical() {
-            collect_ranges(platform, ColumnIterator::new(platform, line), &mut ranges);
+            collect_ranges(ColumnIterator::new(platform, line), &mut ranges);
         } else {
-            collect_ranges(platform, LineIterator::new(platform, line), &mut ranges);
+            collect_ranges(LineIterator::new(platform, line), &mut ranges);
         };
         for range in &ranges {
             let range_size = range.x1 - range.x0 + 1;
@@ -79,14 +92,49 @@ fn slide(platform: &mut VecGrid<Tile>, dir: Direction) {
     }
 }
 
-fn collect_ranges<'a>(
-    platform: &'a VecGrid<Tile>,
-    iter: impl Iterator<Item = &'a Tile>,
-    ranges: &mut Vec<Range>,
-) {
+/// Rolls the rocks one at a time, each to the free tile after the last rock
+/// or cube before it. Simpler than [`slide`], which rewrites whole ranges
+fn slide_rolling(platform: &mut VecGrid<Tile>, dir: Direction) {
+    let (lines, len) = if dir.is_vertical() {
+        (platform.width(), platform.height())
+    } else {
+        (platform.height(), platform.width())
+    };
+    let towards_start = matches!(dir, Direction::Up | Direction::Left);
+    for line in 0..lines {
+        let coord = |i: usize| {
+            let i = if towards_start { i } else { len - 1 - i };
+            if dir.is_vertical() {
+                (line, i)
+            } else {
+                (i, line)
+            }
+        };
+        let mut free = 0;
+        for i in 0..len {
+            let (x, y) = coord(i);
+            match platform.get(x, y) {
+                Some(Tile::CubeRock) => free = i + 1,
+                Some(Tile::RoundedRock) => {
+                    if free != i {
+                        *platform.get_mut(x, y).unwrap() = Tile::Empty;
+                        let (x, y) = coord(free);
+                        *platform.get_mut(x, y).unwrap() = Tile::RoundedRock;
+                    }
+                    free += 1;
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+fn collect_ranges<'a>(iter: impl Iterator<Item = &'a Tile>, ranges: &mut Vec<Range>) {
     let mut num_rocks = 0;
     let mut x0 = 0;
+    let mut len = 0;
     for (i, tile) in iter.enumerate() {
+        len = i + 1;
         match tile {
             Tile::RoundedRock => {
                 num_rocks += 1;
@@ -108,41 +156,86 @@ fn collect_ranges<'a>(
     if num_rocks != 0 {
         ranges.push(Range {
             x0,
-            x1: platform.x1(),
+            x1: len - 1,
             num_rocks,
         });
     }
 }
 
-fn process_input(input: &str) -> VecGrid<Tile> {
-    let ascii_grid = AsciiGrid::from_ascii(input.as_bytes());
-    VecGrid::from_iter(
+/// The platform with its rocks where they start
+pub fn parse(input: &str) -> Result<VecGrid<Tile>, Day14Error> {
+    let ascii_grid =
+        AsciiGrid::try_from_ascii(input.as_bytes()).map_err(|_| Day14Error::NotAGrid)?;
+    let tiles = ascii_grid
+        .iter()
+        .map(|&c| Tile::from_ascii(c).ok_or(Day14Error::UnknownTile(c)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(VecGrid::from_iter(
         ascii_grid.width(),
         ascii_grid.height(),
-        ascii_grid.iter().map(|&c| Tile::from_ascii(c)),
-    )
+        tiles.into_iter(),
+    ))
 }
 
-pub fn day14_1(input: &str) -> Result<usize, ()> {
-    let mut platform = process_input(input);
+/// A way of sliding the rocks of a platform
+type Slide = fn(&mut VecGrid<Tile>, Direction);
+
+fn tilt(platform: &VecGrid<Tile>, slide: Slide) -> usize {
+    let mut platform = platform.clone();
     slide(&mut platform, Direction::Up);
-    Ok(total_load(&platform))
+    total_load(&platform)
 }
 
-fn cycle(platform: &mut VecGrid<Tile>) {
+pub fn part1(platform: &VecGrid<Tile>) -> usize {
+    tilt(platform, slide)
+}
+
+/// [`part1`] with [`slide_rolling`]
+pub fn part1_rolling(platform: &VecGrid<Tile>) -> usize {
+    tilt(platform, slide_rolling)
+}
+
+fn cycle(platform: &mut VecGrid<Tile>, slide: Slide) {
     slide(platform, Direction::Up);
     slide(platform, Direction::Left);
     slide(platform, Direction::Down);
     slide(platform, Direction::Right);
 }
 
-pub fn day14_2(input: &str) -> Result<usize, ()> {
-    let mut platform = process_input(input);
-    let mut remaining_cycles = 1_000_000_000usize;
+/// How many spin cycles part 2 runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub cycles: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            cycles: 1_000_000_000,
+        }
+    }
+}
+
+pub fn part2(platform: &VecGrid<Tile>) -> usize {
+    part2_with(platform, &Params::default())
+}
+
+/// [`part2`] with [`slide_rolling`]
+pub fn part2_rolling(platform: &VecGrid<Tile>) -> usize {
+    spin(platform, &Params::default(), slide_rolling)
+}
+
+pub fn part2_with(platform: &VecGrid<Tile>, params: &Params) -> usize {
+    spin(platform, params, slide)
+}
+
+fn spin(platform: &VecGrid<Tile>, params: &Params, slide: Slide) -> usize {
+    let mut platform = platform.clone();
+    let mut remaining_cycles = params.cycles;
     let mut seen_states: HashMap<u64, usize> = HashMap::new();
     while remaining_cycles != 0 {
         remaining_cycles -= 1;
-        cycle(&mut platform);
+        cycle(&mut platform, slide);
         let mut s = DefaultHasher::new();
         platform.hash(&mut s);
         let hash = s.finish();
@@ -159,14 +252,126 @@ pub fn day14_2(input: &str) -> Result<usize, ()> {
     }
     while remaining_cycles != 0 {
         remaining_cycles -= 1;
-        cycle(&mut platform);
+        cycle(&mut platform, slide);
+    }
+    total_load(&platform)
+}
+
+pub fn day14_1(input: &str) -> Result<usize, Day14Error> {
+    parse(input).map(|platform| part1(&platform))
+}
+
+pub fn day14_2(input: &str) -> Result<usize, Day14Error> {
+    parse(input).map(|platform| part2(&platform))
+}
+
+pub fn day14_1_rolling(input: &str) -> Result<usize, Day14Error> {
+    parse(input).map(|platform| part1_rolling(&platform))
+}
+
+pub fn day14_2_rolling(input: &str) -> Result<usize, Day14Error> {
+    parse(input).map(|platform| part2_rolling(&platform))
+}
+
+pub fn day14_2_with(input: &str, params: &Params) -> Result<usize, Day14Error> {
+    parse(input).map(|platform| part2_with(&platform, params))
+}
+
+/// Generates a `size` by `size` platform, for the scaling benchmark
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let mut platform = String::with_capacity(size * (size + 1));
+    for _ in 0..size {
+        platform.extend((0..size).map(|_| match rng.gen_range(0..20) {
+            0..=3 => 'O',
+            4..=6 => '#',
+            _ => '.',
+        }));
+        platform.push('\n');
+    }
+    platform
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    type Platform = Vec<Vec<u8>>;
+
+    /// Rolls every rock north, one row at a time until none can move
+    fn tilt_north(platform: &mut Platform) {
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for y in 1..platform.len() {
+                for x in 0..platform[y].len() {
+                    if platform[y][x] == b'O' && platform[y - 1][x] == b'.' {
+                        platform[y - 1][x] = b'O';
+                        platform[y][x] = b'.';
+                        moved = true;
+                    }
+                }
+            }
+        }
+    }
+
+    fn rotate_clockwise(platform: &Platform) -> Platform {
+        let height = platform.len();
+        (0..platform[0].len())
+            .map(|x| (0..height).rev().map(|y| platform[y][x]).collect())
+            .collect()
+    }
+
+    /// Tilts north, west, south then east, by rotating the platform in between
+    fn spin_cycle(mut platform: Platform) -> Platform {
+        for _ in 0..4 {
+            tilt_north(&mut platform);
+            platform = rotate_clockwise(&platform);
+        }
+        platform
+    }
+
+    fn load(platform: &Platform) -> usize {
+        platform
+            .iter()
+            .enumerate()
+            .map(|(y, row)| row.iter().filter(|&&c| c == b'O').count() * (platform.len() - y))
+            .sum()
+    }
+
+    fn parse(input: &str) -> Platform {
+        input.lines().map(|line| line.bytes().collect()).collect()
+    }
+
+    pub fn day14_1(input: &str) -> usize {
+        let mut platform = parse(input);
+        tilt_north(&mut platform);
+        load(&platform)
+    }
+
+    pub fn day14_2(input: &str) -> usize {
+        let total = 1_000_000_000;
+        let mut seen = vec![parse(input)];
+        loop {
+            let next = spin_cycle(seen.last().unwrap().clone());
+            if let Some(start) = seen.iter().position(|platform| *platform == next) {
+                let period = seen.len() - start;
+                return load(&seen[start + (total - start) % period]);
+            }
+            seen.push(next);
+        }
     }
-    Ok(total_load(&platform))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{day14_1, day14_2};
+    use proptest::prelude::*;
+
+    use super::{
+        check_parsers, day14_1, day14_1_rolling, day14_2, day14_2_rolling, day14_2_with, reference,
+        Params,
+    };
 
     const INPUT: &str = "O....#....
 O.OO#....#
@@ -179,16 +384,34 @@ O.#..O.#.#
 #....###..
 #OO..#....";
 
-    #[test]
-    fn test_day14_1() {
-        let res = day14_1(INPUT);
-        assert_eq!(res, Ok(136))
+    example_tests! {
+        test_day14_1: day14_1(INPUT) => Ok(136),
+        test_day14_2: day14_2(INPUT) => Ok(64),
+        test_day14_1_rolling: day14_1_rolling(INPUT) => Ok(136),
+        test_day14_2_rolling: day14_2_rolling(INPUT) => Ok(64),
+        test_day14_2_no_cycle: day14_2_with(INPUT, &Params { cycles: 0 }) => Ok(104),
+        test_day14_2_one_cycle: day14_2_with(INPUT, &Params { cycles: 1 }) => Ok(87),
     }
 
-    #[test]
-    fn test_day14_2() {
-        let res = day14_2(INPUT);
+    fn platform() -> impl Strategy<Value = String> {
+        (1..8usize, 1..8usize)
+            .prop_flat_map(|(width, height)| {
+                prop::collection::vec(prop::collection::vec("[O#..]", width), height)
+            })
+            .prop_map(|rows| rows.iter().map(|row| row.concat() + "\n").collect())
+    }
+
+    differential_tests! {
+        test_day14_1_reference(platform()): day14_1 == reference::day14_1,
+        test_day14_2_reference(platform()): day14_2 == reference::day14_2,
+        test_day14_1_rolling_reference(platform()): day14_1_rolling == reference::day14_1,
+        test_day14_2_rolling_reference(platform()): day14_2_rolling == reference::day14_2,
+    }
 
-        assert_eq!(res, Ok(64))
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[.#O\n]{0,64}") {
+            check_parsers(&input);
+        }
     }
 }
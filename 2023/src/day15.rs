@@ -0,0 +1,203 @@
+//! Day 15: Lens Library
+//!
+//! `parse` reads the steps of the initialization sequence.
+
+use nom::{
+    branch::alt,
+    character::complete::{alpha1, char, u8},
+    combinator::{all_consuming, consumed, map, value},
+    multi::separated_list1,
+    sequence::{pair, preceded, terminated},
+    IResult,
+};
+
+use crate::parse::sp;
+
+pub type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
+
+/// The Holiday ASCII String Helper algorithm
+pub fn hash(s: &str) -> u8 {
+    s.bytes()
+        .fold(0, |acc, b| acc.wrapping_add(b).wrapping_mul(17))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// `-`
+    Remove,
+    /// `=` and the focal length of the lens
+    Insert(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step<'a> {
+    /// The whole step, which part 1 hashes
+    pub text: &'a str,
+    pub label: &'a str,
+    pub operation: Operation,
+}
+
+impl<'a> Step<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let operation = alt((
+            value(Operation::Remove, char('-')),
+            map(preceded(char('='), u8), Operation::Insert),
+        ));
+        map(
+            consumed(pair(alpha1, operation)),
+            |(text, (label, operation))| Self {
+                text,
+                label,
+                operation,
+            },
+        )(input)
+    }
+}
+
+/// The 256 boxes of lenses, a hash map keeping its entries in the order they
+/// were first inserted in each bucket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LensBoxes<'a> {
+    boxes: [Vec<(&'a str, u8)>; 256],
+}
+
+impl Default for LensBoxes<'_> {
+    fn default() -> Self {
+        Self {
+            boxes: std::array::from_fn(|_| Vec::new()),
+        }
+    }
+}
+
+impl<'a> LensBoxes<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puts the lens in the box of its label, replacing the lens with the
+    /// same label if any, in its place. Returns the replaced focal length
+    pub fn insert(&mut self, label: &'a str, focal_length: u8) -> Option<u8> {
+        let lenses = &mut self.boxes[hash(label) as usize];
+        match lenses.iter_mut().find(|(other, _)| *other == label) {
+            Some((_, old)) => Some(std::mem::replace(old, focal_length)),
+            None => {
+                lenses.push((label, focal_length));
+                None
+            }
+        }
+    }
+
+    /// Takes the lens with `label` out of its box, moving the lenses behind it
+    /// forward. Returns its focal length
+    pub fn remove(&mut self, label: &str) -> Option<u8> {
+        let lenses = &mut self.boxes[hash(label) as usize];
+        let i = lenses.iter().position(|(other, _)| *other == label)?;
+        Some(lenses.remove(i).1)
+    }
+
+    /// The focal length of the lens with `label`
+    pub fn get(&self, label: &str) -> Option<u8> {
+        self.boxes[hash(label) as usize]
+            .iter()
+            .find(|(other, _)| *other == label)
+            .map(|&(_, focal_length)| focal_length)
+    }
+
+    /// Sums the box number times the slot times the focal length of each lens,
+    /// counting both from one
+    pub fn focusing_power(&self) -> usize {
+        self.boxes
+            .iter()
+            .enumerate()
+            .flat_map(|(b, lenses)| {
+                lenses
+                    .iter()
+                    .enumerate()
+                    .map(move |(slot, &(_, focal_length))| {
+                        (b + 1) * (slot + 1) * focal_length as usize
+                    })
+            })
+            .sum()
+    }
+
+    pub fn apply(&mut self, step: &Step<'a>) {
+        match step.operation {
+            Operation::Remove => {
+                self.remove(step.label);
+            }
+            Operation::Insert(focal_length) => {
+                self.insert(step.label, focal_length);
+            }
+        }
+    }
+}
+
+/// The comma separated steps, the line break at the end ignored
+pub fn parse(input: &str) -> Result<Vec<Step<'_>>, ParseError<'_>> {
+    let (_, steps) = all_consuming(terminated(separated_list1(char(','), Step::parse), sp))(input)?;
+    Ok(steps)
+}
+
+pub fn part1(steps: &[Step]) -> u32 {
+    steps.iter().map(|step| hash(step.text) as u32).sum()
+}
+
+pub fn part2(steps: &[Step]) -> usize {
+    let mut boxes = LensBoxes::new();
+    for step in steps {
+        boxes.apply(step);
+    }
+    boxes.focusing_power()
+}
+
+pub fn day15_1(input: &str) -> Result<u32, ParseError<'_>> {
+    parse(input).map(|steps| part1(&steps))
+}
+
+pub fn day15_2(input: &str) -> Result<usize, ParseError<'_>> {
+    parse(input).map(|steps| part2(&steps))
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day15_1, day15_2, hash, LensBoxes};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7\n";
+
+    example_tests! {
+        test_hash: hash("HASH") => 52,
+        test_day15_1: day15_1(INPUT) => Ok(1320),
+        test_day15_2: day15_2(INPUT) => Ok(145),
+    }
+
+    #[test]
+    fn test_lens_boxes() {
+        let mut boxes = LensBoxes::new();
+        assert_eq!(boxes.insert("rn", 1), None);
+        assert_eq!(boxes.insert("cm", 2), None);
+        assert_eq!(boxes.focusing_power(), 1 + 2 * 2);
+        assert_eq!(boxes.insert("rn", 5), Some(1));
+        assert_eq!(boxes.get("rn"), Some(5));
+        assert_eq!(boxes.focusing_power(), 5 + 2 * 2);
+        assert_eq!(boxes.remove("rn"), Some(5));
+        assert_eq!(boxes.remove("rn"), None);
+        assert_eq!(boxes.get("rn"), None);
+        assert_eq!(boxes.focusing_power(), 2);
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert!(day15_1("rn=1,cm?\n").is_err());
+        assert!(day15_1("").is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[a-z=0-9,\n-]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
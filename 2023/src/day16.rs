@@ -0,0 +1,155 @@
+//! Day 16: The Floor Will Be Lava
+//!
+//! `parse` reads the contraption as an [`AsciiGrid`].
+
+use crate::grid::{AsciiGrid, Coord, Direction, Grid, MutGrid, NotAGrid, VecGrid};
+
+/// Where a beam going `dir` goes on after `tile`: on, turned, or split in two
+fn bounce(tile: u8, dir: Direction) -> (Direction, Option<Direction>) {
+    use Direction::*;
+    match (tile, dir) {
+        (b'/', Right) => (Up, None),
+        (b'/', Left) => (Down, None),
+        (b'/', Up) => (Right, None),
+        (b'/', Down) => (Left, None),
+        (b'\\', Right) => (Down, None),
+        (b'\\', Left) => (Up, None),
+        (b'\\', Up) => (Left, None),
+        (b'\\', Down) => (Right, None),
+        (b'|', Left | Right) => (Up, Some(Down)),
+        (b'-', Up | Down) => (Left, Some(Right)),
+        _ => (dir, None),
+    }
+}
+
+/// The bit of `dir` in the directions a tile was crossed in
+fn bit(dir: Direction) -> u8 {
+    match dir {
+        Direction::Up => 1,
+        Direction::Down => 2,
+        Direction::Left => 4,
+        Direction::Right => 8,
+    }
+}
+
+/// Follows the beams through the contraption. A beam that reaches a tile in a
+/// direction it was already crossed in would only retrace its steps, so each
+/// `(Coord, Direction)` is visited once
+struct Beams<'a> {
+    grid: &'a AsciiGrid<'a>,
+    /// The directions each tile was crossed in
+    crossed: VecGrid<u8>,
+    pending: Vec<(Coord, Direction)>,
+}
+
+impl<'a> Beams<'a> {
+    fn new(grid: &'a AsciiGrid<'a>) -> Self {
+        Self {
+            grid,
+            crossed: VecGrid::new(grid.width(), grid.height(), 0),
+            pending: Vec::new(),
+        }
+    }
+
+    /// How many tiles a beam entering at `start` going `dir` energizes
+    fn energized(&mut self, start: Coord, dir: Direction) -> usize {
+        self.crossed.grid_raw.fill(0);
+        self.pending.push((start, dir));
+        while let Some((coord, dir)) = self.pending.pop() {
+            let Some(&tile) = self.grid.get_coord(coord) else {
+                continue;
+            };
+            let crossed = self.crossed.get_coord_mut(coord).unwrap();
+            if *crossed & bit(dir) != 0 {
+                continue;
+            }
+            *crossed |= bit(dir);
+
+            let (dir, split) = bounce(tile, dir);
+            for dir in std::iter::once(dir).chain(split) {
+                if let Some(next) = coord.at_dir(dir) {
+                    self.pending.push((next, dir));
+                }
+            }
+        }
+        self.crossed
+            .grid_raw
+            .iter()
+            .filter(|&&dirs| dirs != 0)
+            .count()
+    }
+}
+
+/// The contraption's mirrors and splitters
+pub fn parse(input: &str) -> Result<AsciiGrid<'_>, NotAGrid> {
+    AsciiGrid::try_from_ascii(input.as_bytes())
+}
+
+pub fn part1(grid: &AsciiGrid) -> usize {
+    Beams::new(grid).energized(Coord { x: 0, y: 0 }, Direction::Right)
+}
+
+/// Tries the beam from every tile of the edges, going away from it. The
+/// buffers of the search are shared between the tries
+pub fn part2(grid: &AsciiGrid) -> usize {
+    let (width, height) = (grid.width(), grid.height());
+    let columns = (0..width).flat_map(|x| {
+        [
+            (Coord { x, y: 0 }, Direction::Down),
+            (Coord { x, y: height - 1 }, Direction::Up),
+        ]
+    });
+    let lines = (0..height).flat_map(|y| {
+        [
+            (Coord { x: 0, y }, Direction::Right),
+            (Coord { x: width - 1, y }, Direction::Left),
+        ]
+    });
+    let mut beams = Beams::new(grid);
+    columns
+        .chain(lines)
+        .map(|(start, dir)| beams.energized(start, dir))
+        .max()
+        .unwrap_or(0)
+}
+
+pub fn day16_1(input: &str) -> Result<usize, NotAGrid> {
+    parse(input).map(|grid| part1(&grid))
+}
+
+pub fn day16_2(input: &str) -> Result<usize, NotAGrid> {
+    parse(input).map(|grid| part2(&grid))
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day16_1, day16_2};
+    use proptest::prelude::*;
+
+    const INPUT: &str = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....
+";
+
+    example_tests! {
+        test_day16_1: day16_1(INPUT) => Ok(46),
+        test_day16_2: day16_2(INPUT) => Ok(51),
+        test_day16_1_loop: day16_1("-\\\n\\/\n") => Ok(4),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[.|/\\\\\n-]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Day 17: Clumsy Crucible
+//!
+//! `parse` reads the heat loss of each city block.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::grid::{AsciiGrid, Coord, Direction, Grid, MutGrid, VecGrid};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Day17Error {
+    Empty,
+    NotAGrid,
+    NotADigit(Coord),
+    /// The crucible can't stop on the factory after a long enough run
+    NoPath,
+}
+
+/// How many blocks a crucible must and can go in a straight line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crucible {
+    /// Before it can turn or stop
+    pub min_run: usize,
+    /// Before it has to turn
+    pub max_run: usize,
+}
+
+impl Crucible {
+    pub const NORMAL: Self = Self {
+        min_run: 1,
+        max_run: 3,
+    };
+    pub const ULTRA: Self = Self {
+        min_run: 4,
+        max_run: 10,
+    };
+}
+
+/// In the order of [`Direction`]'s variants
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+fn turns(dir: Direction) -> [Direction; 2] {
+    if dir.is_vertical() {
+        [Direction::Left, Direction::Right]
+    } else {
+        [Direction::Up, Direction::Down]
+    }
+}
+
+/// A crucible on a block, how it got there and for how many blocks it went
+/// straight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct State {
+    x: usize,
+    y: usize,
+    dir: usize,
+    run: usize,
+}
+
+/// Dijkstra's search over the [`State`]s, from the top left block to the
+/// bottom right one
+pub fn min_heat_loss(city: &VecGrid<u8>, crucible: Crucible) -> Result<u32, Day17Error> {
+    let (width, height) = (city.width(), city.height());
+    let states = crucible.max_run + 1;
+    let index = |s: &State| ((s.y * width + s.x) * 4 + s.dir) * states + s.run;
+    let mut best = vec![u32::MAX; width * height * 4 * states];
+    let mut queue = BinaryHeap::new();
+    // the first block's heat loss doesn't count, and it can leave either way
+    for dir in [Direction::Right, Direction::Down] {
+        let start = State {
+            x: 0,
+            y: 0,
+            dir: dir as usize,
+            run: 0,
+        };
+        best[index(&start)] = 0;
+        queue.push(Reverse((0, start)));
+    }
+
+    while let Some(Reverse((loss, state))) = queue.pop() {
+        if loss > best[index(&state)] {
+            continue;
+        }
+        if (state.x, state.y) == (width - 1, height - 1) && state.run >= crucible.min_run {
+            return Ok(loss);
+        }
+        let dir = DIRECTIONS[state.dir];
+        let straight = (state.run < crucible.max_run).then_some((dir, state.run + 1));
+        let turned = (state.run >= crucible.min_run || state.run == 0)
+            .then(|| turns(dir).map(|dir| (dir, 1)))
+            .into_iter()
+            .flatten();
+        let coord = Coord {
+            x: state.x,
+            y: state.y,
+        };
+        for (dir, run) in straight.into_iter().chain(turned) {
+            let Some(Coord { x, y }) = coord.at_dir(dir) else {
+                continue;
+            };
+            let Some(&block) = city.get(x, y) else {
+                continue;
+            };
+            let next = State {
+                x,
+                y,
+                dir: dir as usize,
+                run,
+            };
+            let loss = loss + block as u32;
+            if loss < best[index(&next)] {
+                best[index(&next)] = loss;
+                queue.push(Reverse((loss, next)));
+            }
+        }
+    }
+    Err(Day17Error::NoPath)
+}
+
+/// The heat loss of each block
+pub fn parse(input: &str) -> Result<VecGrid<u8>, Day17Error> {
+    if input.trim().is_empty() {
+        return Err(Day17Error::Empty);
+    }
+    let ascii = AsciiGrid::try_from_ascii(input.as_bytes()).map_err(|_| Day17Error::NotAGrid)?;
+    let mut city = VecGrid::new(ascii.width(), ascii.height(), 0);
+    for coord in ascii.coord_iter() {
+        let c = *ascii.get_coord(coord).unwrap();
+        if !c.is_ascii_digit() {
+            return Err(Day17Error::NotADigit(coord));
+        }
+        *city.get_coord_mut(coord).unwrap() = c - b'0';
+    }
+    Ok(city)
+}
+
+pub fn part1(city: &VecGrid<u8>) -> Result<u32, Day17Error> {
+    min_heat_loss(city, Crucible::NORMAL)
+}
+
+pub fn part2(city: &VecGrid<u8>) -> Result<u32, Day17Error> {
+    min_heat_loss(city, Crucible::ULTRA)
+}
+
+pub fn day17_1(input: &str) -> Result<u32, Day17Error> {
+    parse(input).and_then(|city| part1(&city))
+}
+
+pub fn day17_2(input: &str) -> Result<u32, Day17Error> {
+    parse(input).and_then(|city| part2(&city))
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day17_1, day17_2, Day17Error};
+    use crate::grid::Coord;
+    use proptest::prelude::*;
+
+    const INPUT: &str = "2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533
+";
+
+    const INPUT_2: &str = "111111111111
+999999999991
+999999999991
+999999999991
+999999999991
+";
+
+    example_tests! {
+        test_day17_1: day17_1(INPUT) => Ok(102),
+        test_day17_2: day17_2(INPUT) => Ok(94),
+        test_day17_2_long_turn: day17_2(INPUT_2) => Ok(71),
+        test_day17_2_too_small: day17_2("12\n34\n") => Err(Day17Error::NoPath),
+        test_day17_1_not_a_digit: day17_1("12\n3x\n") => Err(Day17Error::NotADigit(Coord { x: 1, y: 1 })),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[1-9\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
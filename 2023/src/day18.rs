@@ -0,0 +1,298 @@
+//! Day 18: Lavaduct Lagoon
+//!
+//! `parse` reads each instruction of the dig plan, both as written and as
+//! hidden in its colour.
+
+use nom::{
+    bytes::complete::{tag, take_while_m_n},
+    character::complete::{char, one_of, u32},
+    combinator::{map_opt, map_res},
+    sequence::delimited,
+    IResult,
+};
+
+use crate::grid::Direction;
+
+pub type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
+
+/// Digs `len` meters towards `dir`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trench {
+    pub dir: Direction,
+    pub len: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    /// What the plan says, for part 1
+    pub trench: Trench,
+    /// What the colour says, for part 2: five hex digits of length then a
+    /// direction
+    pub colour: Trench,
+}
+
+impl Instruction {
+    fn parse(input: &str) -> IResult<&str, Self> {
+        let (i, dir) = one_of("UDLR")(input)?;
+        let (i, _) = char(' ')(i)?;
+        let (i, len) = u32(i)?;
+        let (i, colour) = delimited(
+            tag(" (#"),
+            map_opt(
+                map_res(
+                    take_while_m_n(6, 6, |c: char| c.is_ascii_hexdigit()),
+                    |hex| i64::from_str_radix(hex, 16),
+                ),
+                Self::from_colour,
+            ),
+            char(')'),
+        )(i)?;
+        let dir = match dir {
+            'U' => Direction::Up,
+            'D' => Direction::Down,
+            'L' => Direction::Left,
+            _ => Direction::Right,
+        };
+        let trench = Trench {
+            dir,
+            len: len as i64,
+        };
+        Ok((i, Self { trench, colour }))
+    }
+
+    fn from_colour(colour: i64) -> Option<Trench> {
+        let dir = match colour & 0xf {
+            0 => Direction::Right,
+            1 => Direction::Down,
+            2 => Direction::Left,
+            3 => Direction::Up,
+            _ => return None,
+        };
+        Some(Trench {
+            dir,
+            len: colour >> 4,
+        })
+    }
+}
+
+/// How many cubic meters the lagoon dug along `trenches` holds, edge included.
+/// The shoelace formula gives the area inside the path through the middle of
+/// the edge blocks, and Pick's theorem the blocks strictly inside it, to which
+/// the edge blocks are added
+pub fn lagoon_size(trenches: impl Iterator<Item = Trench>) -> i64 {
+    let (mut x, mut y) = (0i64, 0i64);
+    let mut double_area = 0;
+    let mut edge = 0;
+    for Trench { dir, len } in trenches {
+        let (nx, ny) = match dir {
+            Direction::Up => (x, y - len),
+            Direction::Down => (x, y + len),
+            Direction::Left => (x - len, y),
+            Direction::Right => (x + len, y),
+        };
+        double_area += x * ny - nx * y;
+        edge += len;
+        (x, y) = (nx, ny);
+    }
+    let inside = (double_area.abs() - edge) / 2 + 1;
+    inside + edge
+}
+
+/// The dig plan, one instruction per line
+pub fn parse(input: &str) -> Result<Vec<Instruction>, ParseError<'_>> {
+    input
+        .lines()
+        .map(|line| Ok(Instruction::parse(line)?.1))
+        .collect()
+}
+
+pub fn part1(plan: &[Instruction]) -> i64 {
+    lagoon_size(plan.iter().map(|instruction| instruction.trench))
+}
+
+pub fn part2(plan: &[Instruction]) -> i64 {
+    lagoon_size(plan.iter().map(|instruction| instruction.colour))
+}
+
+pub fn day18_1(input: &str) -> Result<i64, ParseError<'_>> {
+    parse(input).map(|plan| part1(&plan))
+}
+
+pub fn day18_2(input: &str) -> Result<i64, ParseError<'_>> {
+    parse(input).map(|plan| part2(&plan))
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    use std::collections::HashSet;
+
+    /// Digs every cube of the trenches, then counts the cubes left around them
+    /// by filling the outside
+    fn lagoon_size(trenches: impl Iterator<Item = (char, i64)>) -> i64 {
+        let (mut x, mut y) = (0i64, 0i64);
+        let mut dug = HashSet::from([(x, y)]);
+        for (dir, len) in trenches {
+            for _ in 0..len {
+                match dir {
+                    'U' => y -= 1,
+                    'D' => y += 1,
+                    'L' => x -= 1,
+                    _ => x += 1,
+                }
+                dug.insert((x, y));
+            }
+        }
+        let min_x = dug.iter().map(|&(x, _)| x).min().unwrap() - 1;
+        let max_x = dug.iter().map(|&(x, _)| x).max().unwrap() + 1;
+        let min_y = dug.iter().map(|&(_, y)| y).min().unwrap() - 1;
+        let max_y = dug.iter().map(|&(_, y)| y).max().unwrap() + 1;
+        let mut outside = HashSet::new();
+        let mut to_fill = vec![(min_x, min_y)];
+        while let Some((x, y)) = to_fill.pop() {
+            if !(min_x..=max_x).contains(&x)
+                || !(min_y..=max_y).contains(&y)
+                || dug.contains(&(x, y))
+                || !outside.insert((x, y))
+            {
+                continue;
+            }
+            to_fill.extend([(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]);
+        }
+        (max_x - min_x + 1) * (max_y - min_y + 1) - outside.len() as i64
+    }
+
+    pub fn day18_1(input: &str) -> i64 {
+        lagoon_size(input.lines().map(|line| {
+            let mut words = line.split(' ');
+            let dir = words.next().unwrap().chars().next().unwrap();
+            (dir, words.next().unwrap().parse().unwrap())
+        }))
+    }
+
+    pub fn day18_2(input: &str) -> i64 {
+        lagoon_size(input.lines().map(|line| {
+            let colour = &line[line.find('#').unwrap() + 1..][..6];
+            let dir = ['R', 'D', 'L', 'U'][colour[5..].parse::<usize>().unwrap()];
+            (dir, i64::from_str_radix(&colour[..5], 16).unwrap())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day18_1, day18_2, reference};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)
+";
+
+    example_tests! {
+        test_day18_1: day18_1(INPUT) => Ok(62),
+        test_day18_2: day18_2(INPUT) => Ok(952408144115),
+        test_day18_1_square: day18_1("R 2 (#000020)\nD 2 (#000021)\nL 2 (#000022)\nU 2 (#000023)\n") => Ok(9),
+    }
+
+    /// Dig plans around a region whose columns of cubes are each a single run
+    /// of rows, overlapping the run of the previous column, so that the trench
+    /// never touches itself. The colours dig the same shape `scale` times
+    /// larger
+    fn plans() -> impl Strategy<Value = String> {
+        let runs = prop::collection::vec((0..8i64, 0..8i64), 1..8);
+        (runs, 1..4i64).prop_map(|(runs, scale)| {
+            let mut columns: Vec<(i64, i64)> = vec![];
+            for (a, b) in runs {
+                let (mut low, mut high) = (a.min(b), a.max(b));
+                if let Some(&(previous_low, previous_high)) = columns.last() {
+                    low = low.min(previous_high);
+                    high = high.max(previous_low);
+                }
+                columns.push((low, high));
+            }
+            let inside = |x: i64, y: i64| {
+                usize::try_from(x)
+                    .ok()
+                    .and_then(|x| columns.get(x))
+                    .is_some_and(|&(low, high)| (low..=high).contains(&y))
+            };
+            // the trench follows the corners of the cubes, from the top left
+            // one which it leaves to the right
+            let start = (0, columns[0].0);
+            let (mut x, mut y) = start;
+            let mut trenches: Vec<(char, i64)> = vec![];
+            let mut dir = 'R';
+            loop {
+                match trenches.last_mut() {
+                    Some((last, len)) if *last == dir => *len += 1,
+                    _ => trenches.push((dir, 1)),
+                }
+                match dir {
+                    'U' => y -= 1,
+                    'D' => y += 1,
+                    'L' => x -= 1,
+                    _ => x += 1,
+                }
+                if (x, y) == start {
+                    break;
+                }
+                let edges = [
+                    ('U', inside(x - 1, y - 1) != inside(x, y - 1)),
+                    ('D', inside(x - 1, y) != inside(x, y)),
+                    ('L', inside(x - 1, y - 1) != inside(x - 1, y)),
+                    ('R', inside(x, y - 1) != inside(x, y)),
+                ];
+                let back = match dir {
+                    'U' => 'D',
+                    'D' => 'U',
+                    'L' => 'R',
+                    _ => 'L',
+                };
+                dir = edges
+                    .into_iter()
+                    .find(|&(next, edge)| edge && next != back)
+                    .unwrap()
+                    .0;
+            }
+            trenches
+                .into_iter()
+                .map(|(dir, len)| {
+                    let colour_dir = "RDLU".find(dir).unwrap();
+                    format!("{dir} {len} (#{:05x}{colour_dir})\n", len * scale)
+                })
+                .collect()
+        })
+    }
+
+    differential_tests! {
+        test_day18_1_reference(plans()): day18_1 == reference::day18_1,
+        test_day18_2_reference(plans()): day18_2 == reference::day18_2,
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_colour() {
+        assert!(day18_1("R 6 (#70c714)\n").is_err());
+        assert!(day18_1("R 6 (#70c7)\n").is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[UDLR0-9 ()#a-f\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -0,0 +1,568 @@
+//! Day 19: Aplenty
+//!
+//! `parse` reads the workflows into a [`System`], each rule pointing to the
+//! workflow it sends parts to, and the parts to sort.
+
+use std::{cmp::Ordering, collections::HashMap, ops::Range};
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{alpha1, char, u32},
+    combinator::{map, opt, value},
+    multi::separated_list1,
+    sequence::{delimited, pair, preceded, terminated},
+    IResult,
+};
+
+pub type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
+
+#[derive(Debug, PartialEq)]
+pub enum Day19Error<'a> {
+    Parse(ParseError<'a>),
+    NoInWorkflow,
+    UnknownWorkflow(&'a str),
+    /// A part could go around the workflow forever
+    Cycle(&'a str),
+}
+
+impl<'a> From<ParseError<'a>> for Day19Error<'a> {
+    fn from(e: ParseError<'a>) -> Self {
+        Self::Parse(e)
+    }
+}
+
+pub type Rating = u64;
+
+/// Ratings are read as `u32`s, so that a bound one past a rating and the sum of
+/// a part's ratings can't overflow
+fn rating(input: &str) -> IResult<&str, Rating> {
+    map(u32, Rating::from)(input)
+}
+
+/// The ratings a part has, in the order of [`Category`]'s variants
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    ExtremelyCool,
+    Musical,
+    Aerodynamic,
+    Shiny,
+}
+
+impl Category {
+    fn parse(input: &str) -> IResult<&str, Self> {
+        alt((
+            value(Self::ExtremelyCool, char('x')),
+            value(Self::Musical, char('m')),
+            value(Self::Aerodynamic, char('a')),
+            value(Self::Shiny, char('s')),
+        ))(input)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Part {
+    pub ratings: [Rating; 4],
+}
+
+impl Part {
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        let field = |category| preceded(terminated(char(category), char('=')), rating);
+        let (i, _) = char('{')(input)?;
+        let (i, x) = terminated(field('x'), char(','))(i)?;
+        let (i, m) = terminated(field('m'), char(','))(i)?;
+        let (i, a) = terminated(field('a'), char(','))(i)?;
+        let (i, s) = terminated(field('s'), char('}'))(i)?;
+        Ok((
+            i,
+            Self {
+                ratings: [x, m, a, s],
+            },
+        ))
+    }
+}
+
+/// Every part whose ratings fall in the four ranges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartRanges {
+    pub ratings: [Range<Rating>; 4],
+}
+
+impl PartRanges {
+    /// How many different parts there are in the ranges
+    pub fn combinations(&self) -> u64 {
+        self.ratings
+            .iter()
+            .map(|range| range.end.saturating_sub(range.start))
+            .product()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ratings.iter().any(|range| range.is_empty())
+    }
+}
+
+/// A rule's test, like `a<2006`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    pub category: Category,
+    /// `Less` for `<`, `Greater` for `>`
+    pub ordering: Ordering,
+    pub rating: Rating,
+}
+
+impl Condition {
+    fn parse(input: &str) -> IResult<&str, Self> {
+        let (i, category) = Category::parse(input)?;
+        let (i, ordering) = alt((
+            value(Ordering::Less, char('<')),
+            value(Ordering::Greater, char('>')),
+        ))(i)?;
+        let (i, rating) = rating(i)?;
+        Ok((
+            i,
+            Self {
+                category,
+                ordering,
+                rating,
+            },
+        ))
+    }
+
+    pub fn matches(&self, part: &Part) -> bool {
+        part.ratings[self.category as usize].cmp(&self.rating) == self.ordering
+    }
+
+    /// Splits the ranges into the parts that match and those that don't,
+    /// either of which may be empty
+    pub fn split_ranges(&self, ranges: PartRanges) -> (PartRanges, PartRanges) {
+        let range = &ranges.ratings[self.category as usize];
+        let bound = match self.ordering {
+            Ordering::Less => self.rating,
+            _ => self.rating + 1,
+        };
+        let bound = bound.clamp(range.start, range.end.max(range.start));
+        let (below, above) = (range.start..bound, bound..range.end);
+        let (matching, rest) = match self.ordering {
+            Ordering::Less => (below, above),
+            _ => (above, below),
+        };
+        let mut matching_ranges = ranges.clone();
+        matching_ranges.ratings[self.category as usize] = matching;
+        let mut rest_ranges = ranges;
+        rest_ranges.ratings[self.category as usize] = rest;
+        (matching_ranges, rest_ranges)
+    }
+}
+
+/// Where a rule sends a part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Accept,
+    Reject,
+    /// The index of the workflow in the [`System`]
+    Workflow(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    /// The rule matches every part without one
+    pub condition: Option<Condition>,
+    pub target: Target,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workflow {
+    pub rules: Vec<Rule>,
+}
+
+impl Workflow {
+    /// Where the first rule that matches `part` sends it
+    pub fn run(&self, part: &Part) -> Target {
+        self.rules
+            .iter()
+            .find(|rule| rule.condition.is_none_or(|cond| cond.matches(part)))
+            .map_or(Target::Reject, |rule| rule.target)
+    }
+
+    /// Where each rule sends the parts of `ranges` that reach it, which the
+    /// workflow may split into several ranges
+    pub fn run_ranges(&self, mut ranges: PartRanges) -> Vec<(PartRanges, Target)> {
+        let mut res = Vec::new();
+        for rule in &self.rules {
+            let Some(condition) = rule.condition else {
+                res.push((ranges, rule.target));
+                return res;
+            };
+            let (matching, rest) = condition.split_ranges(ranges);
+            if !matching.is_empty() {
+                res.push((matching, rule.target));
+            }
+            if rest.is_empty() {
+                return res;
+            }
+            ranges = rest;
+        }
+        res.push((ranges, Target::Reject));
+        res
+    }
+}
+
+/// A rule as written, naming the workflow it sends parts to
+type RawRule<'a> = (Option<Condition>, &'a str);
+
+/// A workflow as written: its name and rules
+type RawWorkflow<'a> = (&'a str, Vec<RawRule<'a>>);
+
+fn parse_workflow(input: &str) -> IResult<&str, RawWorkflow<'_>> {
+    let rule = pair(opt(terminated(Condition::parse, char(':'))), alpha1);
+    let (i, name) = alpha1(input)?;
+    let (i, rules) = delimited(char('{'), separated_list1(char(','), rule), char('}'))(i)?;
+    Ok((i, (name, rules)))
+}
+
+/// The workflows, linked to each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct System {
+    pub workflows: Vec<Workflow>,
+    /// The index of the `in` workflow
+    pub start: usize,
+}
+
+impl System {
+    /// Links the rules to the workflows they name, checking that no part can
+    /// loop through them
+    fn link<'a>(raw: Vec<RawWorkflow<'a>>) -> Result<Self, Day19Error<'a>> {
+        let indices: HashMap<&str, usize> = raw
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| (*name, i))
+            .collect();
+        let start = *indices.get("in").ok_or(Day19Error::NoInWorkflow)?;
+        let mut workflows = Vec::with_capacity(raw.len());
+        for (_, rules) in &raw {
+            let mut linked = Vec::with_capacity(rules.len());
+            for &(condition, target) in rules {
+                let target = match target {
+                    "A" => Target::Accept,
+                    "R" => Target::Reject,
+                    name => Target::Workflow(
+                        *indices.get(name).ok_or(Day19Error::UnknownWorkflow(name))?,
+                    ),
+                };
+                linked.push(Rule { condition, target });
+            }
+            workflows.push(Workflow { rules: linked });
+        }
+        let system = Self { workflows, start };
+        if let Some(i) = system.find_cycle() {
+            return Err(Day19Error::Cycle(raw[i].0));
+        }
+        Ok(system)
+    }
+
+    /// A workflow on a cycle, found by a depth first search
+    fn find_cycle(&self) -> Option<usize> {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum Mark {
+            New,
+            Open,
+            Done,
+        }
+        fn visit(system: &System, marks: &mut [Mark], i: usize) -> Option<usize> {
+            match marks[i] {
+                Mark::Open => return Some(i),
+                Mark::Done => return None,
+                Mark::New => marks[i] = Mark::Open,
+            }
+            for rule in &system.workflows[i].rules {
+                if let Target::Workflow(next) = rule.target {
+                    if let Some(cycle) = visit(system, marks, next) {
+                        return Some(cycle);
+                    }
+                }
+            }
+            marks[i] = Mark::Done;
+            None
+        }
+        let mut marks = vec![Mark::New; self.workflows.len()];
+        (0..self.workflows.len()).find_map(|i| visit(self, &mut marks, i))
+    }
+
+    pub fn accepts(&self, part: &Part) -> bool {
+        let mut target = Target::Workflow(self.start);
+        loop {
+            match target {
+                Target::Accept => return true,
+                Target::Reject => return false,
+                Target::Workflow(i) => target = self.workflows[i].run(part),
+            }
+        }
+    }
+
+    /// How many of the parts in `ranges` are accepted
+    pub fn accepted_combinations(&self, ranges: PartRanges) -> u64 {
+        let mut pending = vec![(ranges, Target::Workflow(self.start))];
+        let mut accepted = 0;
+        while let Some((ranges, target)) = pending.pop() {
+            match target {
+                Target::Accept => accepted += ranges.combinations(),
+                Target::Reject => (),
+                Target::Workflow(i) => pending.extend(self.workflows[i].run_ranges(ranges)),
+            }
+        }
+        accepted
+    }
+}
+
+fn parse_input(input: &str) -> IResult<&str, (Vec<RawWorkflow<'_>>, Vec<Part>)> {
+    let (i, workflows) = separated_list1(char('\n'), parse_workflow)(input)?;
+    let (i, _) = tag("\n\n")(i)?;
+    let (i, parts) = separated_list1(char('\n'), Part::parse)(i)?;
+    let (i, _) = opt(char('\n'))(i)?;
+    Ok((i, (workflows, parts)))
+}
+
+/// The linked workflows and the parts
+pub fn parse(input: &str) -> Result<(System, Vec<Part>), Day19Error<'_>> {
+    let (_, (workflows, parts)) = parse_input(input)?;
+    Ok((System::link(workflows)?, parts))
+}
+
+pub fn part1((system, parts): &(System, Vec<Part>)) -> Rating {
+    parts
+        .iter()
+        .filter(|part| system.accepts(part))
+        .map(|part| part.ratings.iter().sum::<Rating>())
+        .sum()
+}
+
+pub fn part2((system, _): &(System, Vec<Part>)) -> u64 {
+    system.accepted_combinations(PartRanges {
+        ratings: [1..4001, 1..4001, 1..4001, 1..4001],
+    })
+}
+
+pub fn day19_1(input: &str) -> Result<Rating, Day19Error<'_>> {
+    parse(input).map(|parsed| part1(&parsed))
+}
+
+pub fn day19_2(input: &str) -> Result<u64, Day19Error<'_>> {
+    parse(input).map(|parsed| part2(&parsed))
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    use std::collections::HashMap;
+
+    use itertools::Itertools;
+
+    /// The rules of each workflow as written, and the ratings of each part
+    fn parse(input: &str) -> (HashMap<&str, Vec<&str>>, Vec<[u64; 4]>) {
+        let (workflows, parts) = input.split_once("\n\n").unwrap();
+        let workflows = workflows
+            .lines()
+            .map(|line| {
+                let (name, rules) = line.trim_end_matches('}').split_once('{').unwrap();
+                (name, rules.split(',').collect())
+            })
+            .collect();
+        let parts = parts
+            .lines()
+            .map(|line| {
+                let ratings: Vec<u64> = line
+                    .split(|c: char| !c.is_ascii_digit())
+                    .filter(|n| !n.is_empty())
+                    .map(|n| n.parse().unwrap())
+                    .collect();
+                [ratings[0], ratings[1], ratings[2], ratings[3]]
+            })
+            .collect();
+        (workflows, parts)
+    }
+
+    fn accepts(workflows: &HashMap<&str, Vec<&str>>, part: [u64; 4]) -> bool {
+        let mut workflow = "in";
+        'workflows: loop {
+            for rule in &workflows[workflow] {
+                let target = match rule.split_once(':') {
+                    Some((condition, target)) => {
+                        let rating = part["xmas".find(&condition[..1]).unwrap()];
+                        let bound: u64 = condition[2..].parse().unwrap();
+                        let matches = match &condition[1..2] {
+                            "<" => rating < bound,
+                            _ => rating > bound,
+                        };
+                        if !matches {
+                            continue;
+                        }
+                        target
+                    }
+                    None => rule,
+                };
+                match target {
+                    "A" => return true,
+                    "R" => return false,
+                    next => {
+                        workflow = next;
+                        continue 'workflows;
+                    }
+                }
+            }
+            return false;
+        }
+    }
+
+    pub fn day19_1(input: &str) -> u64 {
+        let (workflows, parts) = parse(input);
+        parts
+            .into_iter()
+            .filter(|&part| accepts(&workflows, part))
+            .map(|part| part.iter().sum::<u64>())
+            .sum()
+    }
+
+    /// Cuts each rating where a rule compares it, and runs a single part for
+    /// each block of ratings between the cuts
+    pub fn day19_2(input: &str) -> u64 {
+        let (workflows, _) = parse(input);
+        let mut cuts: [Vec<u64>; 4] = std::array::from_fn(|_| vec![1, 4001]);
+        for condition in workflows
+            .values()
+            .flatten()
+            .filter_map(|rule| Some(rule.split_once(':')?.0))
+        {
+            let bound: u64 = condition[2..].parse().unwrap();
+            let cut = if &condition[1..2] == "<" {
+                bound
+            } else {
+                bound + 1
+            };
+            cuts["xmas".find(&condition[..1]).unwrap()].push(cut.clamp(1, 4001));
+        }
+        for cuts in &mut cuts {
+            cuts.sort_unstable();
+            cuts.dedup();
+        }
+        let blocks = cuts.map(|cuts| {
+            cuts.windows(2)
+                .map(|w| (w[0], w[1] - w[0]))
+                .collect::<Vec<_>>()
+        });
+        blocks
+            .iter()
+            .multi_cartesian_product()
+            .filter(|blocks| accepts(&workflows, [0, 1, 2, 3].map(|i| blocks[i].0)))
+            .map(|blocks| blocks.iter().map(|&&(_, len)| len).product::<u64>())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day19_1, day19_2, reference, Day19Error};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}
+";
+
+    example_tests! {
+        test_day19_1: day19_1(INPUT) => Ok(19114),
+        test_day19_2: day19_2(INPUT) => Ok(167409079868000),
+        test_day19_2_accept_all: day19_2("in{A}\n\n{x=1,m=1,a=1,s=1}\n") => Ok(4000u64.pow(4)),
+        test_day19_2_one_rating: day19_2("in{x<2:A,R}\n\n{x=1,m=1,a=1,s=1}\n") => Ok(4000u64.pow(3)),
+        test_day19_1_no_in: day19_1("px{A}\n\n{x=1,m=1,a=1,s=1}\n") => Err(Day19Error::NoInWorkflow),
+        test_day19_1_unknown: day19_1("in{a<5:px,A}\n\n{x=1,m=1,a=1,s=1}\n") => Err(Day19Error::UnknownWorkflow("px")),
+        test_day19_1_cycle: day19_1("in{a<5:px,A}\npx{in}\n\n{x=1,m=1,a=1,s=1}\n") => Err(Day19Error::Cycle("in")),
+    }
+
+    #[test]
+    fn huge_ratings_are_rejected() {
+        let condition = "in{x>18446744073709551615:A,R}\n\n{x=1,m=1,a=1,s=1}\n";
+        assert!(matches!(day19_2(condition), Err(Day19Error::Parse(_))));
+        let part = "in{A}\n\n{x=1,m=1,a=1,s=18446744073709551615}\n";
+        assert!(matches!(day19_1(part), Err(Day19Error::Parse(_))));
+    }
+
+    /// A few workflows, each only sending parts to the ones after it so that
+    /// there is no cycle, and a few parts. A workflow may end without a rule
+    /// matching every part
+    fn systems() -> impl Strategy<Value = String> {
+        let condition = (0..4usize, any::<bool>(), 0..=4001u64);
+        let workflow = (
+            prop::collection::vec((condition, 0..6usize), 0..4),
+            prop::option::of(0..6usize),
+        );
+        let part = prop::array::uniform4(1..=4000u64);
+        (
+            prop::collection::vec(workflow, 1..6),
+            prop::collection::vec(part, 1..4),
+        )
+            .prop_map(|(workflows, parts)| {
+                let count = workflows.len();
+                let name = |i: usize| match i {
+                    0 => "in".to_string(),
+                    _ => format!("w{}", (b'a' + i as u8) as char),
+                };
+                // past the last workflow, parts are accepted
+                let target = |from: usize, to: usize| match to {
+                    0 => "R".to_string(),
+                    to if from + to < count => name(from + to),
+                    _ => "A".to_string(),
+                };
+                let mut input = String::new();
+                for (i, (rules, last)) in workflows.into_iter().enumerate() {
+                    let mut rules: Vec<String> = rules
+                        .into_iter()
+                        .map(|((category, less, rating), to)| {
+                            let category = &"xmas"[category..=category];
+                            let ordering = if less { '<' } else { '>' };
+                            format!("{category}{ordering}{rating}:{}", target(i, to))
+                        })
+                        .collect();
+                    match last {
+                        Some(to) => rules.push(target(i, to)),
+                        None if rules.is_empty() => rules.push("A".to_string()),
+                        None => (),
+                    }
+                    input += &format!("{}{{{}}}\n", name(i), rules.join(","));
+                }
+                input += "\n";
+                for [x, m, a, s] in parts {
+                    input += &format!("{{x={x},m={m},a={a},s={s}}}\n");
+                }
+                input
+            })
+    }
+
+    differential_tests! {
+        test_day19_1_reference(systems()): day19_1 == reference::day19_1,
+        test_day19_2_reference(systems()): day19_2 == reference::day19_2,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[{}xmas<>:,=ARa-z0-9\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use itertools::Itertools;
+//! Day 2: Cube Conundrum
+//!
+//! `parse` reads each [`Game`] and the [`BallSet`]s shown in it.
+
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -11,7 +14,7 @@ use nom::{
 pub type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
 
 #[derive(Debug, Default)]
-struct BallSet {
+pub struct BallSet {
     red_cnt: u32,
     green_cnt: u32,
     blue_cnt: u32,
@@ -56,7 +59,7 @@ impl BallSet {
 }
 
 #[derive(Debug)]
-struct Game {
+pub struct Game {
     id: u32,
     sets: Vec<BallSet>,
 }
@@ -88,27 +91,41 @@ impl Game {
     }
 }
 
-pub fn day2_1(input: &str) -> Result<u32, ParseError> {
-    input
-        .lines()
-        .map(|s| Ok(Game::parse(s)?.1))
-        .filter_ok(|game| game.possible_to_have(12, 13, 14))
-        .map_ok(|game| game.id)
-        .fold_ok(0, |acc, n| acc + n)
+/// Every game, in order
+pub fn parse(input: &str) -> Result<Vec<Game>, ParseError<'_>> {
+    input.lines().map(|s| Ok(Game::parse(s)?.1)).collect()
+}
+
+pub fn part1(games: &[Game]) -> u32 {
+    games
+        .iter()
+        .filter(|game| game.possible_to_have(12, 13, 14))
+        .map(|game| game.id)
+        .sum()
+}
+
+pub fn part2(games: &[Game]) -> u32 {
+    games
+        .iter()
+        .map(|game| game.smallest_ball_set().to_tuple())
+        .map(|(r, g, b)| r * g * b)
+        .sum()
 }
 
-pub fn day2_2(input: &str) -> Result<u32, ParseError> {
-    input
-        .lines()
-        .map(|s| Ok(Game::parse(s)?.1))
-        .map_ok(|game| game.smallest_ball_set().to_tuple())
-        .map_ok(|(r, g, b)| r * g * b)
-        .fold_ok(0, |acc, n| acc + n)
+pub fn day2_1(input: &str) -> Result<u32, ParseError<'_>> {
+    parse(input).map(|games| part1(&games))
 }
 
+pub fn day2_2(input: &str) -> Result<u32, ParseError<'_>> {
+    parse(input).map(|games| part2(&games))
+}
+
+harness::check_parsers!(Game::parse);
+
 #[cfg(test)]
 mod tests {
-    use super::{day2_1, day2_2};
+    use super::{check_parsers, day2_1, day2_2};
+    use proptest::prelude::*;
 
     const INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
 Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
@@ -117,15 +134,15 @@ Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
 ";
 
-    #[test]
-    fn test_day2_1() {
-        let res = day2_1(INPUT);
-        assert_eq!(res, Ok(8))
+    example_tests! {
+        test_day2_1: day2_1(INPUT) => Ok(8),
+        test_day2_2: day2_2(INPUT) => Ok(2286),
     }
 
-    #[test]
-    fn test_day2_2() {
-        let res = day2_2(INPUT);
-        assert_eq!(res, Ok(2286))
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[Game :0-9,;redgbluen\n]{0,64}") {
+            check_parsers(&input);
+        }
     }
 }
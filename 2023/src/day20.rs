@@ -0,0 +1,330 @@
+//! Day 20: Pulse Propagation
+//!
+//! `parse` reads the modules into a [`Network`], each output pointing to the
+//! module it sends pulses to.
+
+use std::collections::{HashMap, VecDeque};
+
+use harness::runner::cancelled;
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{alpha1, char},
+    combinator::{opt, value},
+    multi::separated_list1,
+    IResult,
+};
+use num::Integer;
+
+pub type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
+
+#[derive(Debug, PartialEq)]
+pub enum Day20Error<'a> {
+    Parse(ParseError<'a>),
+    NoBroadcaster,
+    /// Part 2 expects `rx` to be fed by a single conjunction
+    NoConjunctionBeforeRx,
+    /// An input of the conjunction before `rx` sent no high pulse in that many
+    /// button presses
+    NoCycle(usize),
+    /// A press sent more than that many pulses, as conjunctions feeding each
+    /// other can keep sending them forever
+    TooManyPulses(usize),
+}
+
+impl<'a> From<ParseError<'a>> for Day20Error<'a> {
+    fn from(e: ParseError<'a>) -> Self {
+        Self::Parse(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Broadcaster,
+    /// `%`, flips on every low pulse and sends its new state
+    FlipFlop,
+    /// `&`, sends a low pulse once the last pulse of each input was high
+    Conjunction,
+    /// Only named as an output, like `rx`
+    Sink,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    pub kind: Kind,
+    /// Each output, and which input of it this module is
+    pub outputs: Vec<(usize, usize)>,
+    pub inputs: Vec<usize>,
+}
+
+/// A module as written: its kind, name and the names of its outputs
+type RawModule<'a> = (Kind, &'a str, Vec<&'a str>);
+
+fn parse_module(input: &str) -> IResult<&str, RawModule<'_>> {
+    let (i, kind) = opt(alt((
+        value(Kind::FlipFlop, char('%')),
+        value(Kind::Conjunction, char('&')),
+    )))(input)?;
+    let (i, name) = alpha1(i)?;
+    let (i, _) = tag(" -> ")(i)?;
+    let (i, outputs) = separated_list1(tag(", "), alpha1)(i)?;
+    Ok((i, (kind.unwrap_or(Kind::Broadcaster), name, outputs)))
+}
+
+/// The modules, linked to each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network<'a> {
+    pub names: Vec<&'a str>,
+    pub modules: Vec<Module>,
+    pub broadcaster: usize,
+}
+
+impl<'a> Network<'a> {
+    fn link(raw: Vec<RawModule<'a>>) -> Result<Self, Day20Error<'a>> {
+        let mut indices: HashMap<&str, usize> = HashMap::new();
+        let mut names = Vec::new();
+        let mut modules = Vec::new();
+        let mut index = |name: &'a str, modules: &mut Vec<Module>| {
+            *indices.entry(name).or_insert_with(|| {
+                names.push(name);
+                modules.push(Module {
+                    kind: Kind::Sink,
+                    outputs: Vec::new(),
+                    inputs: Vec::new(),
+                });
+                modules.len() - 1
+            })
+        };
+        let mut broadcaster = None;
+        for (kind, name, outputs) in raw {
+            let i = index(name, &mut modules);
+            modules[i].kind = kind;
+            if name == "broadcaster" {
+                broadcaster = Some(i);
+            }
+            for output in outputs {
+                let o = index(output, &mut modules);
+                let slot = modules[o].inputs.len();
+                modules[o].inputs.push(i);
+                modules[i].outputs.push((o, slot));
+            }
+        }
+        Ok(Self {
+            names,
+            modules,
+            broadcaster: broadcaster.ok_or(Day20Error::NoBroadcaster)?,
+        })
+    }
+
+    fn index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|&other| other == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pulse {
+    pub from: usize,
+    pub to: usize,
+    /// Which input of `to` it arrives on
+    pub slot: usize,
+    pub high: bool,
+}
+
+/// The state of the modules as the button is pushed again and again
+pub struct Machine<'n> {
+    network: &'n Network<'n>,
+    /// Whether each flip-flop is on
+    on: Vec<bool>,
+    /// The last pulse each conjunction got on each input
+    memory: Vec<Vec<bool>>,
+    /// How many of those were high
+    high_inputs: Vec<usize>,
+    queue: VecDeque<Pulse>,
+}
+
+impl<'n> Machine<'n> {
+    pub fn new(network: &'n Network<'n>) -> Self {
+        Self {
+            network,
+            on: vec![false; network.modules.len()],
+            memory: network
+                .modules
+                .iter()
+                .map(|module| vec![false; module.inputs.len()])
+                .collect(),
+            high_inputs: vec![0; network.modules.len()],
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Sends a low pulse to the broadcaster and dispatches every pulse that
+    /// follows, in the order they were sent, showing each one to `watch`.
+    /// Gives up after [`MAX_PULSES`] pulses
+    pub fn push_button<'e>(&mut self, mut watch: impl FnMut(&Pulse)) -> Result<(), Day20Error<'e>> {
+        self.queue.clear();
+        self.queue.push_back(Pulse {
+            from: usize::MAX,
+            to: self.network.broadcaster,
+            slot: 0,
+            high: false,
+        });
+        let mut pulses = 0;
+        while let Some(pulse) = self.queue.pop_front() {
+            pulses += 1;
+            if pulses > MAX_PULSES {
+                return Err(Day20Error::TooManyPulses(MAX_PULSES));
+            }
+            // the runner gave up on us, the answer will be dropped anyway
+            if cancelled() {
+                break;
+            }
+            watch(&pulse);
+            let module = &self.network.modules[pulse.to];
+            let sent = match module.kind {
+                Kind::Broadcaster => Some(pulse.high),
+                Kind::FlipFlop if !pulse.high => {
+                    self.on[pulse.to] = !self.on[pulse.to];
+                    Some(self.on[pulse.to])
+                }
+                Kind::FlipFlop | Kind::Sink => None,
+                Kind::Conjunction => {
+                    let last = &mut self.memory[pulse.to][pulse.slot];
+                    if *last != pulse.high {
+                        *last = pulse.high;
+                        if pulse.high {
+                            self.high_inputs[pulse.to] += 1;
+                        } else {
+                            self.high_inputs[pulse.to] -= 1;
+                        }
+                    }
+                    Some(self.high_inputs[pulse.to] != module.inputs.len())
+                }
+            };
+            if let Some(high) = sent {
+                self.queue
+                    .extend(module.outputs.iter().map(|&(to, slot)| Pulse {
+                        from: pulse.to,
+                        to,
+                        slot,
+                        high,
+                    }));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How many pulses a single press may send. The puzzle's presses send a few
+/// hundred
+const MAX_PULSES: usize = 1 << 20;
+
+/// How many presses part 2 tries before giving up on an input of the
+/// conjunction before `rx`
+const MAX_PRESSES: usize = 1 << 16;
+
+/// The modules
+pub fn parse(input: &str) -> Result<Network<'_>, Day20Error<'_>> {
+    let raw = input
+        .lines()
+        .map(|line| Ok(parse_module(line)?.1))
+        .collect::<Result<_, ParseError>>()?;
+    Network::link(raw)
+}
+
+pub fn part1<'a>(network: &Network<'a>) -> Result<u64, Day20Error<'a>> {
+    let mut machine = Machine::new(network);
+    let (mut low, mut high) = (0, 0);
+    for _ in 0..1000 {
+        machine.push_button(|pulse| {
+            if pulse.high {
+                high += 1;
+            } else {
+                low += 1;
+            }
+        })?;
+    }
+    Ok(low * high)
+}
+
+/// `rx` gets a low pulse once every input of the conjunction before it sent a
+/// high pulse during the same press. Each input does so on a cycle of its own,
+/// starting from the first press, so they all do on the least common multiple
+/// of the cycles
+pub fn part2<'a>(network: &Network<'a>) -> Result<u64, Day20Error<'a>> {
+    let rx = network
+        .index("rx")
+        .ok_or(Day20Error::NoConjunctionBeforeRx)?;
+    let feeder = match network.modules[rx].inputs[..] {
+        [feeder] if network.modules[feeder].kind == Kind::Conjunction => feeder,
+        _ => return Err(Day20Error::NoConjunctionBeforeRx),
+    };
+    let mut cycles: Vec<Option<u64>> = vec![None; network.modules[feeder].inputs.len()];
+    let mut machine = Machine::new(network);
+    for press in 1..=MAX_PRESSES {
+        machine.push_button(|pulse| {
+            if pulse.to == feeder && pulse.high {
+                cycles[pulse.slot].get_or_insert(press as u64);
+            }
+        })?;
+        if cycles.iter().all(Option::is_some) {
+            return Ok(cycles.into_iter().flatten().fold(1, |acc, n| acc.lcm(&n)));
+        }
+    }
+    Err(Day20Error::NoCycle(MAX_PRESSES))
+}
+
+pub fn day20_1(input: &str) -> Result<u64, Day20Error<'_>> {
+    parse(input).and_then(|network| part1(&network))
+}
+
+pub fn day20_2(input: &str) -> Result<u64, Day20Error<'_>> {
+    parse(input).and_then(|network| part2(&network))
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day20_1, day20_2, Day20Error};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a
+";
+
+    const INPUT_2: &str = "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output
+";
+
+    /// `inva` sends a high pulse every 2 presses, `invb` every 4
+    const INPUT_RX: &str = "broadcaster -> a, b
+%a -> inva
+&inva -> hub
+%b -> c
+%c -> invb
+&invb -> hub
+&hub -> rx
+";
+
+    example_tests! {
+        test_day20_1: day20_1(INPUT) => Ok(32000000),
+        test_day20_1_output: day20_1(INPUT_2) => Ok(11687500),
+        test_day20_2: day20_2(INPUT_RX) => Ok(4),
+        test_day20_2_no_rx: day20_2(INPUT) => Err(Day20Error::NoConjunctionBeforeRx),
+        test_day20_1_no_broadcaster: day20_1("%a -> b\n") => Err(Day20Error::NoBroadcaster),
+        test_day20_1_feeds_itself: day20_1("broadcaster -> a\n&a -> a\n") => Err(Day20Error::TooManyPulses(1 << 20)),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[%&a-z ,>\n-]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Day 21: Step Counter
+//!
+//! `parse` reads the map of the garden as an [`AsciiGrid`] and finds where the
+//! elf starts.
+
+use std::collections::VecDeque;
+
+use crate::{
+    day9::extrapolate_at,
+    grid::{AsciiGrid, Coord, Grid},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Day21Error {
+    NotAGrid,
+    NoStart,
+    /// The reachable plots didn't settle into a quadratic growth on the
+    /// repeated map
+    NoQuadraticGrowth,
+}
+
+/// How many steps the elf takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    /// On the garden as is, in part 1
+    pub steps: usize,
+    /// On the garden repeated forever, in part 2
+    pub infinite_steps: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            steps: 64,
+            infinite_steps: 26501365,
+        }
+    }
+}
+
+/// Part 2 gives up once it would need to walk further than this to see the
+/// growth settle
+const MAX_WALK: usize = 2048;
+
+/// How many plots a walk of exactly `steps` can end on: those at an even
+/// distance of `steps` or less, since a step back and forth wastes two
+fn reachable(plots_by_distance: &[u64], steps: usize) -> u64 {
+    plots_by_distance
+        .iter()
+        .take(steps + 1)
+        .skip(steps % 2)
+        .step_by(2)
+        .sum()
+}
+
+/// How many plots are at each distance from `start`, up to `max_steps`, by a
+/// breadth first search. On the `tiled` garden the map repeats on every side,
+/// otherwise its edges are walls
+fn plots_by_distance(garden: &AsciiGrid, start: Coord, max_steps: usize, tiled: bool) -> Vec<u64> {
+    let (width, height) = (garden.width() as i64, garden.height() as i64);
+    // the window around `start` that a walk of `max_steps` can't leave
+    let side = 2 * max_steps + 1;
+    let is_plot = |wx: usize, wy: usize| {
+        let x = start.x as i64 + wx as i64 - max_steps as i64;
+        let y = start.y as i64 + wy as i64 - max_steps as i64;
+        let inside = (0..width).contains(&x) && (0..height).contains(&y);
+        if !(tiled || inside) {
+            return false;
+        }
+        let tile = garden.get(x.rem_euclid(width) as usize, y.rem_euclid(height) as usize);
+        tile != Some(&b'#')
+    };
+
+    let mut distances = vec![u32::MAX; side * side];
+    let mut counts = vec![0; max_steps + 1];
+    let mut queue = VecDeque::from([(max_steps, max_steps)]);
+    distances[max_steps * side + max_steps] = 0;
+    while let Some((x, y)) = queue.pop_front() {
+        let distance = distances[y * side + x];
+        counts[distance as usize] += 1;
+        if distance as usize == max_steps {
+            continue;
+        }
+        let neighbours = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbours {
+            if nx >= side || ny >= side || distances[ny * side + nx] != u32::MAX {
+                continue;
+            }
+            if is_plot(nx, ny) {
+                distances[ny * side + nx] = distance + 1;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    counts
+}
+
+/// The garden and where the elf starts
+pub fn parse(input: &str) -> Result<(AsciiGrid<'_>, Coord), Day21Error> {
+    let garden = AsciiGrid::try_from_ascii(input.as_bytes()).map_err(|_| Day21Error::NotAGrid)?;
+    let start = garden
+        .find_coord(|&c| c == b'S')
+        .ok_or(Day21Error::NoStart)?;
+    Ok((garden, start))
+}
+
+pub fn part1(garden: &(AsciiGrid, Coord)) -> u64 {
+    part1_with(garden, &Params::default())
+}
+
+pub fn part1_with((garden, start): &(AsciiGrid, Coord), params: &Params) -> u64 {
+    let counts = plots_by_distance(garden, *start, params.steps, false);
+    reachable(&counts, params.steps)
+}
+
+pub fn part2(garden: &(AsciiGrid, Coord)) -> Result<u64, Day21Error> {
+    part2_with(garden, &Params::default())
+}
+
+/// Once the walk is a few maps wide, each map width more reaches a quadratic
+/// number of plots more: the plots reachable after `rest + k * width` steps
+/// are a quadratic in `k`. Those are counted for growing `k` until the last
+/// few fit the same quadratic, which is then extrapolated
+pub fn part2_with(
+    (garden, start): &(AsciiGrid, Coord),
+    params: &Params,
+) -> Result<u64, Day21Error> {
+    let width = garden.width();
+    let rest = params.infinite_steps % width;
+    let target = params.infinite_steps / width;
+    let mut cycles = 4;
+    while rest + cycles * width <= MAX_WALK {
+        let max_steps = params.infinite_steps.min(rest + cycles * width);
+        let counts = plots_by_distance(garden, *start, max_steps, true);
+        let samples: Vec<i64> = (0..=cycles.min(target))
+            .map(|k| reachable(&counts, rest + k * width) as i64)
+            .collect();
+        if target <= cycles {
+            return Ok(samples[target] as u64);
+        }
+        // the quadratic through three samples, if the ones after it fit it
+        let settled = (0..=cycles - 4).find_map(|from| {
+            let quadratic = &samples[from..from + 3];
+            let fits = (from + 3..=cycles)
+                .all(|k| extrapolate_at(quadratic.to_vec(), (k - from) as i64) == samples[k]);
+            fits.then_some((from, quadratic))
+        });
+        if let Some((from, quadratic)) = settled {
+            return Ok(extrapolate_at(quadratic.to_vec(), (target - from) as i64) as u64);
+        }
+        cycles *= 2;
+    }
+    Err(Day21Error::NoQuadraticGrowth)
+}
+
+pub fn day21_1(input: &str) -> Result<u64, Day21Error> {
+    parse(input).map(|garden| part1(&garden))
+}
+
+pub fn day21_1_with(input: &str, params: &Params) -> Result<u64, Day21Error> {
+    parse(input).map(|garden| part1_with(&garden, params))
+}
+
+pub fn day21_2(input: &str) -> Result<u64, Day21Error> {
+    parse(input).and_then(|garden| part2(&garden))
+}
+
+pub fn day21_2_with(input: &str, params: &Params) -> Result<u64, Day21Error> {
+    parse(input).and_then(|garden| part2_with(&garden, params))
+}
+
+harness::check_parsers!(parse);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day21_1_with, day21_2_with, Day21Error, Params};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........
+";
+
+    fn steps(steps: usize) -> Params {
+        Params {
+            steps,
+            infinite_steps: steps,
+        }
+    }
+
+    example_tests! {
+        test_day21_1: day21_1_with(INPUT, &steps(6)) => Ok(16),
+        test_day21_2_6: day21_2_with(INPUT, &steps(6)) => Ok(16),
+        test_day21_2_10: day21_2_with(INPUT, &steps(10)) => Ok(50),
+        test_day21_2_50: day21_2_with(INPUT, &steps(50)) => Ok(1594),
+        test_day21_2_100: day21_2_with(INPUT, &steps(100)) => Ok(6536),
+        test_day21_2_500: day21_2_with(INPUT, &steps(500)) => Ok(167004),
+        test_day21_2_1000: day21_2_with(INPUT, &steps(1000)) => Ok(668697),
+        test_day21_2_5000: day21_2_with(INPUT, &steps(5000)) => Ok(16733044),
+        test_day21_no_start: day21_1_with("..\n..\n", &steps(6)) => Err(Day21Error::NoStart),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[.#S\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
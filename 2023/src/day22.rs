@@ -0,0 +1,341 @@
+//! Day 22: Sand Slabs
+//!
+//! `parse` reads the bricks of the snapshot, as they were before settling.
+
+use nom::{
+    character::complete::{char, u32},
+    combinator::verify,
+    sequence::{separated_pair, tuple},
+    IResult,
+};
+#[cfg(feature = "scaling")]
+use rand::Rng;
+
+use crate::grid::{Grid, MutGrid, VecGrid};
+
+pub type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
+
+/// The ground is sized from the bricks' `x` and `y`, which must be below this.
+/// The puzzle's is 10 by 10
+pub const MAX_GROUND: u32 = 1000;
+
+/// The cubes from `min` to `max`, both included, as `[x, y, z]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Brick {
+    pub min: [u32; 3],
+    pub max: [u32; 3],
+}
+
+impl Brick {
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        let ground = || verify(u32, |&coord| coord < MAX_GROUND);
+        let corner = |i| {
+            let (i, (x, _, y, _, z)) = tuple((ground(), char(','), ground(), char(','), u32))(i)?;
+            Ok((i, [x, y, z]))
+        };
+        let (i, (a, b)) = separated_pair(corner, char('~'), corner)(input)?;
+        Ok((
+            i,
+            Self {
+                min: [0, 1, 2].map(|axis| a[axis].min(b[axis])),
+                max: [0, 1, 2].map(|axis| a[axis].max(b[axis])),
+            },
+        ))
+    }
+
+    fn footprint(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.min[1]..=self.max[1])
+            .flat_map(|y| (self.min[0]..=self.max[0]).map(move |x| (x as usize, y as usize)))
+    }
+}
+
+/// Which settled bricks rest on which
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stack {
+    /// The bricks right above each brick, resting on it
+    pub supports: Vec<Vec<usize>>,
+    /// The bricks right below each brick, that it rests on
+    pub supported_by: Vec<Vec<usize>>,
+}
+
+impl Stack {
+    /// Lets the bricks fall, lowest first, until they rest on the ground or on
+    /// other bricks. Bricks are numbered from the lowest one
+    pub fn settle(bricks: &[Brick]) -> Self {
+        let mut bricks = bricks.to_vec();
+        bricks.sort_unstable_by_key(|brick| brick.min[2]);
+        let width = bricks
+            .iter()
+            .map(|brick| brick.max[0] + 1)
+            .max()
+            .unwrap_or(0);
+        let depth = bricks
+            .iter()
+            .map(|brick| brick.max[1] + 1)
+            .max()
+            .unwrap_or(0);
+        // the height of the highest cube above each column, and its brick
+        let mut tops: VecGrid<(u64, Option<usize>)> =
+            VecGrid::new(width as usize, depth as usize, (0, None));
+
+        let mut supported_by = vec![Vec::new(); bricks.len()];
+        let mut supports = vec![Vec::new(); bricks.len()];
+        for (i, brick) in bricks.iter().enumerate() {
+            let rest = brick
+                .footprint()
+                .map(|(x, y)| tops.get(x, y).unwrap().0)
+                .max()
+                .unwrap_or(0);
+            for (x, y) in brick.footprint() {
+                let top = tops.get_mut(x, y).unwrap();
+                match *top {
+                    (height, Some(below))
+                        if height == rest && !supported_by[i].contains(&below) =>
+                    {
+                        supported_by[i].push(below);
+                        supports[below].push(i);
+                    }
+                    _ => (),
+                }
+                *top = (rest + 1 + u64::from(brick.max[2] - brick.min[2]), Some(i));
+            }
+        }
+        Self {
+            supports,
+            supported_by,
+        }
+    }
+
+    /// Whether every brick resting on `brick` rests on another one too
+    pub fn can_disintegrate(&self, brick: usize) -> bool {
+        self.supports[brick]
+            .iter()
+            .all(|&above| self.supported_by[above].len() > 1)
+    }
+
+    /// How many other bricks fall once `brick` is gone: those that rest only
+    /// on bricks that fell
+    pub fn chain_reaction(&self, brick: usize) -> usize {
+        let mut remaining: Vec<usize> = self.supported_by.iter().map(Vec::len).collect();
+        let mut falling = vec![brick];
+        let mut fallen = 0;
+        while let Some(brick) = falling.pop() {
+            for &above in &self.supports[brick] {
+                remaining[above] -= 1;
+                if remaining[above] == 0 {
+                    fallen += 1;
+                    falling.push(above);
+                }
+            }
+        }
+        fallen
+    }
+}
+
+/// The bricks, one per line
+pub fn parse(input: &str) -> Result<Vec<Brick>, ParseError<'_>> {
+    input
+        .lines()
+        .map(|line| Ok(Brick::parse(line)?.1))
+        .collect()
+}
+
+pub fn part1(bricks: &[Brick]) -> usize {
+    let stack = Stack::settle(bricks);
+    (0..bricks.len())
+        .filter(|&brick| stack.can_disintegrate(brick))
+        .count()
+}
+
+pub fn part2(bricks: &[Brick]) -> usize {
+    let stack = Stack::settle(bricks);
+    (0..bricks.len())
+        .map(|brick| stack.chain_reaction(brick))
+        .sum()
+}
+
+pub fn day22_1(input: &str) -> Result<usize, ParseError<'_>> {
+    parse(input).map(|bricks| part1(&bricks))
+}
+
+pub fn day22_2(input: &str) -> Result<usize, ParseError<'_>> {
+    parse(input).map(|bricks| part2(&bricks))
+}
+
+/// Generates a snapshot of `size` bricks, for the scaling benchmark. Like the
+/// puzzle inputs they are up to 4 cubes long, above a 10 by 10 ground. Each
+/// brick is higher than the previous one so that none overlap
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let mut bricks = String::new();
+    for brick in 0..size {
+        let axis = rng.gen_range(0..3);
+        let len = rng.gen_range(0..4);
+        let mut min = [rng.gen_range(0..10), rng.gen_range(0..10), 1 + 4 * brick];
+        if axis < 2 {
+            min[axis] = min[axis].min(9 - len);
+        }
+        let mut max = min;
+        max[axis] += len;
+        bricks += &format!(
+            "{},{},{}~{},{},{}\n",
+            min[0], min[1], min[2], max[0], max[1], max[2]
+        );
+    }
+    bricks
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    /// The cubes of each brick
+    fn parse(input: &str) -> Vec<Vec<[u32; 3]>> {
+        input
+            .lines()
+            .map(|line| {
+                let corners: Vec<Vec<u32>> = line
+                    .split('~')
+                    .map(|corner| corner.split(',').map(|n| n.parse().unwrap()).collect())
+                    .collect();
+                let (a, b) = (&corners[0], &corners[1]);
+                let mut cubes = vec![];
+                for x in a[0].min(b[0])..=a[0].max(b[0]) {
+                    for y in a[1].min(b[1])..=a[1].max(b[1]) {
+                        for z in a[2].min(b[2])..=a[2].max(b[2]) {
+                            cubes.push([x, y, z]);
+                        }
+                    }
+                }
+                cubes
+            })
+            .collect()
+    }
+
+    /// Moves the bricks down one cube at a time until none can, returning how
+    /// many moved
+    fn settle(bricks: &mut [Vec<[u32; 3]>]) -> usize {
+        let mut fell = vec![false; bricks.len()];
+        loop {
+            let mut moved = false;
+            for i in 0..bricks.len() {
+                let can_fall = bricks[i].iter().all(|&[x, y, z]| {
+                    z > 1
+                        && bricks
+                            .iter()
+                            .enumerate()
+                            .all(|(j, other)| j == i || !other.contains(&[x, y, z - 1]))
+                });
+                if can_fall {
+                    bricks[i].iter_mut().for_each(|cube| cube[2] -= 1);
+                    fell[i] = true;
+                    moved = true;
+                }
+            }
+            if !moved {
+                return fell.into_iter().filter(|&fell| fell).count();
+            }
+        }
+    }
+
+    /// How many bricks fall once each brick is taken away from the settled
+    /// stack
+    fn falling(input: &str) -> Vec<usize> {
+        let mut bricks = parse(input);
+        settle(&mut bricks);
+        (0..bricks.len())
+            .map(|i| {
+                let mut others = bricks.clone();
+                others.remove(i);
+                settle(&mut others)
+            })
+            .collect()
+    }
+
+    pub fn day22_1(input: &str) -> usize {
+        falling(input).into_iter().filter(|&fell| fell == 0).count()
+    }
+
+    pub fn day22_2(input: &str) -> usize {
+        falling(input).into_iter().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day22_1, day22_2, reference};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9
+";
+
+    /// Two bricks side by side hold up a third, which holds up a tower
+    const BRIDGE: &str = "0,0,1~0,0,1
+2,0,1~2,0,1
+0,0,3~2,0,3
+1,0,5~1,0,7
+1,0,9~1,0,9
+";
+
+    example_tests! {
+        test_day22_1: day22_1(INPUT) => Ok(5),
+        test_day22_2: day22_2(INPUT) => Ok(7),
+        test_day22_1_bridge: day22_1(BRIDGE) => Ok(3),
+        test_day22_2_bridge: day22_2(BRIDGE) => Ok(3),
+    }
+
+    #[test]
+    fn far_bricks_are_rejected() {
+        assert!(day22_1("4000000000,0,1~4000000000,0,1\n").is_err());
+        assert!(day22_1("0,1000,1~0,1000,1\n").is_err());
+        assert_eq!(day22_1("999,0,1~999,0,1\n"), Ok(1));
+    }
+
+    #[test]
+    fn tall_bricks_stack() {
+        let tall = "0,0,1~0,0,4294967295\n0,0,1~0,0,4294967295\n";
+        assert_eq!(day22_2(tall), Ok(1));
+    }
+
+    /// A few bricks of up to three cubes along one axis, above a 3 by 3
+    /// ground, leaving out those that would cross an earlier one
+    fn snapshots() -> impl Strategy<Value = String> {
+        let brick = ((0..3u32, 0..3u32, 1..10u32), 0..3usize, 0..3u32);
+        prop::collection::vec(brick, 1..10).prop_map(|bricks| {
+            let mut kept: Vec<([u32; 3], [u32; 3])> = vec![];
+            for ((x, y, z), axis, len) in bricks {
+                let min = [x, y, z];
+                let mut max = min;
+                max[axis] = (max[axis] + len).min(if axis == 2 { 12 } else { 2 });
+                let overlaps = kept.iter().any(|(other_min, other_max)| {
+                    (0..3).all(|i| min[i] <= other_max[i] && other_min[i] <= max[i])
+                });
+                if !overlaps {
+                    kept.push((min, max));
+                }
+            }
+            kept.into_iter()
+                .map(|([x0, y0, z0], [x1, y1, z1])| format!("{x0},{y0},{z0}~{x1},{y1},{z1}\n"))
+                .collect()
+        })
+    }
+
+    differential_tests! {
+        test_day22_1_reference(snapshots()): day22_1 == reference::day22_1,
+        test_day22_2_reference(snapshots()): day22_2 == reference::day22_2,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[0-9,~\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
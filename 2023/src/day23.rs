@@ -0,0 +1,353 @@
+//! Day 23: A Long Walk
+//!
+//! `parse` reads the map of the trails as an [`AsciiGrid`].
+
+use strum::IntoEnumIterator;
+
+use crate::grid::{AsciiGrid, Coord, Direction, Grid, MutGrid, VecGrid};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Day23Error {
+    NotAGrid,
+    /// No path tile on the first or last line
+    NoStartOrEnd,
+    /// The search keeps the junctions visited in a `u64`
+    TooManyJunctions(usize),
+    /// No hike reaches the last line
+    NoPath,
+}
+
+/// Whether a hike may leave `tile` towards `dir`: slopes only go downhill
+fn can_leave(tile: u8, dir: Direction, slippery: bool) -> bool {
+    match tile {
+        b'^' if slippery => dir == Direction::Up,
+        b'v' if slippery => dir == Direction::Down,
+        b'<' if slippery => dir == Direction::Left,
+        b'>' if slippery => dir == Direction::Right,
+        _ => true,
+    }
+}
+
+/// The trails between the junctions of the map, the start and the end
+struct Junctions {
+    /// The junctions each one leads to, and how many steps away
+    edges: Vec<Vec<(usize, u32)>>,
+    start: usize,
+    end: usize,
+}
+
+impl Junctions {
+    fn new(map: &AsciiGrid, start: Coord, end: Coord, slippery: bool) -> Result<Self, Day23Error> {
+        let is_path = |coord: Coord| map.get_coord(coord).is_some_and(|&tile| tile != b'#');
+        let neighbours = move |coord: Coord| {
+            Direction::iter().filter_map(move |dir| {
+                let next = coord.at_dir(dir)?;
+                is_path(next).then_some((dir, next))
+            })
+        };
+
+        let mut index: VecGrid<Option<usize>> = VecGrid::new(map.width(), map.height(), None);
+        let mut junctions = vec![start, end];
+        junctions.extend(
+            map.coord_iter()
+                .filter(|&coord| is_path(coord) && neighbours(coord).count() > 2),
+        );
+        if junctions.len() > 64 {
+            return Err(Day23Error::TooManyJunctions(junctions.len()));
+        }
+        for (i, &coord) in junctions.iter().enumerate() {
+            *index.get_coord_mut(coord).unwrap() = Some(i);
+        }
+
+        let edges = junctions
+            .iter()
+            .map(|&from| {
+                neighbours(from)
+                    .filter_map(|(dir, mut coord)| {
+                        if !can_leave(*map.get_coord(from)?, dir, slippery) {
+                            return None;
+                        }
+                        let mut prev = from;
+                        let mut steps = 1;
+                        // follow the trail until the next junction
+                        loop {
+                            if let Some(to) = *index.get_coord(coord)? {
+                                return Some((to, steps));
+                            }
+                            let tile = *map.get_coord(coord)?;
+                            let (_, next) = neighbours(coord).find(|&(dir, next)| {
+                                next != prev && can_leave(tile, dir, slippery)
+                            })?;
+                            (prev, coord) = (coord, next);
+                            steps += 1;
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok(Self {
+            edges,
+            start: 0,
+            end: 1,
+        })
+    }
+
+    /// The longest hike from the start, by a depth first search going through
+    /// each junction at most once. A hike enters each junction it can still
+    /// reach at most once, by one of its trails: the longest trails into them
+    /// bound how much longer it can get, and hikes that can't beat the longest
+    /// one found so far, or can't reach the end anymore, are given up on
+    fn longest_hike(&self) -> Option<u32> {
+        let mut longest_into = vec![0; self.edges.len()];
+        let mut next = vec![0; self.edges.len()];
+        for (from, edges) in self.edges.iter().enumerate() {
+            for &(to, steps) in edges {
+                longest_into[to] = longest_into[to].max(steps);
+                next[from] |= 1 << to;
+            }
+        }
+        // longer trails first find long hikes sooner, which cuts more
+        let mut edges = self.edges.clone();
+        for edges in &mut edges {
+            edges.sort_unstable_by_key(|&(_, steps)| std::cmp::Reverse(steps));
+        }
+        let mut search = Search {
+            edges,
+            junctions: self,
+            longest_into,
+            next,
+            longest: None,
+        };
+        search.go(self.start, 1 << self.start, 0);
+        search.longest
+    }
+}
+
+/// The state of [`Junctions::longest_hike`]
+struct Search<'a> {
+    junctions: &'a Junctions,
+    /// The trails of each junction, longest first
+    edges: Vec<Vec<(usize, u32)>>,
+    longest_into: Vec<u32>,
+    /// The junctions each one leads to, as a mask
+    next: Vec<u64>,
+    longest: Option<u32>,
+}
+
+impl Search<'_> {
+    /// The junctions a hike at `from` can still reach, without going through
+    /// the `visited` ones again
+    fn reachable(&self, from: usize, visited: u64) -> u64 {
+        let mut reached = 0;
+        let mut frontier: u64 = 1 << from;
+        while frontier != 0 {
+            reached |= frontier;
+            let mut next = 0;
+            let mut junctions = frontier;
+            while junctions != 0 {
+                next |= self.next[junctions.trailing_zeros() as usize];
+                junctions &= junctions - 1;
+            }
+            frontier = next & !visited & !reached;
+        }
+        reached & !(1 << from)
+    }
+
+    /// Goes on from `from` after `steps`
+    fn go(&mut self, from: usize, visited: u64, steps: u32) {
+        let junctions = self.junctions;
+        if from == junctions.end {
+            self.longest = self.longest.max(Some(steps));
+            return;
+        }
+        let reachable = self.reachable(from, visited);
+        if reachable & (1 << junctions.end) == 0 {
+            return;
+        }
+        let bound: u32 = (0..self.longest_into.len())
+            .filter(|&i| reachable & (1 << i) != 0)
+            .map(|i| self.longest_into[i])
+            .sum();
+        if self.longest.is_some_and(|longest| steps + bound <= longest) {
+            return;
+        }
+        for i in 0..self.edges[from].len() {
+            let (to, trail) = self.edges[from][i];
+            if visited & (1 << to) == 0 {
+                self.go(to, visited | (1 << to), steps + trail);
+            }
+        }
+    }
+}
+
+/// The map of the trails
+pub fn parse(input: &str) -> Result<AsciiGrid<'_>, Day23Error> {
+    AsciiGrid::try_from_ascii(input.as_bytes()).map_err(|_| Day23Error::NotAGrid)
+}
+
+fn longest_hike(map: &AsciiGrid, slippery: bool) -> Result<u32, Day23Error> {
+    let on_line = |y: usize| {
+        (0..map.width())
+            .find(|&x| map.get(x, y) == Some(&b'.'))
+            .map(|x| Coord { x, y })
+    };
+    let (Some(start), Some(end)) = (on_line(0), on_line(map.height() - 1)) else {
+        return Err(Day23Error::NoStartOrEnd);
+    };
+    Junctions::new(map, start, end, slippery)?
+        .longest_hike()
+        .ok_or(Day23Error::NoPath)
+}
+
+pub fn part1(map: &AsciiGrid) -> Result<u32, Day23Error> {
+    longest_hike(map, true)
+}
+
+pub fn part2(map: &AsciiGrid) -> Result<u32, Day23Error> {
+    longest_hike(map, false)
+}
+
+pub fn day23_1(input: &str) -> Result<u32, Day23Error> {
+    parse(input).and_then(|map| part1(&map))
+}
+
+pub fn day23_2(input: &str) -> Result<u32, Day23Error> {
+    parse(input).and_then(|map| part2(&map))
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    /// Tries every step from `(x, y)` to a tile not `visited` yet, until the
+    /// last line
+    fn longest_hike(
+        map: &[&[u8]],
+        (x, y): (usize, usize),
+        visited: &mut Vec<Vec<bool>>,
+        slippery: bool,
+    ) -> Option<u32> {
+        if y == map.len() - 1 {
+            return Some(0);
+        }
+        let mut longest = None;
+        for (dx, dy, slope) in [(0, -1, b'^'), (0, 1, b'v'), (-1, 0, b'<'), (1, 0, b'>')] {
+            if slippery && map[y][x] != b'.' && map[y][x] != slope {
+                continue;
+            }
+            let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) else {
+                continue;
+            };
+            if map[ny].get(nx).is_none_or(|&tile| tile == b'#') || visited[ny][nx] {
+                continue;
+            }
+            visited[ny][nx] = true;
+            if let Some(steps) = longest_hike(map, (nx, ny), visited, slippery) {
+                longest = longest.max(Some(steps + 1));
+            }
+            visited[ny][nx] = false;
+        }
+        longest
+    }
+
+    fn day23(input: &str, slippery: bool) -> u32 {
+        let map: Vec<&[u8]> = input.lines().map(str::as_bytes).collect();
+        let start = map[0].iter().position(|&tile| tile == b'.').unwrap();
+        let mut visited = vec![vec![false; map[0].len()]; map.len()];
+        visited[0][start] = true;
+        longest_hike(&map, (start, 0), &mut visited, slippery).unwrap()
+    }
+
+    pub fn day23_1(input: &str) -> u32 {
+        day23(input, true)
+    }
+
+    pub fn day23_2(input: &str) -> u32 {
+        day23(input, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day23_1, day23_2, reference, Day23Error};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###v#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#
+";
+
+    example_tests! {
+        test_day23_1: day23_1(INPUT) => Ok(94),
+        test_day23_2: day23_2(INPUT) => Ok(154),
+        test_day23_1_uphill: day23_1("#.#\n#^#\n#.#\n") => Err(Day23Error::NoPath),
+        test_day23_2_uphill: day23_2("#.#\n#^#\n#.#\n") => Ok(2),
+        test_day23_no_end: day23_1("#.#\n###\n") => Err(Day23Error::NoStartOrEnd),
+    }
+
+    /// Maps of up to 5 by 5 random tiles inside their walls, with a gap in the
+    /// top and bottom walls. A trail without slopes goes down from the top gap,
+    /// across and down to the bottom one, so that there is always a hike
+    fn maps() -> impl Strategy<Value = String> {
+        (1..6usize, 1..6usize).prop_flat_map(|(width, height)| {
+            let tiles = prop::sample::select(b"...##<>^v".to_vec());
+            (
+                prop::collection::vec(tiles, width * height),
+                0..width,
+                0..width,
+                1..=height,
+            )
+                .prop_map(move |(tiles, start, end, across)| {
+                    let mut map = vec![vec![b'#'; width + 2]; height + 2];
+                    for (y, line) in tiles.chunks(width).enumerate() {
+                        map[y + 1][1..=width].copy_from_slice(line);
+                    }
+                    for line in &mut map[..=across] {
+                        line[start + 1] = b'.';
+                    }
+                    for x in start.min(end)..=start.max(end) {
+                        map[across][x + 1] = b'.';
+                    }
+                    for line in &mut map[across..] {
+                        line[end + 1] = b'.';
+                    }
+                    map.into_iter()
+                        .map(|line| String::from_utf8(line).unwrap() + "\n")
+                        .collect()
+                })
+        })
+    }
+
+    differential_tests! {
+        test_day23_1_reference(maps()): day23_1 == reference::day23_1,
+        test_day23_2_reference(maps()): day23_2 == reference::day23_2,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[#.<>^v\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
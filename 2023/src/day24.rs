@@ -0,0 +1,416 @@
+//! Day 24: Never Tell Me The Odds
+//!
+//! `parse` reads the position and velocity of each [`Hailstone`].
+//!
+//! Both parts stay in exact arithmetic: part 1 compares the crossings as
+//! fractions in `i128`, and part 2 solves for the rock over `BigRational`,
+//! whose intermediate values outgrow any fixed width integer.
+
+use nom::{
+    character::complete::{char, i64, space0},
+    sequence::{delimited, tuple},
+    IResult,
+};
+use num::{BigInt, BigRational, ToPrimitive, Zero};
+
+pub type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
+
+#[derive(Debug, PartialEq)]
+pub enum Day24Error<'a> {
+    Parse(ParseError<'a>),
+    /// No throw from a whole position at a whole velocity hits every hailstone
+    NoRock,
+}
+
+impl<'a> From<ParseError<'a>> for Day24Error<'a> {
+    fn from(e: ParseError<'a>) -> Self {
+        Self::Parse(e)
+    }
+}
+
+/// Where it is at time 0, as `[x, y, z]`, and how far it moves each
+/// nanosecond
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hailstone {
+    pub position: [i64; 3],
+    pub velocity: [i64; 3],
+}
+
+impl Hailstone {
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        let vector = |i| {
+            let comma = || tuple((char(','), space0));
+            let (i, (x, _, y, _, z)) = tuple((i64, comma(), i64, comma(), i64))(i)?;
+            Ok((i, [x, y, z]))
+        };
+        let (i, position) = vector(input)?;
+        let (i, _) = delimited(space0, char('@'), space0)(i)?;
+        let (i, velocity) = vector(i)?;
+        Ok((i, Self { position, velocity }))
+    }
+
+    /// Whether the paths of `self` and `other`, ignoring `z`, cross inside
+    /// the test area, now or in the future of both. Parallel paths never do
+    fn crosses_xy(&self, other: &Self, params: &Params) -> bool {
+        let ([ax, ay, _], [avx, avy, _]) = (self.position, self.velocity);
+        let ([bx, by, _], [bvx, bvy, _]) = (other.position, other.velocity);
+        let [ax, ay, avx, avy, bx, by, bvx, bvy] =
+            [ax, ay, avx, avy, bx, by, bvx, bvy].map(i128::from);
+        // `a + t * av = b + s * bv`, by Cramer's rule
+        let mut det = bvx * avy - avx * bvy;
+        if det == 0 {
+            return false;
+        }
+        let (dx, dy) = (bx - ax, by - ay);
+        let mut t = bvx * dy - dx * bvy;
+        let mut s = avx * dy - dx * avy;
+        if det < 0 {
+            (det, t, s) = (-det, -t, -s);
+        }
+        // the crossing is at `(x / det, y / det)`
+        let (x, y) = (ax * det + t * avx, ay * det + t * avy);
+        let area = i128::from(params.min) * det..=i128::from(params.max) * det;
+        t >= 0 && s >= 0 && area.contains(&x) && area.contains(&y)
+    }
+}
+
+/// The test area of part 1, from `min` to `max` on both `x` and `y`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            min: 200_000_000_000_000,
+            max: 400_000_000_000_000,
+        }
+    }
+}
+
+fn cross<T>(a: [T; 3], b: [T; 3]) -> [T; 3]
+where
+    T: Copy + std::ops::Mul<Output = T> + std::ops::Sub<Output = T>,
+{
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Solves the square system of `rows`, each one its coefficients followed by
+/// its right hand side, by Gaussian elimination. `None` if it is singular
+fn solve<const N: usize, const M: usize>(
+    mut rows: [[BigRational; M]; N],
+) -> Option<[BigRational; N]> {
+    for col in 0..N {
+        let pivot = (col..N).find(|&row| !rows[row][col].is_zero())?;
+        rows.swap(col, pivot);
+        for row in 0..N {
+            if row != col && !rows[row][col].is_zero() {
+                let factor = &rows[row][col] / &rows[col][col];
+                let pivot_row = rows[col].clone();
+                for (value, pivot) in rows[row].iter_mut().zip(&pivot_row).skip(col) {
+                    *value -= &factor * pivot;
+                }
+            }
+        }
+    }
+    Some(std::array::from_fn(|i| &rows[i][N] / &rows[i][i]))
+}
+
+/// The rock hits hailstone `i` iff `(rock - p_i) × (rock_v - v_i) = 0`. The
+/// only nonlinear term, `rock × rock_v`, is the same for every hailstone, so
+/// subtracting the equations of `j` from those of `i` leaves
+/// `rock × (v_j - v_i) + (p_j - p_i) × rock_v = p_j × v_j - p_i × v_i`, three
+/// linear equations in the six unknowns
+fn rock_equations(a: &Hailstone, b: &Hailstone) -> [[i128; 7]; 3] {
+    let [pa, va, pb, vb] =
+        [a.position, a.velocity, b.position, b.velocity].map(|v| v.map(i128::from));
+    let [ax, ay, az] = [0, 1, 2].map(|k| vb[k] - va[k]);
+    let [bx, by, bz] = [0, 1, 2].map(|k| pb[k] - pa[k]);
+    let (cb, ca) = (cross(pb, vb), cross(pa, va));
+    let [cx, cy, cz] = [0, 1, 2].map(|k| cb[k] - ca[k]);
+    [
+        [0, az, -ay, 0, -bz, by, cx],
+        [-az, 0, ax, bz, 0, -bx, cy],
+        [ay, -ax, 0, -by, bx, 0, cz],
+    ]
+}
+
+/// Whether a rock thrown from `position` at `velocity` hits `hailstone` at
+/// time 0 or later
+fn hits(position: [i128; 3], velocity: [i128; 3], hailstone: &Hailstone) -> bool {
+    let offset: [i128; 3] =
+        std::array::from_fn(|k| position[k] - i128::from(hailstone.position[k]));
+    let closing: [i128; 3] =
+        std::array::from_fn(|k| velocity[k] - i128::from(hailstone.velocity[k]));
+    // the offset shrinks to nothing along the closing velocity
+    let towards: i128 = (0..3).map(|k| offset[k] * closing[k]).sum();
+    cross(offset, closing) == [0; 3] && (towards < 0 || offset == [0; 3])
+}
+
+/// The throw that hits all three hailstones, as `[x, y, z, vx, vy, vz]`, if
+/// their equations are independent
+fn throw(a: &Hailstone, b: &Hailstone, c: &Hailstone) -> Option<[BigRational; 6]> {
+    let mut rows = rock_equations(a, b).into_iter().chain(rock_equations(a, c));
+    let system = std::array::from_fn(|_| {
+        rows.next()
+            .unwrap()
+            .map(|n| BigRational::from_integer(BigInt::from(n)))
+    });
+    solve(system)
+}
+
+/// The hailstones, one per line
+pub fn parse(input: &str) -> Result<Vec<Hailstone>, Day24Error<'_>> {
+    Ok(input
+        .lines()
+        .map(|line| Ok(Hailstone::parse(line)?.1))
+        .collect::<Result<_, ParseError>>()?)
+}
+
+pub fn part1(hailstones: &[Hailstone]) -> usize {
+    part1_with(hailstones, &Params::default())
+}
+
+pub fn part1_with(hailstones: &[Hailstone], params: &Params) -> usize {
+    hailstones
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            hailstones[i + 1..]
+                .iter()
+                .filter(|b| a.crosses_xy(b, params))
+                .count()
+        })
+        .sum()
+}
+
+/// Three hailstones are enough to pin the throw down, unless they make the
+/// equations dependent, in which case the next ones are tried. The throw must
+/// then hit every other hailstone too
+pub fn part2<'a>(hailstones: &[Hailstone]) -> Result<i64, Day24Error<'a>> {
+    let first = hailstones.first().ok_or(Day24Error::NoRock)?;
+    let rock = (1..hailstones.len())
+        .flat_map(|j| (j + 1..hailstones.len()).map(move |k| (j, k)))
+        .find_map(|(j, k)| throw(first, &hailstones[j], &hailstones[k]))
+        .ok_or(Day24Error::NoRock)?
+        .iter()
+        .map(|n| n.is_integer().then(|| n.to_integer().to_i128()).flatten())
+        .collect::<Option<Vec<_>>>()
+        .ok_or(Day24Error::NoRock)?;
+    let (position, velocity) = ([rock[0], rock[1], rock[2]], [rock[3], rock[4], rock[5]]);
+    if !hailstones
+        .iter()
+        .all(|hailstone| hits(position, velocity, hailstone))
+    {
+        return Err(Day24Error::NoRock);
+    }
+    position
+        .iter()
+        .sum::<i128>()
+        .try_into()
+        .map_err(|_| Day24Error::NoRock)
+}
+
+pub fn day24_1(input: &str) -> Result<usize, Day24Error<'_>> {
+    parse(input).map(|hailstones| part1(&hailstones))
+}
+
+pub fn day24_1_with<'a>(input: &'a str, params: &Params) -> Result<usize, Day24Error<'a>> {
+    parse(input).map(|hailstones| part1_with(&hailstones, params))
+}
+
+pub fn day24_2(input: &str) -> Result<i64, Day24Error<'_>> {
+    parse(input).and_then(|hailstones| part2(&hailstones))
+}
+
+harness::check_parsers!(parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    use num::rational::Ratio;
+
+    /// The position then velocity of each hailstone
+    fn parse(input: &str) -> Vec<[i64; 6]> {
+        input
+            .lines()
+            .map(|line| {
+                let numbers: Vec<i64> = line
+                    .split([',', '@'])
+                    .map(|n| n.trim().parse().unwrap())
+                    .collect();
+                [0, 1, 2, 3, 4, 5].map(|i| numbers[i])
+            })
+            .collect()
+    }
+
+    /// Solves `a + t * av = b + s * bv` on `x` and `y` as fractions
+    pub fn crossings(input: &str, min: i64, max: i64) -> usize {
+        let hailstones: Vec<[i128; 6]> = parse(input)
+            .into_iter()
+            .map(|hailstone| hailstone.map(i128::from))
+            .collect();
+        let area = Ratio::from(i128::from(min))..=Ratio::from(i128::from(max));
+        let mut crossings = 0;
+        for (i, &[ax, ay, _, avx, avy, _]) in hailstones.iter().enumerate() {
+            for &[bx, by, _, bvx, bvy, _] in &hailstones[i + 1..] {
+                let det = avx * bvy - avy * bvx;
+                if det == 0 {
+                    continue;
+                }
+                let t = Ratio::new((bx - ax) * bvy - (by - ay) * bvx, det);
+                let s = Ratio::new((bx - ax) * avy - (by - ay) * avx, det);
+                let (x, y) = (t * avx + ax, t * avy + ay);
+                if t >= Ratio::from(0)
+                    && s >= Ratio::from(0)
+                    && area.contains(&x)
+                    && area.contains(&y)
+                {
+                    crossings += 1;
+                }
+            }
+        }
+        crossings
+    }
+
+    /// When a rock at `position` moving at `velocity` is where `hailstone`
+    /// is, if it is at a whole time
+    fn hit_time(position: [i64; 3], velocity: [i64; 3], hailstone: &[i64; 6]) -> Option<i64> {
+        let offset = [0, 1, 2].map(|k| hailstone[k] - position[k]);
+        let closing = [0, 1, 2].map(|k| velocity[k] - hailstone[k + 3]);
+        let time = match (0..3).find(|&k| closing[k] != 0) {
+            Some(k) if offset[k] % closing[k] == 0 => offset[k] / closing[k],
+            Some(_) => return None,
+            None => 0,
+        };
+        (0..3)
+            .all(|k| offset[k] == time * closing[k])
+            .then_some(time)
+    }
+
+    /// Tries every velocity from -6 to 6 on each axis, and every time up to 20
+    /// for the rock to hit the first hailstone
+    pub fn day24_2(input: &str) -> i64 {
+        let hailstones = parse(input);
+        let first = hailstones[0];
+        for vx in -6..=6 {
+            for vy in -6..=6 {
+                for vz in -6..=6 {
+                    let velocity = [vx, vy, vz];
+                    for time in 0..=20 {
+                        let position =
+                            [0, 1, 2].map(|k| first[k] + time * (first[k + 3] - velocity[k]));
+                        if hailstones.iter().all(|hailstone| {
+                            hit_time(position, velocity, hailstone).is_some_and(|t| t >= 0)
+                        }) {
+                            return position.iter().sum();
+                        }
+                    }
+                }
+            }
+        }
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day24_1_with, day24_2, reference, Day24Error, Params};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3
+";
+
+    const EXAMPLE: Params = Params { min: 7, max: 27 };
+
+    /// Three hailstones on parallel paths: no single line hits them all
+    const PARALLEL: &str = "0, 0, 0 @ 1, 0, 0
+0, 1, 0 @ 1, 0, 0
+0, 0, 1 @ 1, 0, 0
+";
+
+    example_tests! {
+        test_day24_1: day24_1_with(INPUT, &EXAMPLE) => Ok(2),
+        test_day24_2: day24_2(INPUT) => Ok(47),
+        test_day24_1_parallel: day24_1_with(PARALLEL, &Params { min: -10, max: 10 }) => Ok(0),
+        test_day24_2_parallel: day24_2(PARALLEL) => Err(Day24Error::NoRock),
+    }
+
+    fn day24_1_example(input: &str) -> Result<usize, Day24Error<'_>> {
+        day24_1_with(input, &EXAMPLE)
+    }
+
+    fn reference_day24_1_example(input: &str) -> usize {
+        reference::crossings(input, EXAMPLE.min, EXAMPLE.max)
+    }
+
+    fn format_hailstone(position: [i64; 3], velocity: [i64; 3]) -> String {
+        let [x, y, z] = position;
+        let [vx, vy, vz] = velocity;
+        format!("{x}, {y}, {z} @ {vx}, {vy}, {vz}\n")
+    }
+
+    /// Hailstones around the example's test area
+    fn hailstones() -> impl Strategy<Value = String> {
+        let hailstone = (
+            prop::array::uniform3(0..35i64),
+            prop::array::uniform3(-5..=5i64),
+        );
+        prop::collection::vec(hailstone, 1..8).prop_map(|hailstones| {
+            hailstones
+                .into_iter()
+                .map(|(position, velocity)| format_hailstone(position, velocity))
+                .collect()
+        })
+    }
+
+    /// Hailstones that a rock thrown from a small position at a small
+    /// velocity hits, each at its own time
+    fn storms() -> impl Strategy<Value = String> {
+        let velocities = prop::collection::vec(prop::array::uniform3(-5..=5i64), 5..8);
+        let times = Just((1..=20).collect::<Vec<i64>>()).prop_shuffle();
+        (
+            prop::array::uniform3(-50..=50i64),
+            prop::array::uniform3(-5..=5i64),
+            velocities,
+            times,
+        )
+            .prop_map(|(position, velocity, velocities, times)| {
+                velocities
+                    .into_iter()
+                    .zip(times)
+                    .map(|(mut hailstone_velocity, time)| {
+                        // a hailstone as fast as the rock would be hit at any time
+                        if hailstone_velocity == velocity {
+                            hailstone_velocity[0] += 1;
+                        }
+                        let hailstone_position = [0, 1, 2]
+                            .map(|k| position[k] + time * (velocity[k] - hailstone_velocity[k]));
+                        format_hailstone(hailstone_position, hailstone_velocity)
+                    })
+                    .collect()
+            })
+    }
+
+    differential_tests! {
+        test_day24_1_reference(hailstones()): day24_1_example == reference_day24_1_example,
+        test_day24_2_reference(storms()): day24_2 == reference::day24_2,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[0-9,@ \n-]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+}
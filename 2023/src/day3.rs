@@ -1,4 +1,8 @@
-use crate::grid::{AsciiGrid, Coord, CoordIterator, Grid};
+//! Day 3: Gear Ratios
+//!
+//! `parse` reads the engine schematic as an [`AsciiGrid`].
+
+use crate::grid::{AsciiGrid, Coord, CoordIterator, Grid, NotAGrid};
 
 fn is_symbol(byte: u8) -> bool {
     !byte.is_ascii_digit() && byte != b'.'
@@ -15,8 +19,12 @@ fn number_end(grid: &impl Grid<Item = u8>, line: usize, x_start: usize) -> usize
     x_end
 }
 
-pub fn day3_1(input: &str) -> Result<u32, ()> {
-    let grid = AsciiGrid::from_ascii(input.as_bytes());
+/// The engine schematic
+pub fn parse(input: &str) -> Result<AsciiGrid<'_>, NotAGrid> {
+    AsciiGrid::try_from_ascii(input.as_bytes())
+}
+
+pub fn part1(grid: &AsciiGrid) -> u32 {
     let mut sum = 0;
     let mut skip = false;
     for Coord { x, y } in grid.coord_iter() {
@@ -30,7 +38,7 @@ pub fn day3_1(input: &str) -> Result<u32, ()> {
         }
         let mut touches_symbol = false;
         let x0 = x;
-        let x1 = number_end(&grid, y, x0);
+        let x1 = number_end(grid, y, x0);
         for Coord { x: xs, y: ys } in
             CoordIterator::new(x0.saturating_sub(1), y.saturating_sub(1), x1, y + 1)
         {
@@ -49,7 +57,7 @@ pub fn day3_1(input: &str) -> Result<u32, ()> {
         }
         skip = true;
     }
-    Ok(sum)
+    sum
 }
 
 #[derive(Debug, Default)]
@@ -135,8 +143,7 @@ fn get_grid_num(grid: &AsciiGrid, line: usize, x: usize) -> GridNumber {
     GridNumber { x: x0, y: line, n }
 }
 
-pub fn day3_2(input: &str) -> Result<u32, ()> {
-    let grid = AsciiGrid::from_ascii(input.as_bytes());
+pub fn part2(grid: &AsciiGrid) -> u32 {
     let mut sum = 0;
     for Coord { x, y } in grid.coord_iter() {
         let &c = grid.get(x, y).unwrap();
@@ -153,7 +160,7 @@ pub fn day3_2(input: &str) -> Result<u32, ()> {
             if !b.is_ascii_digit() {
                 continue;
             }
-            let grid_num = get_grid_num(&grid, ys, xs);
+            let grid_num = get_grid_num(grid, ys, xs);
             if !pair.contains(&grid_num) && pair.full() {
                 pair.clear();
                 break;
@@ -164,12 +171,23 @@ pub fn day3_2(input: &str) -> Result<u32, ()> {
             sum += n1.n * n2.n
         }
     }
-    Ok(sum)
+    sum
+}
+
+pub fn day3_1(input: &str) -> Result<u32, NotAGrid> {
+    parse(input).map(|grid| part1(&grid))
 }
 
+pub fn day3_2(input: &str) -> Result<u32, NotAGrid> {
+    parse(input).map(|grid| part2(&grid))
+}
+
+harness::check_parsers!(parse);
+
 #[cfg(test)]
 mod tests {
-    use super::{day3_1, day3_2};
+    use super::{check_parsers, day3_1, day3_2};
+    use proptest::prelude::*;
 
     const INPUT: &str = "467..114..
 ...*......
@@ -182,15 +200,16 @@ mod tests {
 ...$.*....
 .664.598..
 ";
-    #[test]
-    fn test_day3_1() {
-        let res = day3_1(INPUT);
-        assert_eq!(res, Ok(4361))
+
+    example_tests! {
+        test_day3_1: day3_1(INPUT) => Ok(4361),
+        test_day3_2: day3_2(INPUT) => Ok(467835),
     }
 
-    #[test]
-    fn test_day3_2() {
-        let res = day3_2(INPUT);
-        assert_eq!(res, Ok(467835))
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[0-9.*#+\n]{0,64}") {
+            check_parsers(&input);
+        }
     }
 }
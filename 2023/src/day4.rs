@@ -1,3 +1,8 @@
+//! Day 4: Scratchcards
+//!
+//! `parse` counts the winning numbers of each card.
+
+#[cfg(feature = "scaling")]
 use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
@@ -7,6 +12,8 @@ use nom::{
     sequence::preceded,
     IResult,
 };
+#[cfg(feature = "scaling")]
+use rand::Rng;
 
 use super::parse::sp;
 
@@ -51,19 +58,22 @@ impl Card {
     }
 }
 
-pub fn day4_1(input: &str) -> Result<u32, ParseError> {
+/// How many winning numbers each card has
+pub fn parse(input: &str) -> Result<Vec<u32>, ParseError<'_>> {
     input
         .lines()
-        .map(Card::parse_calc_matches)
-        .fold_ok(0, |acc, (_, n)| acc + Card::matches_to_points(n))
+        .map(|line| Ok(Card::parse_calc_matches(line)?.1))
+        .collect()
+}
+
+pub fn part1(matches: &[u32]) -> u32 {
+    matches.iter().map(|&n| Card::matches_to_points(n)).sum()
 }
 
-pub fn day4_2(input: &str) -> Result<u32, ParseError> {
+pub fn part2(all_matches: &[u32]) -> u32 {
     let mut cards = Vec::<Card>::new();
     let mut total = 0;
-    for match_parse in input.lines().map(Card::parse_calc_matches) {
-        let (_, matches) = match_parse?;
-
+    for &matches in all_matches {
         let amount = 1 + cards.iter().map(|card| card.amount).sum::<u32>();
         total += amount;
 
@@ -82,12 +92,72 @@ pub fn day4_2(input: &str) -> Result<u32, ParseError> {
             cards.insert(pos, new_card);
         }
     }
-    Ok(total)
+    total
+}
+
+pub fn day4_1(input: &str) -> Result<u32, ParseError<'_>> {
+    parse(input).map(|matches| part1(&matches))
+}
+
+pub fn day4_2(input: &str) -> Result<u32, ParseError<'_>> {
+    parse(input).map(|matches| part2(&matches))
+}
+
+/// Generates `size` scratchcards, for the scaling benchmark. Cards have fewer
+/// numbers than the puzzle's, as otherwise the won copies grow exponentially
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    let mut numbers = |amount| {
+        rand::seq::index::sample(rng, 99, amount)
+            .iter()
+            .map(|n| format!("{:>2}", n + 1))
+            .join(" ")
+    };
+    (1..=size)
+        .map(|id| format!("Card {id}: {} | {}\n", numbers(5), numbers(8)))
+        .collect()
+}
+
+harness::check_parsers!(Card::parse_calc_matches);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    fn matches(line: &str) -> usize {
+        let (_, numbers) = line.split_once(':').unwrap();
+        let (winning, have) = numbers.split_once('|').unwrap();
+        let winning: Vec<&str> = winning.split_whitespace().collect();
+        have.split_whitespace()
+            .filter(|n| winning.contains(n))
+            .count()
+    }
+
+    pub fn day4_1(input: &str) -> u32 {
+        input
+            .lines()
+            .map(|line| match matches(line) {
+                0 => 0,
+                n => 2u32.pow(n as u32 - 1),
+            })
+            .sum()
+    }
+
+    pub fn day4_2(input: &str) -> u32 {
+        let matches: Vec<usize> = input.lines().map(matches).collect();
+        let mut copies = vec![1; matches.len()];
+        for card in 0..matches.len() {
+            for won in card + 1..=(card + matches[card]).min(matches.len() - 1) {
+                copies[won] += copies[card];
+            }
+        }
+        copies.iter().sum()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const INPUT: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
 Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
@@ -97,15 +167,43 @@ Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
 ";
 
-    #[test]
-    fn test_day4_1() {
-        let res = day4_1(INPUT);
-        assert_eq!(res, Ok(13))
+    example_tests! {
+        test_day4_1: day4_1(INPUT) => Ok(13),
+        test_day4_2: day4_2(INPUT) => Ok(30),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[Card :0-9|\n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+
+    fn cards() -> impl Strategy<Value = String> {
+        let card = (
+            prop::collection::btree_set(1..100u32, 1..6),
+            prop::collection::btree_set(1..100u32, 1..10),
+        );
+        prop::collection::vec(card, 1..8).prop_map(|cards| {
+            let numbers = |numbers: &std::collections::BTreeSet<u32>| {
+                numbers
+                    .iter()
+                    .map(|n| format!("{n:>2}"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            cards
+                .iter()
+                .enumerate()
+                .map(|(i, (winning, have))| {
+                    format!("Card {}: {} | {}\n", i + 1, numbers(winning), numbers(have))
+                })
+                .collect()
+        })
     }
 
-    #[test]
-    fn test_day4_2() {
-        let res = day4_2(INPUT);
-        assert_eq!(res, Ok(30))
+    differential_tests! {
+        test_day4_1_reference(cards()): day4_1 == reference::day4_1,
+        test_day4_2_reference(cards()): day4_2 == reference::day4_2,
     }
 }
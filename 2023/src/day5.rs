@@ -1,3 +1,8 @@
+//! Day 5: If You Give A Seed A Fertilizer
+//!
+//! `parse` reads the seeds and a [`CategoryMap`] per [`Category`], which
+//! converts single ids or whole ranges of them to the next category.
+
 use std::ops::Range;
 
 use itertools::Itertools;
@@ -15,10 +20,12 @@ use crate::parse::sp;
 
 pub type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
 
-type Id = u64;
+/// A seed, soil, fertilizer or other number
+pub type Id = u64;
 
+/// What the ids in a map stand for
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Category {
+pub enum Category {
     Seed,
     Soil,
     Fertilizer,
@@ -44,6 +51,7 @@ impl Category {
     }
 }
 
+/// Parses the `seeds:` line
 pub fn parse_seed_list(input: &str) -> IResult<&str, Vec<Id>> {
     let (i, _) = preceded(sp, tag("seeds:"))(input)?;
     separated_list1(char(' '), preceded(sp, u64))(i)
@@ -82,10 +90,12 @@ impl MapRange {
     }
 }
 
+/// A map from the ids of one category to those of the next one. Ids that no
+/// range of the map covers keep their number
 #[derive(Debug)]
-struct CategoryMap {
-    source_category: Category,
-    destination_category: Category,
+pub struct CategoryMap {
+    pub source_category: Category,
+    pub destination_category: Category,
     ranges: Vec<MapRange>,
 }
 
@@ -107,6 +117,7 @@ impl CategoryMap {
         ))
     }
 
+    /// The destination id of a source id
     pub fn convert(&self, id: Id) -> Id {
         for range in &self.ranges {
             let Some(convertion) = range.convert(id) else {
@@ -117,6 +128,8 @@ impl CategoryMap {
         id
     }
 
+    /// The destination ids of a whole range of source ids, which the map may
+    /// split into several ranges
     pub fn convert_range(&self, mut range: Range<Id>) -> Vec<Range<Id>> {
         let mut res = Vec::new();
         let mut push_to_res = |range: Range<Id>| {
@@ -196,8 +209,13 @@ fn parse_input(input: &str) -> IResult<&str, (Vec<Id>, Vec<CategoryMap>)> {
     Ok((i, (seeds, maps)))
 }
 
-pub fn day5_1(input: &str) -> Result<Id, ParseError> {
-    let (_, (mut ids, maps)) = parse_input(input)?;
+/// The seeds, and the maps from each category to the next
+pub fn parse(input: &str) -> Result<(Vec<Id>, Vec<CategoryMap>), ParseError<'_>> {
+    Ok(parse_input(input)?.1)
+}
+
+pub fn part1((ids, maps): &(Vec<Id>, Vec<CategoryMap>)) -> Id {
+    let mut ids = ids.clone();
     let mut curr_cat = Category::Seed;
     while curr_cat != Category::Location {
         let map = maps
@@ -209,13 +227,13 @@ pub fn day5_1(input: &str) -> Result<Id, ParseError> {
         }
         curr_cat = map.destination_category;
     }
-    Ok(ids.into_iter().min().unwrap())
+    ids.into_iter().min().unwrap()
 }
 
-pub fn day5_2(input: &str) -> Result<Id, ParseError> {
-    let (_, (ranges, maps)) = parse_input(input)?;
+pub fn part2((ranges, maps): &(Vec<Id>, Vec<CategoryMap>)) -> Id {
     let mut ranges: Vec<_> = ranges
-        .into_iter()
+        .iter()
+        .copied()
         .tuples()
         .map(|(begin, length)| begin..(begin + length))
         .collect();
@@ -241,13 +259,69 @@ pub fn day5_2(input: &str) -> Result<Id, ParseError> {
 
     let smallest = ranges.into_iter().min_by_key(|range| range.start).unwrap();
 
-    Ok(smallest.start)
+    smallest.start
+}
+
+pub fn day5_1(input: &str) -> Result<Id, ParseError<'_>> {
+    parse(input).map(|almanac| part1(&almanac))
+}
+
+pub fn day5_2(input: &str) -> Result<Id, ParseError<'_>> {
+    parse(input).map(|almanac| part2(&almanac))
 }
 
+harness::check_parsers!(parse_input);
+
+/// Naive implementations, checked against the solvers by the differential tests
 #[cfg(test)]
-mod tests {
+mod reference {
+    fn numbers(line: &str) -> Vec<u64> {
+        line.split_whitespace()
+            .filter_map(|n| n.parse().ok())
+            .collect()
+    }
 
-    use super::{day5_1, day5_2};
+    fn parse(input: &str) -> (Vec<u64>, Vec<Vec<Vec<u64>>>) {
+        let mut blocks = input.split("\n\n");
+        let seeds = numbers(blocks.next().unwrap());
+        let maps = blocks
+            .map(|block| block.lines().skip(1).map(numbers).collect())
+            .collect();
+        (seeds, maps)
+    }
+
+    fn location(maps: &[Vec<Vec<u64>>], seed: u64) -> u64 {
+        maps.iter().fold(seed, |id, map| {
+            map.iter()
+                .find(|range| (range[1]..range[1] + range[2]).contains(&id))
+                .map_or(id, |range| id - range[1] + range[0])
+        })
+    }
+
+    pub fn day5_1(input: &str) -> u64 {
+        let (seeds, maps) = parse(input);
+        seeds
+            .iter()
+            .map(|&seed| location(&maps, seed))
+            .min()
+            .unwrap()
+    }
+
+    pub fn day5_2(input: &str) -> u64 {
+        let (seeds, maps) = parse(input);
+        seeds
+            .chunks(2)
+            .flat_map(|range| range[0]..range[0] + range[1])
+            .map(|seed| location(&maps, seed))
+            .min()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_parsers, day5_1, day5_2, reference};
+    use proptest::prelude::*;
 
     const INPUT: &str = "seeds: 79 14 55 13
 
@@ -284,15 +358,74 @@ humidity-to-location map:
 56 93 4
 ";
 
-    #[test]
-    fn test_day5_1() {
-        let res = day5_1(INPUT);
-        assert_eq!(res, Ok(35))
+    example_tests! {
+        test_day5_1: day5_1(INPUT) => Ok(35),
+        test_day5_2: day5_2(INPUT) => Ok(46),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[seedsoiltr: 0-9\n-]{0,96}") {
+            check_parsers(&input);
+        }
+    }
+
+    const CATEGORIES: [&str; 8] = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+
+    fn map() -> impl Strategy<Value = String> {
+        // pairs of sorted bounds give disjoint source ranges
+        prop::collection::btree_set(0..150u64, 2..8)
+            .prop_flat_map(|bounds| {
+                let sources: Vec<(u64, u64)> = bounds
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .chunks_exact(2)
+                    .map(|pair| (pair[0], pair[1] - pair[0]))
+                    .collect();
+                let destinations = prop::collection::vec(0..150u64, sources.len());
+                (Just(sources), destinations)
+            })
+            .prop_map(|(sources, destinations)| {
+                sources
+                    .iter()
+                    .zip(destinations)
+                    .map(|((source, len), destination)| format!("{destination} {source} {len}\n"))
+                    .collect()
+            })
+    }
+
+    fn almanac() -> impl Strategy<Value = String> {
+        (
+            prop::collection::vec((0..100u64, 1..20u64), 1..6),
+            prop::collection::vec(map(), 7),
+        )
+            .prop_map(|(seeds, maps)| {
+                let seeds: Vec<String> = seeds
+                    .iter()
+                    .map(|(start, len)| format!("{start} {len}"))
+                    .collect();
+                let maps: Vec<String> = maps
+                    .iter()
+                    .enumerate()
+                    .map(|(i, map)| {
+                        format!("{}-to-{} map:\n{map}", CATEGORIES[i], CATEGORIES[i + 1])
+                    })
+                    .collect();
+                format!("seeds: {}\n\n{}", seeds.join(" "), maps.join("\n"))
+            })
     }
 
-    #[test]
-    fn test_day5_2() {
-        let res = day5_2(INPUT);
-        assert_eq!(res, Ok(46))
+    differential_tests! {
+        test_day5_1_reference(almanac()): day5_1 == reference::day5_1,
+        test_day5_2_reference(almanac()): day5_2 == reference::day5_2,
     }
 }
@@ -1,6 +1,11 @@
+//! Day 6: Wait For It
+//!
+//! `parse` reads the sheet both as several [`Race`]s and as a single one.
+
 use nom::{
     bytes::complete::{tag, take_while1},
     character::complete::{char, i64},
+    combinator::map_res,
     multi::many1,
     sequence::{delimited, preceded},
     IResult,
@@ -10,7 +15,7 @@ use crate::parse::sp;
 
 pub type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
 
-struct Race {
+pub struct Race {
     time: i64,
     distance: i64,
 }
@@ -28,19 +33,20 @@ impl Race {
     }
 
     fn parse_bad_kerning_line<'a>(line_tag: &str, input: &'a str) -> IResult<&'a str, i64> {
-        let (i, res) = delimited(
+        delimited(
             tag(line_tag),
-            take_while1(|c: char| c == ' ' || c.is_ascii_digit()),
+            map_res(
+                take_while1(|c: char| c == ' ' || c.is_ascii_digit()),
+                |digits: &str| {
+                    digits
+                        .chars()
+                        .filter(|c| c.is_ascii_digit())
+                        .collect::<String>()
+                        .parse()
+                },
+            ),
             char('\n'),
-        )(input)?;
-        Ok((
-            i,
-            res.chars()
-                .filter(|c| c.is_ascii_digit())
-                .collect::<String>()
-                .parse()
-                .unwrap(),
-        ))
+        )(input)
     }
 
     pub fn parse_bad_kerning(input: &str) -> IResult<&str, Self> {
@@ -50,49 +56,125 @@ impl Race {
     }
 
     pub fn ways_to_beat_record(&self) -> u64 {
+        let (time, distance) = (self.time as i128, self.distance as i128);
+        // no hold goes further than the record, and the roots below would be
+        // NaN or the same
+        if time <= 0 || time * time <= 4 * distance {
+            return 0;
+        }
         let t = self.time as f64;
         let d = self.distance as f64;
         let delta = (t * t - 4.0 * d).sqrt();
         let x0 = (t - delta) / 2.0;
         let x1 = (t + delta) / 2.0;
         // the final answer is the amount of integers in the range ]x0, x1[
-        (x1.floor() as u64) - (x0.floor() as u64)
-            // edge case when x0/x1 is an integer
-            - if x0.fract() < 1e-10 { 1 } else { 0 }
+        // that the button can be held for, which may still be empty
+        let first = (x0.floor() as i64 + 1).max(0);
+        let last = (x1.ceil() as i64 - 1).min(self.time);
+        (last + 1 - first).max(0) as u64
     }
 }
 
-pub fn day6_1(input: &str) -> Result<u64, ParseError> {
+/// The races as part 1 reads the sheet, and the single race part 2 reads
+pub fn parse(input: &str) -> Result<(Vec<Race>, Race), ParseError<'_>> {
     let (_, races) = Race::parse_races(input)?;
-    let prod = races
-        .into_iter()
+    let (_, race) = Race::parse_bad_kerning(input)?;
+    Ok((races, race))
+}
+
+pub fn part1((races, _): &(Vec<Race>, Race)) -> u64 {
+    races
+        .iter()
         .map(|race| race.ways_to_beat_record())
-        .product();
-    Ok(prod)
+        .product()
 }
 
-pub fn day6_2(input: &str) -> Result<u64, ParseError> {
-    let (_, race) = Race::parse_bad_kerning(input)?;
-    Ok(race.ways_to_beat_record())
+pub fn part2((_, race): &(Vec<Race>, Race)) -> u64 {
+    race.ways_to_beat_record()
+}
+
+pub fn day6_1(input: &str) -> Result<u64, ParseError<'_>> {
+    parse(input).map(|races| part1(&races))
+}
+
+pub fn day6_2(input: &str) -> Result<u64, ParseError<'_>> {
+    parse(input).map(|races| part2(&races))
+}
+
+harness::check_parsers!(Race::parse_races, Race::parse_bad_kerning);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    fn ways_to_win(time: u64, distance: u64) -> u64 {
+        (0..=time)
+            .filter(|hold| hold * (time - hold) > distance)
+            .count() as u64
+    }
+
+    fn lines(input: &str) -> impl Iterator<Item = Vec<&str>> {
+        input
+            .lines()
+            .map(|line| line.split_whitespace().skip(1).collect())
+    }
+
+    pub fn day6_1(input: &str) -> u64 {
+        let mut lines = lines(input);
+        let times = lines.next().unwrap();
+        let distances = lines.next().unwrap();
+        times
+            .iter()
+            .zip(distances)
+            .map(|(time, distance)| ways_to_win(time.parse().unwrap(), distance.parse().unwrap()))
+            .product()
+    }
+
+    pub fn day6_2(input: &str) -> u64 {
+        let mut numbers = lines(input).map(|line| line.concat().parse().unwrap());
+        ways_to_win(numbers.next().unwrap(), numbers.next().unwrap())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{day6_1, day6_2};
+    use super::{check_parsers, day6_1, day6_2, reference};
+    use proptest::prelude::*;
 
     const INPUT: &str = "Time:      7  15   30
 Distance:  9  40  200
 ";
 
-    #[test]
-    fn test_day6_1() {
-        let res = day6_1(INPUT);
-        assert_eq!(res, Ok(288))
+    example_tests! {
+        test_day6_1: day6_1(INPUT) => Ok(288),
+        test_day6_2: day6_2(INPUT) => Ok(71503),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[TimeDstanc: 0-9\n]{0,64}") {
+            check_parsers(&input);
+        }
+
+        #[test]
+        fn race_sheets_never_panic(input in "Time:[ 0-9]{0,24}\nDistance:[ 0-9]{0,24}\n") {
+            check_parsers(&input);
+        }
+    }
+
+    fn races() -> impl Strategy<Value = String> {
+        // up to records that can't be beaten, and races that are over at once
+        let race = (0..60u64).prop_flat_map(|time| (Just(time), 0..=time * time / 4 + 2));
+        prop::collection::vec(race, 1..3).prop_map(|races| {
+            let (times, distances): (Vec<String>, Vec<String>) = races
+                .iter()
+                .map(|(time, distance)| (format!("{time:>4}"), format!("{distance:>4}")))
+                .unzip();
+            format!("Time:{}\nDistance:{}\n", times.concat(), distances.concat())
+        })
     }
 
-    #[test]
-    fn test_day6_2() {
-        let res = day6_2(INPUT);
-        assert_eq!(res, Ok(71503))
+    differential_tests! {
+        test_day6_1_reference(races()): day6_1 == reference::day6_1,
+        test_day6_2_reference(races()): day6_2 == reference::day6_2,
     }
 }
@@ -1,3 +1,11 @@
+//! Day 7: Camel Cards
+//!
+//! `parse` reads every [`Hand`]. [`HandType`] tells the kind of five cards,
+//! with or without jokers.
+
+#[cfg(feature = "scaling")]
+use std::collections::HashSet;
+
 use itertools::Itertools;
 use nom::{
     branch::alt,
@@ -7,13 +15,16 @@ use nom::{
     sequence::preceded,
     IResult,
 };
+#[cfg(feature = "scaling")]
+use rand::Rng;
 
 use crate::parse::sp;
 
 pub type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
 
+/// A card's label, ordered by strength when J is a jack
 #[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy)]
-enum Card {
+pub enum Card {
     Two = 0,
     Three = 1,
     Four = 2,
@@ -51,8 +62,9 @@ impl Card {
     }
 }
 
+/// The kind of a hand, ordered from the weakest to the strongest
 #[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy)]
-enum HandType {
+pub enum HandType {
     HighCard = 0,
     OnePair = 1,
     TwoPair = 2,
@@ -63,7 +75,9 @@ enum HandType {
 }
 
 impl HandType {
-    fn from_hand(hand: [Card; HAND_SIZE], j_as_joker: bool) -> Self {
+    /// The kind of `hand`. With `j_as_joker` the jacks are jokers instead,
+    /// which act as whatever card makes the strongest kind
+    pub fn from_hand(hand: [Card; HAND_SIZE], j_as_joker: bool) -> Self {
         let mut counts = [0; NUM_CARDS];
         for card in hand {
             counts[card as usize] += 1;
@@ -98,25 +112,20 @@ impl HandType {
     }
 }
 
-const HAND_SIZE: usize = 5;
+pub const HAND_SIZE: usize = 5;
 
-struct Hand {
-    power: u32,
+/// A hand of cards, and what was bid on it
+pub struct Hand {
+    cards: [Card; HAND_SIZE],
     bid: u32,
 }
 
 impl Hand {
-    fn parse<P>(power_func: P) -> impl Fn(&str) -> IResult<&str, Self>
-    where
-        P: Fn([Card; HAND_SIZE]) -> u32,
-    {
-        move |input| {
-            let mut hand = [Card::Two; HAND_SIZE];
-            let (i, ()) = preceded(sp, fill(Card::parse, &mut hand))(input)?;
-            let (i, bid) = preceded(sp, u32)(i)?;
-            let power = power_func(hand);
-            Ok((i, Self { power, bid }))
-        }
+    fn parse(input: &str) -> IResult<&str, Self> {
+        let mut cards = [Card::Two; HAND_SIZE];
+        let (i, ()) = preceded(sp, fill(Card::parse, &mut cards))(input)?;
+        let (i, bid) = preceded(sp, u32)(i)?;
+        Ok((i, Self { cards, bid }))
     }
 
     fn power(hand: [Card; HAND_SIZE]) -> u32 {
@@ -142,37 +151,119 @@ impl Hand {
     }
 }
 
-fn process_day<P>(input: &str, line_parser: P) -> Result<u32, ParseError>
-where
-    P: Fn(&str) -> IResult<&str, Hand>,
-{
-    let mut hands: Vec<_> = input
-        .lines()
-        .map(line_parser)
-        .map_ok(|(_, hand)| hand)
-        .collect::<Result<Vec<_>, _>>()?;
-    hands.sort_unstable_by_key(|hand| hand.power);
-    let total = hands
+fn total_winnings(hands: &[Hand], power_func: fn([Card; HAND_SIZE]) -> u32) -> u32 {
+    let mut hands: Vec<_> = hands
+        .iter()
+        .map(|hand| (power_func(hand.cards), hand.bid))
+        .collect();
+    hands.sort_unstable_by_key(|&(power, _)| power);
+    hands
         .into_iter()
         .enumerate()
-        .map(|(i, hand)| (i as u32 + 1) * hand.bid)
-        .sum();
-    Ok(total)
+        .map(|(i, (_, bid))| (i as u32 + 1) * bid)
+        .sum()
+}
+
+/// Every hand with its bid
+pub fn parse(input: &str) -> Result<Vec<Hand>, ParseError<'_>> {
+    input
+        .lines()
+        .map(Hand::parse)
+        .map_ok(|(_, hand)| hand)
+        .collect()
+}
+
+pub fn part1(hands: &[Hand]) -> u32 {
+    total_winnings(hands, Hand::power)
+}
+
+pub fn part2(hands: &[Hand]) -> u32 {
+    total_winnings(hands, Hand::power_joker)
 }
 
-pub fn day7_1(input: &str) -> Result<u32, ParseError> {
-    let line_parser = Hand::parse(Hand::power);
-    process_day(input, line_parser)
+pub fn day7_1(input: &str) -> Result<u32, ParseError<'_>> {
+    parse(input).map(|hands| part1(&hands))
 }
 
-pub fn day7_2(input: &str) -> Result<u32, ParseError> {
-    let line_parser = Hand::parse(Hand::power_joker);
-    process_day(input, line_parser)
+pub fn day7_2(input: &str) -> Result<u32, ParseError<'_>> {
+    parse(input).map(|hands| part2(&hands))
+}
+
+/// Generates `size` different hands, for the scaling benchmark. The winnings
+/// are a `u32`, which only fits a few thousand hands
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    const CARDS: &[u8] = b"23456789TJQKA";
+    let mut hands = HashSet::new();
+    while hands.len() < size.min(2048) {
+        let hand: String = (0..5)
+            .map(|_| CARDS[rng.gen_range(0..CARDS.len())] as char)
+            .collect();
+        hands.insert(hand);
+    }
+    hands
+        .iter()
+        .map(|hand| format!("{hand} {}\n", rng.gen_range(1..=1000)))
+        .collect()
+}
+
+harness::check_parsers!(Hand::parse);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    /// Higher is stronger, from high card to five of a kind
+    fn kind(hand: &[u8]) -> Vec<usize> {
+        let mut counts: Vec<usize> = hand
+            .iter()
+            .map(|card| hand.iter().filter(|&other| other == card).count())
+            .collect();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        counts
+    }
+
+    fn total_winnings(input: &str, strength: impl Fn(&str) -> (Vec<usize>, Vec<usize>)) -> u32 {
+        let mut hands: Vec<(&str, u32)> = input
+            .lines()
+            .map(|line| {
+                let (hand, bid) = line.split_once(' ').unwrap();
+                (hand, bid.parse().unwrap())
+            })
+            .collect();
+        hands.sort_by_key(|&(hand, _)| strength(hand));
+        hands
+            .iter()
+            .enumerate()
+            .map(|(rank, (_, bid))| (rank as u32 + 1) * bid)
+            .sum()
+    }
+
+    fn card_values(hand: &str, order: &str) -> Vec<usize> {
+        hand.chars().map(|card| order.find(card).unwrap()).collect()
+    }
+
+    pub fn day7_1(input: &str) -> u32 {
+        total_winnings(input, |hand| {
+            (kind(hand.as_bytes()), card_values(hand, "23456789TJQKA"))
+        })
+    }
+
+    pub fn day7_2(input: &str) -> u32 {
+        total_winnings(input, |hand| {
+            let best_kind = "23456789TQKA"
+                .chars()
+                .map(|joker| kind(hand.replace('J', &joker.to_string()).as_bytes()))
+                .max()
+                .unwrap();
+            (best_kind, card_values(hand, "J23456789TQKA"))
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{day7_1, day7_2};
+    use super::{check_parsers, day7_1, day7_2, reference};
+    use proptest::prelude::*;
 
     const INPUT: &str = "32T3K 765
 T55J5 684
@@ -181,15 +272,30 @@ KTJJT 220
 QQQJA 483
 ";
 
-    #[test]
-    fn test_day7_1() {
-        let res = day7_1(INPUT);
-        assert_eq!(res, Ok(6440))
+    example_tests! {
+        test_day7_1: day7_1(INPUT) => Ok(6440),
+        test_day7_2: day7_2(INPUT) => Ok(5905),
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[2-9TJQKA 0-9\n]{0,32}") {
+            check_parsers(&input);
+        }
+    }
+
+    fn hands() -> impl Strategy<Value = String> {
+        // few different cards so that most hands are more than a high card
+        prop::collection::btree_map("[29TJQA]{5}", 1..1000u32, 1..20).prop_map(|hands| {
+            hands
+                .iter()
+                .map(|(hand, bid)| format!("{hand} {bid}\n"))
+                .collect()
+        })
     }
 
-    #[test]
-    fn test_day7_2() {
-        let res = day7_2(INPUT);
-        assert_eq!(res, Ok(5905))
+    differential_tests! {
+        test_day7_1_reference(hands()): day7_1 == reference::day7_1,
+        test_day7_2_reference(hands()): day7_2 == reference::day7_2,
     }
 }
@@ -1,3 +1,7 @@
+//! Day 8: Haunted Wasteland
+//!
+//! `parse` reads the left/right [`Direction`]s and the network of [`Node`]s.
+
 use std::collections::{HashMap, HashSet};
 
 use nom::{
@@ -16,7 +20,7 @@ use crate::parse::sp;
 pub type ParseError<'a> = nom::Err<nom::error::Error<&'a str>>;
 
 #[derive(Debug, Clone, Copy)]
-enum Direction {
+pub enum Direction {
     Left,
     Right,
 }
@@ -28,7 +32,7 @@ impl Direction {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
-struct NodeId(u16);
+pub struct NodeId(u16);
 
 impl NodeId {
     pub fn parse(input: &str) -> IResult<&str, Self> {
@@ -54,7 +58,7 @@ impl NodeId {
 }
 
 #[derive(Debug)]
-struct Node {
+pub struct Node {
     left: NodeId,
     right: NodeId,
 }
@@ -72,7 +76,8 @@ impl Node {
     }
 }
 
-fn parse_input(input: &str) -> Result<(Vec<Direction>, HashMap<NodeId, Node>), ParseError> {
+/// The left/right instructions, and where each node leads
+pub fn parse(input: &str) -> Result<(Vec<Direction>, HashMap<NodeId, Node>), ParseError<'_>> {
     let (i, dirs) = many0(Direction::parse)(input)?;
     let (_, nodes) = fold_many0(Node::parse, HashMap::new, |mut acc, (id, node)| {
         acc.insert(id, node);
@@ -81,12 +86,11 @@ fn parse_input(input: &str) -> Result<(Vec<Direction>, HashMap<NodeId, Node>), P
     Ok((dirs, nodes))
 }
 
-pub fn day8_1(input: &str) -> Result<u64, ParseError> {
-    let (dirs, nodes) = parse_input(input)?;
+pub fn part1((dirs, nodes): &(Vec<Direction>, HashMap<NodeId, Node>)) -> u64 {
     let mut curr_node = NodeId::from_chars(&['A', 'A', 'A']);
     let target_node = NodeId::from_chars(&['Z', 'Z', 'Z']);
     let mut steps = 0;
-    for dir in dirs.into_iter().cycle() {
+    for dir in dirs.iter().cycle() {
         steps += 1;
         let Node { left, right } = nodes[&curr_node];
         curr_node = match dir {
@@ -97,11 +101,10 @@ pub fn day8_1(input: &str) -> Result<u64, ParseError> {
             break;
         }
     }
-    Ok(steps)
+    steps
 }
 
-pub fn day8_2(input: &str) -> Result<u64, ParseError> {
-    let (dirs, nodes) = parse_input(input)?;
+pub fn part2((dirs, nodes): &(Vec<Direction>, HashMap<NodeId, Node>)) -> u64 {
     let starting_nodes: HashSet<NodeId> =
         HashSet::from_iter(nodes.keys().copied().filter(|node| node.ends_with(b'A')));
     let mut cycle_lengths = Vec::with_capacity(starting_nodes.len());
@@ -121,13 +124,23 @@ pub fn day8_2(input: &str) -> Result<u64, ParseError> {
             }
         }
     }
-    let lcm = cycle_lengths.into_iter().fold(1, |acc, n| acc.lcm(&n));
-    Ok(lcm)
+    cycle_lengths.into_iter().fold(1, |acc, n| acc.lcm(&n))
+}
+
+pub fn day8_1(input: &str) -> Result<u64, ParseError<'_>> {
+    parse(input).map(|network| part1(&network))
+}
+
+pub fn day8_2(input: &str) -> Result<u64, ParseError<'_>> {
+    parse(input).map(|network| part2(&network))
 }
 
+harness::check_parsers!(parse);
+
 #[cfg(test)]
 mod tests {
-    use super::{day8_1, day8_2};
+    use super::{check_parsers, day8_1, day8_2, NodeId};
+    use proptest::prelude::*;
 
     const INPUT: &str = "RL
 
@@ -157,21 +170,24 @@ BBZ = (BBB, BBB)
 XXX = (XXX, XXX)
 ";
 
-    #[test]
-    fn test_day8_1() {
-        let res = day8_1(INPUT);
-        assert_eq!(res, Ok(2))
+    example_tests! {
+        test_day8_1: day8_1(INPUT) => Ok(2),
+        test_day8_1_2: day8_1(INPUT2) => Ok(6),
+        test_day8_2: day8_2(INPUT3) => Ok(6),
     }
 
-    #[test]
-    fn test_day8_1_2() {
-        let res = day8_1(INPUT2);
-        assert_eq!(res, Ok(6))
-    }
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[LRA-Z =(),\n]{0,64}") {
+            check_parsers(&input);
+        }
 
-    #[test]
-    fn test_day8_2() {
-        let res = day8_2(INPUT3);
-        assert_eq!(res, Ok(6))
+        #[test]
+        fn node_ids_are_unique(a in "[A-Z]{3}", b in "[A-Z]{3}") {
+            let (_, id_a) = NodeId::parse(&a).unwrap();
+            let (_, id_b) = NodeId::parse(&b).unwrap();
+            prop_assert_eq!(a == b, id_a == id_b);
+            prop_assert!(id_a.ends_with(a.as_bytes()[2]));
+        }
     }
 }
@@ -1,3 +1,7 @@
+//! Day 9: Mirage Maintenance
+//!
+//! `parse` reads the history of each value.
+
 use itertools::Itertools;
 use nom::{
     character::complete::{char, i64},
@@ -5,6 +9,8 @@ use nom::{
     sequence::preceded,
     IResult,
 };
+#[cfg(feature = "scaling")]
+use rand::Rng;
 
 use crate::parse::sp;
 
@@ -49,43 +55,152 @@ fn extrapolate_first(seq: Sequence) -> SequenceValue {
         .0
 }
 
-fn process_day<F>(input: &str, extrapolate: F) -> Result<SequenceValue, ParseError>
-where
-    F: Fn(Sequence) -> SequenceValue,
-{
+/// The value at `index` of the sequence that `seq` starts, following its
+/// differences: Newton's forward difference formula, for extrapolating far
+/// ahead without computing every value on the way
+#[cfg_attr(not(feature = "day21"), allow(dead_code))]
+pub(crate) fn extrapolate_at(seq: Sequence, index: SequenceValue) -> SequenceValue {
+    let mut binomial = 1;
+    let mut value = 0;
+    for (k, diffs) in get_diffs(seq).iter().enumerate() {
+        let Some(first) = diffs.first() else {
+            break;
+        };
+        value += binomial * first;
+        binomial = binomial * (index - k as SequenceValue) / (k as SequenceValue + 1);
+    }
+    value
+}
+
+/// The history of every value
+pub fn parse(input: &str) -> Result<Vec<Sequence>, ParseError<'_>> {
     input
         .lines()
         .map(parse_sequence)
-        .map_ok(|(_, seq)| extrapolate(seq))
-        .fold_ok(0, |acc, n| acc + n)
+        .map_ok(|(_, seq)| seq)
+        .collect()
 }
 
-pub fn day9_1(input: &str) -> Result<SequenceValue, ParseError> {
-    process_day(input, extrapolate_last)
+pub fn part1(sequences: &[Sequence]) -> SequenceValue {
+    sequences
+        .iter()
+        .map(|seq| extrapolate_last(seq.clone()))
+        .sum()
 }
 
-pub fn day9_2(input: &str) -> Result<SequenceValue, ParseError> {
-    process_day(input, extrapolate_first)
+pub fn part2(sequences: &[Sequence]) -> SequenceValue {
+    sequences
+        .iter()
+        .map(|seq| extrapolate_first(seq.clone()))
+        .sum()
+}
+
+pub fn day9_1(input: &str) -> Result<SequenceValue, ParseError<'_>> {
+    parse(input).map(|sequences| part1(&sequences))
+}
+
+pub fn day9_2(input: &str) -> Result<SequenceValue, ParseError<'_>> {
+    parse(input).map(|sequences| part2(&sequences))
+}
+
+/// Generates a report of `size` histories, for the scaling benchmark. Like in
+/// the puzzle, each one is 21 values of a polynomial of small degree
+#[cfg(feature = "scaling")]
+pub(crate) fn generate(rng: &mut impl Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let degree = rng.gen_range(0..=6);
+            let coefficients: Vec<i64> = (0..=degree).map(|_| rng.gen_range(-5..=5)).collect();
+            let value = |x: i64| coefficients.iter().rev().fold(0, |acc, c| acc * x + c);
+            (0..21).map(value).join(" ") + "\n"
+        })
+        .collect()
+}
+
+harness::check_parsers!(parse_sequence);
+
+/// Naive implementations, checked against the solvers by the differential tests
+#[cfg(test)]
+mod reference {
+    fn differences(sequence: &[i64]) -> Vec<i64> {
+        sequence.windows(2).map(|pair| pair[1] - pair[0]).collect()
+    }
+
+    fn next(sequence: &[i64]) -> i64 {
+        if sequence.iter().all(|&n| n == 0) {
+            0
+        } else {
+            sequence.last().unwrap() + next(&differences(sequence))
+        }
+    }
+
+    fn previous(sequence: &[i64]) -> i64 {
+        if sequence.iter().all(|&n| n == 0) {
+            0
+        } else {
+            sequence[0] - previous(&differences(sequence))
+        }
+    }
+
+    fn sequences(input: &str) -> impl Iterator<Item = Vec<i64>> + '_ {
+        input
+            .lines()
+            .map(|line| line.split(' ').map(|n| n.parse().unwrap()).collect())
+    }
+
+    pub fn day9_1(input: &str) -> i64 {
+        sequences(input).map(|sequence| next(&sequence)).sum()
+    }
+
+    pub fn day9_2(input: &str) -> i64 {
+        sequences(input).map(|sequence| previous(&sequence)).sum()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{day9_1, day9_2};
+    use super::{check_parsers, day9_1, day9_2, extrapolate_at, reference};
+    use proptest::prelude::*;
 
     const INPUT: &str = "0 3 6 9 12 15
 1 3 6 10 15 21
 10 13 16 21 30 45
 ";
 
-    #[test]
-    fn test_day9_1() {
-        let res = day9_1(INPUT);
-        assert_eq!(res, Ok(114))
+    example_tests! {
+        test_day9_1: day9_1(INPUT) => Ok(114),
+        test_day9_2: day9_2(INPUT) => Ok(2),
+        test_extrapolate_at: extrapolate_at(vec![1, 3, 6, 10, 15, 21], 6) => 28,
+        test_extrapolate_at_far: extrapolate_at(vec![1, 3, 6], 99) => 5050,
+        test_extrapolate_at_start: extrapolate_at(vec![10, 13, 16], 0) => 10,
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic(input in "(?s).{0,64}|[-0-9 \n]{0,64}") {
+            check_parsers(&input);
+        }
+    }
+
+    fn report() -> impl Strategy<Value = String> {
+        let sequence = prop::collection::vec(-20..20i64, 1..10);
+        prop::collection::vec(sequence, 1..5).prop_map(|sequences| {
+            sequences
+                .iter()
+                .map(|sequence| {
+                    sequence
+                        .iter()
+                        .map(i64::to_string)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        + "\n"
+                })
+                .collect()
+        })
     }
 
-    #[test]
-    fn test_day9_2() {
-        let res = day9_2(INPUT);
-        assert_eq!(res, Ok(2))
+    differential_tests! {
+        test_day9_1_reference(report()): day9_1 == reference::day9_1,
+        test_day9_2_reference(report()): day9_2 == reference::day9_2,
     }
 }
@@ -0,0 +1,122 @@
+//! Entry points for the `cargo fuzz` targets in `fuzz/`, which can't reach the
+//! crate-private parser checks. Each one panics if the day's parsers misbehave
+
+#[cfg(feature = "day1")]
+pub fn day1(input: &str) {
+    crate::day1::check_parsers(input)
+}
+
+#[cfg(feature = "day2")]
+pub fn day2(input: &str) {
+    crate::day2::check_parsers(input)
+}
+
+#[cfg(feature = "day3")]
+pub fn day3(input: &str) {
+    crate::day3::check_parsers(input)
+}
+
+#[cfg(feature = "day4")]
+pub fn day4(input: &str) {
+    crate::day4::check_parsers(input)
+}
+
+#[cfg(feature = "day5")]
+pub fn day5(input: &str) {
+    crate::day5::check_parsers(input)
+}
+
+#[cfg(feature = "day6")]
+pub fn day6(input: &str) {
+    crate::day6::check_parsers(input)
+}
+
+#[cfg(feature = "day7")]
+pub fn day7(input: &str) {
+    crate::day7::check_parsers(input)
+}
+
+#[cfg(feature = "day8")]
+pub fn day8(input: &str) {
+    crate::day8::check_parsers(input)
+}
+
+#[cfg(feature = "day9")]
+pub fn day9(input: &str) {
+    crate::day9::check_parsers(input)
+}
+
+#[cfg(feature = "day10")]
+pub fn day10(input: &str) {
+    crate::day10::check_parsers(input)
+}
+
+#[cfg(feature = "day11")]
+pub fn day11(input: &str) {
+    crate::day11::check_parsers(input)
+}
+
+#[cfg(feature = "day12")]
+pub fn day12(input: &str) {
+    crate::day12::check_parsers(input)
+}
+
+#[cfg(feature = "day13")]
+pub fn day13(input: &str) {
+    crate::day13::check_parsers(input)
+}
+
+#[cfg(feature = "day14")]
+pub fn day14(input: &str) {
+    crate::day14::check_parsers(input)
+}
+
+#[cfg(feature = "day15")]
+pub fn day15(input: &str) {
+    crate::day15::check_parsers(input)
+}
+
+#[cfg(feature = "day16")]
+pub fn day16(input: &str) {
+    crate::day16::check_parsers(input)
+}
+
+#[cfg(feature = "day17")]
+pub fn day17(input: &str) {
+    crate::day17::check_parsers(input)
+}
+
+#[cfg(feature = "day18")]
+pub fn day18(input: &str) {
+    crate::day18::check_parsers(input)
+}
+
+#[cfg(feature = "day19")]
+pub fn day19(input: &str) {
+    crate::day19::check_parsers(input)
+}
+
+#[cfg(feature = "day20")]
+pub fn day20(input: &str) {
+    crate::day20::check_parsers(input)
+}
+
+#[cfg(feature = "day21")]
+pub fn day21(input: &str) {
+    crate::day21::check_parsers(input)
+}
+
+#[cfg(feature = "day22")]
+pub fn day22(input: &str) {
+    crate::day22::check_parsers(input)
+}
+
+#[cfg(feature = "day23")]
+pub fn day23(input: &str) {
+    crate::day23::check_parsers(input)
+}
+
+#[cfg(feature = "day24")]
+pub fn day24(input: &str) {
+    crate::day24::check_parsers(input)
+}
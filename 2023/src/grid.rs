@@ -1,9 +1,10 @@
-#![allow(dead_code)]
+//! 2D grids, and coordinates and directions on them
 
 use itertools::Itertools;
 use num::Integer;
 use strum::EnumIter;
 
+/// A position on a grid, `y` growing downwards
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Coord {
     pub x: usize,
@@ -88,6 +89,7 @@ impl Coord {
     }
 }
 
+/// One of the four directions to move on a grid
 #[derive(Debug, PartialEq, Eq, Clone, Copy, EnumIter)]
 pub enum Direction {
     Up,
@@ -113,6 +115,7 @@ impl Direction {
     }
 }
 
+/// A rectangle of cells that can be read by coordinate, line or column
 pub trait Grid {
     type Item;
 
@@ -143,19 +146,19 @@ pub trait Grid {
         self.coord_iter()
             .find(|&coord| pred(self.get_coord(coord).unwrap()))
     }
-    fn iter(&self) -> GridIterator<Self>
+    fn iter(&self) -> GridIterator<'_, Self>
     where
         Self: Sized,
     {
         GridIterator::new(self)
     }
-    fn lines(&self) -> Lines<Self>
+    fn lines(&self) -> Lines<'_, Self>
     where
         Self: Sized,
     {
         Lines::new(self)
     }
-    fn columns(&self) -> Columns<Self>
+    fn columns(&self) -> Columns<'_, Self>
     where
         Self: Sized,
     {
@@ -163,6 +166,7 @@ pub trait Grid {
     }
 }
 
+/// A [`Grid`] whose cells can be changed
 pub trait MutGrid: Grid {
     fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut Self::Item>;
     fn get_coord_mut(&mut self, Coord { x, y }: Coord) -> Option<&mut Self::Item> {
@@ -398,6 +402,12 @@ impl<'a, G: Grid> Iterator for Columns<'a, G> {
     }
 }
 
+/// Text that isn't a grid: its lines aren't all as long, or they are empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotAGrid;
+
+/// A grid borrowing the bytes of a puzzle input, one line of text per line
+/// of the grid
 #[derive(Debug, Clone)]
 pub struct AsciiGrid<'a> {
     ascii: &'a [u8],
@@ -406,25 +416,35 @@ pub struct AsciiGrid<'a> {
 }
 
 impl<'a> AsciiGrid<'a> {
+    /// Panics if `ascii` isn't a grid, see [`AsciiGrid::try_from_ascii`]
     pub fn from_ascii(ascii: &'a [u8]) -> AsciiGrid<'a> {
-        let width = ascii.split(|&c| c == b'\n').next().unwrap().len();
+        Self::try_from_ascii(ascii).expect("lines of the same length")
+    }
+    /// Reads the lines of `ascii`, which may end with a newline
+    pub fn try_from_ascii(ascii: &'a [u8]) -> Result<AsciiGrid<'a>, NotAGrid> {
+        let width = ascii
+            .split(|&c| c == b'\n')
+            .next()
+            .map_or(0, |line| line.len());
+        if width == 0 {
+            return Err(NotAGrid);
+        }
         let mut height = 0;
         {
             let mut lines = ascii.split(|&c| c == b'\n').peekable();
             while let Some(line) = lines.next() {
-                if lines.peek().is_some() {
-                    assert!(line.len() == width);
-                    height += 1
-                } else if line.len() == width {
+                if line.len() == width {
                     height += 1
+                } else if lines.peek().is_some() || !line.is_empty() {
+                    return Err(NotAGrid);
                 }
             }
         }
-        Self {
+        Ok(Self {
             ascii,
             width,
             height,
-        }
+        })
     }
     pub fn range(&self, line: usize, x0: usize, x1: usize) -> &[<Self as Grid>::Item] {
         let line_gap = line * (self.width + 1);
@@ -473,6 +493,7 @@ impl<'a> Grid for AsciiGrid<'a> {
     }
 }
 
+/// A grid owning its cells, stored line by line
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct VecGrid<T> {
     pub width: usize,
@@ -1,5 +1,10 @@
+use std::{process::ExitCode, time::Duration};
+
 use adventofrust2023::time_all_days_2023;
 
-fn main() {
-    time_all_days_2023("inputs/");
+/// How long a solver may run before it is given up on
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn main() -> ExitCode {
+    time_all_days_2023("inputs/", TIMEOUT).exit_code()
 }
@@ -0,0 +1,61 @@
+//! The days that can generate inputs for the `scale` command, and the parts
+//! timed on them
+
+// the days with a generator may not all be built
+#![cfg_attr(not(feature = "all"), allow(unused_imports))]
+
+use std::time::Duration;
+
+use harness::{
+    benchmark,
+    scaling::{doubling, Benchmark, Series},
+};
+
+fn benchmark(day: u8) -> Option<Benchmark> {
+    match day {
+        #[cfg(feature = "day4")]
+        4 => Some(benchmark!(day4: "cards", doubling(64, 1 << 20) => day4_1, day4_2)),
+        #[cfg(feature = "day7")]
+        7 => Some(benchmark!(day7: "hands", doubling(16, 2048) => day7_1, day7_2)),
+        #[cfg(feature = "day9")]
+        9 => Some(benchmark!(day9: "histories", doubling(64, 1 << 20) => day9_1, day9_2)),
+        #[cfg(feature = "day11")]
+        11 => Some(benchmark!(day11: "pixels per side", doubling(16, 4096) => day11_1, day11_2)),
+        #[cfg(feature = "day12")]
+        12 => Some(benchmark!(day12: "rows", doubling(64, 1 << 20) => day12_1, day12_2)),
+        #[cfg(feature = "day14")]
+        14 => Some(
+            benchmark!(day14: "tiles per side", doubling(8, 1024) => day14_1, day14_1_rolling, day14_2, day14_2_rolling),
+        ),
+        #[cfg(feature = "day22")]
+        22 => Some(benchmark!(day22: "bricks", doubling(16, 1 << 14) => day22_1, day22_2)),
+        _ => None,
+    }
+}
+
+/// Times each part of `day` on generated inputs of growing size, see
+/// `Benchmark::scale`.
+/// Returns what the sizes count, or `None` if the day has no generator
+pub fn scale_day(day: u8, seed: u64, budget: Duration) -> Option<(&'static str, Vec<Series>)> {
+    let benchmark = benchmark(day)?;
+    Some((benchmark.unit, benchmark.scale(seed, budget)))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::benchmark;
+
+    #[test]
+    fn generated_inputs_are_solved() {
+        for day in 1..=25 {
+            if let Some(benchmark) = benchmark(day) {
+                let input = (benchmark.generate)(&mut StdRng::seed_from_u64(0), benchmark.sizes[0]);
+                for (_, solve) in benchmark.parts {
+                    solve(&input);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! A global allocator counting what goes through it, so the runner can show
+//! what each solver allocates next to its time. Linking this crate is enough
+//! to install it
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt::{self, Display},
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+};
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+/// Bytes currently allocated
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+/// Most bytes allocated at once since the last `measure` started
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+struct Counting;
+
+fn record_allocation(size: usize) {
+    ALLOCATIONS.fetch_add(1, Relaxed);
+    ALLOCATED.fetch_add(size, Relaxed);
+    let live = LIVE.fetch_add(size, Relaxed) + size;
+    PEAK.fetch_max(live, Relaxed);
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            // counted as a new allocation replacing the old one
+            LIVE.fetch_sub(layout.size(), Relaxed);
+            record_allocation(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// What some code allocated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Allocations and reallocations made
+    pub allocations: usize,
+    /// Bytes requested by all of them, freed or not
+    pub bytes: usize,
+    /// Most bytes it had allocated at once, on top of what was already
+    /// allocated when it started
+    pub peak: usize,
+}
+
+/// Runs `f` and counts what it allocates. The counters are global, so whatever
+/// other threads allocate at the same time is counted too
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Stats) {
+    let live = LIVE.load(Relaxed);
+    PEAK.store(live, Relaxed);
+    let allocations = ALLOCATIONS.load(Relaxed);
+    let bytes = ALLOCATED.load(Relaxed);
+    let res = f();
+    let stats = Stats {
+        allocations: ALLOCATIONS.load(Relaxed) - allocations,
+        bytes: ALLOCATED.load(Relaxed) - bytes,
+        peak: PEAK.load(Relaxed).saturating_sub(live),
+    };
+    (res, stats)
+}
+
+/// A size in bytes with a binary unit, like `12.3MiB`
+struct Size(usize);
+
+impl Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+        let mut size = self.0 as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        let size = if unit == 0 {
+            format!("{}{}", self.0, UNITS[0])
+        } else {
+            format!("{size:.1}{}", UNITS[unit])
+        };
+        f.pad(&size)
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>8} allocs {:>9} total {:>9} peak",
+            self.allocations,
+            Size(self.bytes),
+            Size(self.peak)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let ((), stats) = measure(|| {
+            let mut v: Vec<u64> = Vec::with_capacity(1024);
+            v.push(1);
+            drop(v);
+            let _kept = Box::new([0u8; 4096]);
+        });
+        // the test harness may allocate from other threads meanwhile
+        assert!(stats.allocations >= 2);
+        assert!(stats.bytes >= 8 * 1024 + 4096);
+        assert!(stats.peak >= 8 * 1024);
+    }
+
+    #[test]
+    fn test_display() {
+        let stats = Stats {
+            allocations: 3,
+            bytes: 1536,
+            peak: 12 << 20,
+        };
+        assert_eq!(
+            stats.to_string(),
+            "       3 allocs    1.5KiB total   12.0MiB peak"
+        );
+    }
+}
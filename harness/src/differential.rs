@@ -0,0 +1,32 @@
+/// Generates a property test for each solver, checking it gives the same answer
+/// as its naive `reference` implementation on small generated inputs.
+/// References return the bare answer, the solver must return it as `Ok`.
+/// Reference implementations are slow, so only `cases` inputs (64 by default)
+/// are tried per solver
+///
+/// ```ignore
+/// differential_tests! {
+///     cases = 16;
+///     test_day12_1(rows()): day12_1 == reference::day12_1,
+/// }
+/// ```
+#[macro_export]
+macro_rules! differential_tests {
+    (@cases $cases:expr; $($name:ident($strategy:expr): $solver:ident == $reference:path),+ $(,)?) => {
+        proptest::proptest! {
+            #![proptest_config(proptest::prelude::ProptestConfig::with_cases($cases))]
+            $(
+                #[test]
+                fn $name(input in $strategy) {
+                    proptest::prop_assert_eq!($solver(&input), Ok($reference(&input)));
+                }
+            )+
+        }
+    };
+    (cases = $cases:expr; $($tests:tt)+) => {
+        $crate::differential_tests!(@cases $cases; $($tests)+);
+    };
+    ($($tests:tt)+) => {
+        $crate::differential_tests!(@cases 64; $($tests)+);
+    };
+}
@@ -0,0 +1,21 @@
+/// Generates a `#[test]` for each solver call, checking its result on a
+/// puzzle example against the expected answer
+///
+/// ```ignore
+/// example_tests! {
+///     test_day11_1: day11_1(INPUT) => Ok(374),
+///     test_day11_2: sum_galaxy_distances(&AsciiGrid::from_ascii(INPUT.as_bytes()), 10) => 1030,
+/// }
+/// ```
+#[macro_export]
+macro_rules! example_tests {
+    ($($name:ident: $solver:ident($($arg:expr),*) => $expected:expr),+ $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                let res = $solver($($arg),*);
+                assert_eq!(res, $expected);
+            }
+        )+
+    };
+}
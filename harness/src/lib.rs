@@ -0,0 +1,10 @@
+//! What every year crate shares: the runner timing a day's steps on each of
+//! its inputs, the macros generating the example and differential tests and the
+//! parser checks, and the benchmarks behind the `scale` command
+
+mod differential;
+mod examples;
+mod parsers;
+pub mod runner;
+#[cfg(feature = "scaling")]
+pub mod scaling;
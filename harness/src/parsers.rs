@@ -0,0 +1,17 @@
+/// Defines the day's `check_parsers`, which runs each of the given parsers on
+/// arbitrary text and drops what they return. The property tests and the fuzz
+/// targets call it to check that the parsers never panic. Days that can also
+/// check what their parsers accept write `check_parsers` by hand
+///
+/// ```ignore
+/// harness::check_parsers!(Race::parse_races, Race::parse_bad_kerning);
+/// ```
+#[macro_export]
+macro_rules! check_parsers {
+    ($($parser:path),+ $(,)?) => {
+        #[cfg(any(test, fuzzing))]
+        pub(crate) fn check_parsers(input: &str) {
+            $(let _ = $parser(input);)+
+        }
+    };
+}
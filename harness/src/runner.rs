@@ -0,0 +1,669 @@
+//! Runs the steps of each day for `run_day!` on each of its inputs, timing
+//! them and giving up on the ones that take too long
+
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug, Display},
+    fs, io,
+    iter::Sum,
+    ops::Add,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    process::ExitCode,
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Solvers get as much stack as the main thread they used to run on
+const SOLVER_STACK_SIZE: usize = 8 << 20;
+
+/// How long a step has to stop once cancelled, before the steps after it are
+/// skipped
+const CANCEL_GRACE: Duration = Duration::from_millis(100);
+
+/// One of the inputs of a day
+pub struct Input {
+    /// The name of the file in the day's directory of inputs, `None` for `N.txt`
+    pub label: Option<String>,
+    /// Shared with the thread running the steps
+    pub text: Arc<str>,
+    /// The known answers of each part, like `("part1", "24000")`
+    pub answers: Vec<(String, String)>,
+}
+
+impl Input {
+    /// Reads `path`, and its answers from the `.answers` file next to it
+    fn read(path: &Path, label: Option<String>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?.into();
+        let answers = match fs::read_to_string(path.with_extension("answers")) {
+            Ok(answers) => parse_answers(&answers),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            label,
+            text,
+            answers,
+        })
+    }
+
+    /// The name of `step` on this input
+    pub fn step(&self, step: &str) -> String {
+        match &self.label {
+            Some(label) => format!("{step:>5} ({label})"),
+            None => format!("{step:>5}"),
+        }
+    }
+
+    /// Shows the answer of `part`, and the expected one if it is different.
+    /// Returns whether it is wrong: a part that panicked, timed out or was
+    /// skipped is wrong too when its answer is known
+    pub fn check(&self, part: &str, answer: &Shown) -> (String, bool) {
+        let known = self.answers.iter().find(|(known, _)| known == part);
+        match (answer, known) {
+            (Ok(answer), Some((_, expected))) if answer == expected => (answer.clone(), false),
+            (Ok(answer) | Err(answer), Some((_, expected))) => {
+                (format!("{answer} (expected {expected})"), true)
+            }
+            (Ok(answer) | Err(answer), None) => (answer.clone(), false),
+        }
+    }
+}
+
+/// Shows the answer of an alternative implementation of `part`, and the
+/// part's answer if they differ. Returns whether they do
+pub fn versus(part: &str, answer: &Shown, expected: &Shown) -> (String, bool) {
+    match (answer, expected) {
+        (Ok(answer), Ok(expected)) if answer != expected => {
+            (format!("{answer} (differs from {part}: {expected})"), true)
+        }
+        (Ok(answer) | Err(answer), _) => (answer.clone(), false),
+    }
+}
+
+/// How long an alternative implementation of `part` took compared to it, as a
+/// column after its time
+pub fn ratio(part: &str, time: Duration, part_time: Duration) -> String {
+    if part_time.is_zero() {
+        return String::new();
+    }
+    let ratio = time.as_secs_f64() / part_time.as_secs_f64();
+    format!("[{ratio:.2}x {part}] ")
+}
+
+/// What running days took, and how many of their answers were wrong
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Report {
+    pub time: Duration,
+    /// Answers that differ from the known one, or from the part they are an
+    /// alternative to
+    pub failures: usize,
+}
+
+impl Add for Report {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            time: self.time + other.time,
+            failures: self.failures + other.failures,
+        }
+    }
+}
+
+impl Sum for Report {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl Report {
+    /// Fails when some answers were wrong, saying how many
+    pub fn exit_code(&self) -> ExitCode {
+        if self.failures == 0 {
+            return ExitCode::SUCCESS;
+        }
+        eprintln!("wrong answers: {}", self.failures);
+        ExitCode::FAILURE
+    }
+}
+
+/// Reads `part1: <answer>` lines. Lines without a part continue the previous
+/// answer, for the answers drawn over several lines
+fn parse_answers(text: &str) -> Vec<(String, String)> {
+    let mut answers: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        match line.split_once(':') {
+            Some((part, answer)) if part.starts_with("part") => {
+                let answer = answer.strip_prefix(' ').unwrap_or(answer);
+                answers.push((part.to_owned(), answer.to_owned()));
+            }
+            _ => {
+                if let Some((_, answer)) = answers.last_mut() {
+                    answer.push('\n');
+                    answer.push_str(line);
+                }
+            }
+        }
+    }
+    answers
+}
+
+/// The inputs of a day, given the path of its `N.txt`: that file, and every
+/// `.txt` file in the `N/` directory next to it
+pub fn inputs(path: &str) -> io::Result<Vec<Input>> {
+    let path = Path::new(path);
+    let mut inputs = Vec::new();
+    if path.exists() {
+        inputs.push(Input::read(path, None)?);
+    }
+    let dir = path.with_extension("");
+    if dir.is_dir() {
+        let mut files: Vec<_> = fs::read_dir(&dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<_>>()?;
+        files.retain(|file| file.extension().is_some_and(|ext| ext == "txt"));
+        files.sort();
+        for file in files {
+            let label = file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned());
+            inputs.push(Input::read(&file, label)?);
+        }
+    }
+    if inputs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no {} nor {}", path.display(), dir.display()),
+        ));
+    }
+    Ok(inputs)
+}
+
+/// What a step took to run
+pub struct Cost {
+    pub time: Duration,
+    /// What it allocated, unknown if it timed out or panicked
+    #[cfg(feature = "alloc-stats")]
+    pub allocations: Option<alloc_stats::Stats>,
+}
+
+impl Cost {
+    fn measure<T>(step: impl FnOnce() -> T) -> (T, Self) {
+        let start = Instant::now();
+        #[cfg(feature = "alloc-stats")]
+        let (res, allocations) = alloc_stats::measure(step);
+        #[cfg(not(feature = "alloc-stats"))]
+        let res = step();
+        let cost = Self {
+            time: start.elapsed(),
+            #[cfg(feature = "alloc-stats")]
+            allocations: Some(allocations),
+        };
+        (res, cost)
+    }
+
+    fn waited(time: Duration) -> Self {
+        Self {
+            time,
+            #[cfg(feature = "alloc-stats")]
+            allocations: None,
+        }
+    }
+}
+
+impl Display for Cost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{:>6}us]", self.time.as_micros())?;
+        #[cfg(feature = "alloc-stats")]
+        match self.allocations {
+            Some(allocations) => write!(f, " [{allocations}]")?,
+            None => write!(f, " [{:44}]", "")?,
+        }
+        Ok(())
+    }
+}
+
+/// What a step shows: its answer, nothing for the parse step, or why it has
+/// none
+pub type Shown = Result<String, String>;
+
+thread_local! {
+    /// On a thread running `Steps`: which steps the runner gave up on, and
+    /// the step running
+    static RUNNING: RefCell<Option<(Arc<AtomicUsize>, usize)>> = const { RefCell::new(None) };
+}
+
+/// Whether the runner gave up on the step running on this thread. The long
+/// searches check it to stop early, what they return then is dropped. Always
+/// `false` outside the runner
+pub fn cancelled() -> bool {
+    RUNNING.with_borrow(|running| {
+        running
+            .as_ref()
+            .is_some_and(|(given_up, step)| *step < given_up.load(Relaxed))
+    })
+}
+
+/// Runs the steps of a day on one input, one after the other on a thread of
+/// their own so that the runner can give up on them, see `Steps::spawn`
+pub struct Steps {
+    timeout: Duration,
+    receiver: Receiver<(Shown, Cost)>,
+    /// The steps before this one were given up on
+    given_up: Arc<AtomicUsize>,
+    /// The step `wait` waits for next
+    next: usize,
+    /// Set once a step didn't stop after it timed out, the thread is still
+    /// busy with it
+    stuck: bool,
+}
+
+/// The end of `Steps` on the thread running them
+pub struct StepSender(Sender<(Shown, Cost)>);
+
+impl StepSender {
+    /// Runs and measures `step`, and tells the runner what `show` shows of
+    /// it. Returns what it returned, `None` inside if it panicked, or `None`
+    /// if the runner gave up on the steps
+    fn run<T>(
+        &self,
+        step: impl FnOnce() -> T,
+        show: impl FnOnce(&T) -> Shown,
+    ) -> Option<Option<T>> {
+        let (res, cost) = Cost::measure(|| panic::catch_unwind(AssertUnwindSafe(step)));
+        RUNNING.with_borrow_mut(|running| {
+            if let Some((_, step)) = running {
+                *step += 1;
+            }
+        });
+        let shown = match &res {
+            Ok(res) => show(res),
+            Err(_) => Err("panicked".to_owned()),
+        };
+        self.0.send((shown, cost)).ok()?;
+        Some(res.ok())
+    }
+
+    /// Runs the parse step, returning what it parsed. `None` if it failed,
+    /// for the runner to stop there
+    pub fn parse<T, E: Debug>(&self, parse: impl FnOnce() -> Result<T, E>) -> Option<T> {
+        let show = |parsed: &Result<T, E>| match parsed {
+            Ok(_) => Ok(String::new()),
+            Err(e) => Err(format!("{e:?}")),
+        };
+        self.run(parse, show)?.and_then(Result::ok)
+    }
+
+    /// Runs a part, even a panicking one lets the next ones run. `None` if
+    /// the runner gave up on the steps
+    pub fn part<T: Answer>(&self, part: impl FnOnce() -> T) -> Option<()> {
+        self.run(part, |answer| Ok(answer.display())).map(drop)
+    }
+}
+
+impl Steps {
+    /// Runs `steps` on a thread of its own. It runs each step through the
+    /// `StepSender` it is given, in the order the runner then `wait`s for
+    /// them, and owns the input and what is parsed from it
+    pub fn spawn(
+        timeout: Duration,
+        steps: impl FnOnce(StepSender) -> Option<()> + Send + 'static,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let given_up = Arc::new(AtomicUsize::new(0));
+        let running = Arc::clone(&given_up);
+        thread::Builder::new()
+            .stack_size(SOLVER_STACK_SIZE)
+            .spawn(move || {
+                RUNNING.set(Some((running, 0)));
+                steps(StepSender(sender))
+            })
+            .expect("failed to spawn a solver thread");
+        Self {
+            timeout,
+            receiver,
+            given_up,
+            next: 0,
+            stuck: false,
+        }
+    }
+
+    /// Waits at most the timeout for the next step. Returns what it showed and
+    /// cost, or why it didn't finish and the time waited for it.
+    /// A step that times out is cancelled, see `cancelled`. If it doesn't stop
+    /// within `CANCEL_GRACE`, the next steps are skipped: its thread keeps
+    /// running until the program exits
+    pub fn wait(&mut self) -> (Shown, Cost) {
+        if self.stuck {
+            return (
+                Err("skipped after a timeout".to_owned()),
+                Cost::waited(Duration::ZERO),
+            );
+        }
+        let start = Instant::now();
+        let res = match self.receiver.recv_timeout(self.timeout) {
+            Ok(done) => done,
+            Err(RecvTimeoutError::Timeout) => {
+                let waited = Cost::waited(start.elapsed());
+                self.given_up.store(self.next + 1, Relaxed);
+                // what it returns once cancelled is not its answer
+                self.stuck = self.receiver.recv_timeout(CANCEL_GRACE).is_err();
+                (
+                    Err(format!("timed out after {}s", self.timeout.as_secs_f64())),
+                    waited,
+                )
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                (Err("panicked".to_owned()), Cost::waited(start.elapsed()))
+            }
+        };
+        self.next += 1;
+        res
+    }
+}
+
+impl Drop for Steps {
+    /// Cancels the step still running, if any
+    fn drop(&mut self) {
+        self.given_up.store(usize::MAX, Relaxed);
+    }
+}
+
+/// A part's answer as the runner prints it. Most parts can't fail once the
+/// input is parsed, the others return a `Result`
+pub trait Answer {
+    fn display(&self) -> String;
+}
+
+impl<T: Display, E: Debug> Answer for Result<T, E> {
+    fn display(&self) -> String {
+        match self {
+            Ok(t) => t.to_string(),
+            Err(e) => format!("{e:?}"),
+        }
+    }
+}
+
+macro_rules! plain_answer {
+    ($($t:ty),+) => {
+        $(impl Answer for $t {
+            fn display(&self) -> String {
+                self.to_string()
+            }
+        })+
+    };
+}
+
+plain_answer!(i32, i64, u32, u64, usize, String);
+
+/// Runs and times a day's parts on each of its inputs, printing each step
+/// under `$year`. A part can have alternative implementations after it, like
+/// `part2 | part2_naive`, which run on the same parsed input and are compared
+/// to it. Returns a `Report` counting the answers that differ from the known
+/// ones, or from their part's. When the input doesn't parse, each known answer
+/// counts as wrong
+#[macro_export]
+macro_rules! run_day {
+    ($year:literal, $timeout:expr, $($file:expr),+ => $day:ident: $($part:ident $(| $alternative:ident)*),+) => {{
+        let mut path = String::new();
+        $({
+            path.push_str($file);
+        })*
+        let mut report = $crate::runner::Report::default();
+        match $crate::runner::inputs(&path) {
+            Err(e) => println!("{:>10} {} {:>5} {:>5}: {e}", "", $year, stringify!($day), "input"),
+            Ok(inputs) => for input in inputs {
+                let text = std::sync::Arc::clone(&input.text);
+                let mut steps = $crate::runner::Steps::spawn($timeout, move |steps| {
+                    let parsed = steps.parse(|| $day::parse(&text))?;
+                    $(
+                        steps.part(|| $day::$part(&parsed))?;
+                        $(steps.part(|| $day::$alternative(&parsed))?;)*
+                    )+
+                    Some(())
+                });
+                let (parsed, cost) = steps.wait();
+                report.time += cost.time;
+                match parsed {
+                    Err(e) => {
+                        println!(
+                            "{cost} {} {:>5} {}: {e}",
+                            $year,
+                            stringify!($day),
+                            input.step("parse")
+                        );
+                        report.failures += input.answers.len();
+                    }
+                    Ok(_) => {
+                        println!(
+                            "{cost} {} {:>5} {}",
+                            $year,
+                            stringify!($day),
+                            input.step("parse")
+                        );
+                        $({
+                            let (answer, cost) = steps.wait();
+                            let (shown, wrong) = input.check(stringify!($part), &answer);
+                            println!(
+                                "{cost} {} {:>5} {}: {shown}",
+                                $year,
+                                stringify!($day),
+                                input.step(stringify!($part)),
+                            );
+                            report.time += cost.time;
+                            report.failures += usize::from(wrong);
+                            $({
+                                let (other, other_cost) = steps.wait();
+                                let (shown, differs) = $crate::runner::versus(stringify!($part), &other, &answer);
+                                println!(
+                                    "{other_cost} {}{} {:>5} {}: {shown}",
+                                    $crate::runner::ratio(stringify!($part), other_cost.time, cost.time),
+                                    $year,
+                                    stringify!($day),
+                                    input.step(stringify!($alternative)),
+                                );
+                                report.failures += usize::from(differs);
+                            })*
+                        })+
+                    }
+                }
+            },
+        }
+        report
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cancelled, inputs, parse_answers, ratio, versus, Steps};
+    use std::{fs, time::Duration};
+
+    #[test]
+    fn test_inputs() {
+        let dir = std::env::temp_dir().join(format!("inputs-{}", std::process::id()));
+        fs::create_dir_all(dir.join("5")).unwrap();
+        fs::write(dir.join("5.txt"), "mine").unwrap();
+        fs::write(dir.join("5/bob.txt"), "bob's").unwrap();
+        fs::write(dir.join("5/alice.txt"), "alice's").unwrap();
+        fs::write(dir.join("5/alice.answers"), "part1: 35\npart2: 46\n").unwrap();
+
+        let inputs = inputs(dir.join("5.txt").to_str().unwrap()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let labels: Vec<_> = inputs.iter().map(|input| input.label.as_deref()).collect();
+        assert_eq!(labels, [None, Some("alice"), Some("bob")]);
+        assert_eq!(&*inputs[1].text, "alice's");
+        assert_eq!(inputs[1].step("part1"), "part1 (alice)");
+        assert_eq!(
+            inputs[1].check("part1", &Ok("35".to_owned())),
+            ("35".to_owned(), false)
+        );
+        assert_eq!(
+            inputs[1].check("part2", &Ok("45".to_owned())),
+            ("45 (expected 46)".to_owned(), true)
+        );
+        assert_eq!(
+            inputs[1].check("part2", &Err("panicked".to_owned())),
+            ("panicked (expected 46)".to_owned(), true)
+        );
+        assert_eq!(
+            inputs[2].check("part2", &Ok("45".to_owned())),
+            ("45".to_owned(), false)
+        );
+        assert_eq!(
+            inputs[2].check("part2", &Err("panicked".to_owned())),
+            ("panicked".to_owned(), false)
+        );
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        assert_eq!(
+            parse_answers("part1: 13140\npart2:\n##..\n#..#\n"),
+            [
+                ("part1".to_owned(), "13140".to_owned()),
+                ("part2".to_owned(), "\n##..\n#..#".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_versus() {
+        let answer = Ok("64".to_owned());
+        assert_eq!(
+            versus("part2", &Ok("64".to_owned()), &answer),
+            ("64".to_owned(), false)
+        );
+        assert_eq!(
+            versus("part2", &Ok("63".to_owned()), &answer),
+            ("63 (differs from part2: 64)".to_owned(), true)
+        );
+        assert_eq!(
+            versus("part2", &Err("panicked".to_owned()), &answer),
+            ("panicked".to_owned(), false)
+        );
+
+        let (fast, slow) = (Duration::from_micros(10), Duration::from_micros(25));
+        assert_eq!(ratio("part2", slow, fast), "[2.50x part2] ");
+        assert_eq!(ratio("part2", slow, Duration::ZERO), "");
+    }
+
+    /// A day whose alternatives don't all agree
+    mod disagreeing {
+        use std::num::ParseIntError;
+
+        pub fn parse(input: &str) -> Result<u32, ParseIntError> {
+            input.trim().parse()
+        }
+
+        pub fn part1(n: &u32) -> u32 {
+            n * 2
+        }
+
+        pub fn part1_shifted(n: &u32) -> u32 {
+            n << 1
+        }
+
+        pub fn part1_off_by_one(n: &u32) -> u32 {
+            n * 2 + 1
+        }
+    }
+
+    #[test]
+    fn test_run_day_fails_on_disagreement() {
+        let dir = std::env::temp_dir().join(format!("disagreeing-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("1.txt"), "21").unwrap();
+        let path = dir.join("1.txt");
+        let path = path.to_str().unwrap();
+        let timeout = Duration::from_secs(10);
+
+        let report = crate::run_day!(2000, timeout, path => disagreeing: part1 | part1_shifted);
+        assert_eq!(report.failures, 0);
+        let report = crate::run_day!(2000, timeout, path => disagreeing: part1 | part1_shifted | part1_off_by_one);
+        assert_eq!(report.failures, 1);
+
+        fs::write(dir.join("1.answers"), "part1: 43\n").unwrap();
+        let report = crate::run_day!(2000, timeout, path => disagreeing: part1 | part1_off_by_one);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(report.failures, 2);
+    }
+
+    #[test]
+    fn test_run_day_fails_without_known_answers() {
+        let dir = std::env::temp_dir().join(format!("unparsed-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("1.txt"), "twenty-one").unwrap();
+        let path = dir.join("1.txt");
+        let path = path.to_str().unwrap();
+        let timeout = Duration::from_secs(10);
+
+        let report = crate::run_day!(2000, timeout, path => disagreeing: part1);
+        assert_eq!(report.failures, 0);
+        fs::write(dir.join("1.answers"), "part1: 42\n").unwrap();
+        let report = crate::run_day!(2000, timeout, path => disagreeing: part1);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(report.failures, 1);
+    }
+
+    #[test]
+    fn test_steps() {
+        let mut steps = Steps::spawn(Duration::from_secs(10), |steps| {
+            let parsed = steps.parse(|| "6".parse::<u32>())?;
+            steps.part(|| parsed * 7)?;
+            steps.part(|| -> u32 { panic!("bug") })?;
+            steps.part(|| parsed)?;
+            Some(())
+        });
+        assert_eq!(steps.wait().0, Ok(String::new()));
+        assert_eq!(steps.wait().0, Ok("42".to_owned()));
+        assert_eq!(steps.wait().0, Err("panicked".to_owned()));
+        assert_eq!(steps.wait().0, Ok("6".to_owned()));
+
+        let mut steps = Steps::spawn(Duration::from_secs(10), |steps| {
+            steps.parse(|| "six".parse::<u32>())?;
+            steps.part(|| 42)?;
+            Some(())
+        });
+        assert!(steps
+            .wait()
+            .0
+            .is_err_and(|e| e.starts_with("ParseIntError")));
+    }
+
+    #[test]
+    fn test_steps_give_up() {
+        let mut steps = Steps::spawn(Duration::from_millis(10), |steps| {
+            steps.part(|| -> u32 {
+                loop {
+                    std::thread::park();
+                }
+            })?;
+            steps.part(|| 42)?;
+            Some(())
+        });
+        let (answer, cost) = steps.wait();
+        assert_eq!(answer, Err("timed out after 0.01s".to_owned()));
+        assert!(cost.time >= Duration::from_millis(10));
+        assert_eq!(steps.wait().0, Err("skipped after a timeout".to_owned()));
+
+        let mut steps = Steps::spawn(Duration::from_millis(10), |steps| {
+            steps.part(|| {
+                while !cancelled() {
+                    std::thread::yield_now();
+                }
+                0
+            })?;
+            steps.part(|| 42)?;
+            Some(())
+        });
+        assert_eq!(steps.wait().0, Err("timed out after 0.01s".to_owned()));
+        assert_eq!(steps.wait().0, Ok("42".to_owned()));
+        assert!(!cancelled());
+    }
+}
@@ -0,0 +1,82 @@
+//! Generated inputs of growing size for the `scale` command, to see how the
+//! solvers behave past the size of our own inputs
+
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+/// A part's name and solver
+pub type Part = (&'static str, fn(&str));
+
+/// A day's generator, and the parts to time on what it generates
+pub struct Benchmark {
+    /// What the size of a generated input counts
+    pub unit: &'static str,
+    pub sizes: Vec<usize>,
+    pub generate: fn(&mut StdRng, usize) -> String,
+    pub parts: Vec<Part>,
+}
+
+/// The `Benchmark` of a day's `generate` function and `day<day>_<part>`
+/// solvers
+#[macro_export]
+// the days are in the year crate calling it
+#[allow(clippy::crate_in_macro_def)]
+macro_rules! benchmark {
+    ($day:ident: $unit:expr, $sizes:expr => $($part:ident),+) => {
+        $crate::scaling::Benchmark {
+            unit: $unit,
+            sizes: $sizes.into_iter().collect(),
+            generate: |rng, size| crate::$day::generate(rng, size),
+            parts: vec![$((
+                stringify!($part),
+                (|input| {
+                    std::hint::black_box(crate::$day::$part(input).unwrap());
+                }) as fn(&str),
+            )),+],
+        }
+    };
+}
+
+pub fn doubling(from: usize, to: usize) -> impl Iterator<Item = usize> {
+    std::iter::successors(Some(from), |size| Some(size * 2)).take_while(move |&size| size <= to)
+}
+
+/// The time a part took on each generated input size
+pub struct Series {
+    pub part: &'static str,
+    pub times: Vec<(usize, Duration)>,
+}
+
+impl Benchmark {
+    /// Times each part on generated inputs of growing size, the same `seed`
+    /// giving the same inputs. A part is not run on larger inputs once it
+    /// took longer than `budget`
+    pub fn scale(&self, seed: u64, budget: Duration) -> Vec<Series> {
+        let mut series: Vec<Series> = self
+            .parts
+            .iter()
+            .map(|&(part, _)| Series {
+                part,
+                times: vec![],
+            })
+            .collect();
+        for &size in &self.sizes {
+            let input = (self.generate)(&mut StdRng::seed_from_u64(seed), size);
+            let mut ran = false;
+            for (&(_, solve), series) in self.parts.iter().zip(&mut series) {
+                if series.times.last().is_some_and(|&(_, time)| time > budget) {
+                    continue;
+                }
+                let start = Instant::now();
+                solve(&input);
+                series.times.push((size, start.elapsed()));
+                ran = true;
+            }
+            if !ran {
+                break;
+            }
+        }
+        series
+    }
+}
@@ -1,17 +1,184 @@
 // extern crate adventofrust2022;
 
-use std::time::Duration;
+mod scaffold;
+#[cfg(feature = "scaling")]
+mod scale;
+mod submit;
+mod watch;
 
-use adventofrust2022::time_all_days_2022;
-use adventofrust2023::time_all_days_2023;
+use std::{path::Path, process::ExitCode, time::Duration};
 
-fn main() {
-    let total: Duration = [
-        time_all_days_2022("2022/inputs/"),
-        time_all_days_2023("2023/inputs/"),
-    ]
-    .into_iter()
-    .sum();
+use harness::runner::Report;
+use submit::{History, HttpClient, Puzzle, SubmitError};
 
-    println!("[{:>6}ms] final total", total.as_millis());
+const HISTORY_FILE: &str = "submissions.tsv";
+
+/// How long a solver may run before it is given up on, unless `--timeout` says
+/// otherwise
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const USAGE: &str = "usage:
+    adventofrust [--timeout <seconds>]                time every solution
+    adventofrust submit <year> <day> <part> <answer>  submit an answer
+    adventofrust new <year> <day>                     create a day, and its year crate if needed
+    adventofrust scale <year> <day> [seed]            time a day on generated inputs of growing size
+    adventofrust watch <year> <day>                   re-run a day and its tests when its source or input changes";
+
+/// A year crate built into the runner
+// there is none when no year is built
+#[cfg_attr(
+    not(any(feature = "adventofrust2022", feature = "adventofrust2023")),
+    allow(dead_code)
+)]
+struct Year {
+    year: u16,
+    time_all_days: fn(&str, Duration) -> Report,
+    /// Its `scaling::scale_day`, for the years that generate inputs
+    #[cfg(feature = "scaling")]
+    scale_day: Option<scale::ScaleDay>,
+}
+
+/// The [`Year`] of a year crate, with `scaling` if it has a `scaling` module
+#[cfg_attr(
+    not(any(feature = "adventofrust2022", feature = "adventofrust2023")),
+    allow(unused_macros)
+)]
+macro_rules! year {
+    ($year:literal, $krate:ident::$time_all_days:ident) => {
+        Year {
+            year: $year,
+            time_all_days: $krate::$time_all_days,
+            #[cfg(feature = "scaling")]
+            scale_day: None,
+        }
+    };
+    ($year:literal, $krate:ident::$time_all_days:ident, scaling) => {
+        Year {
+            year: $year,
+            time_all_days: $krate::$time_all_days,
+            #[cfg(feature = "scaling")]
+            scale_day: Some($krate::scaling::scale_day),
+        }
+    };
+}
+
+/// Every year the runner was built with, each behind the feature of its crate.
+/// `cargo run -- new` adds new years here
+const YEARS: &[Year] = &[
+    #[cfg(feature = "adventofrust2022")]
+    year!(2022, adventofrust2022::time_all_days_2022, scaling),
+    #[cfg(feature = "adventofrust2023")]
+    year!(2023, adventofrust2023::time_all_days_2023, scaling),
+];
+
+fn time_all(timeout: Duration) -> Report {
+    let total: Report = YEARS
+        .iter()
+        .map(|year| (year.time_all_days)(&format!("{}/inputs/", year.year), timeout))
+        .sum();
+
+    println!("[{:>6}ms] final total", total.time.as_millis());
+    total
+}
+
+fn time(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let timeout = match args {
+        [] => DEFAULT_TIMEOUT,
+        [_flag, seconds] => Duration::try_from_secs_f64(seconds.parse()?)?,
+        _ => return Err(USAGE.into()),
+    };
+    let report = time_all(timeout);
+    if report.failures > 0 {
+        return Err(format!("wrong answers: {}", report.failures).into());
+    }
+    Ok(())
+}
+
+fn submit(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let [year, day, part, answer] = args else {
+        return Err(USAGE.into());
+    };
+    // " 42" and "42" are the same answer to the website and in the history
+    let answer = answer.trim();
+    let puzzle = Puzzle {
+        year: year.parse()?,
+        day: day.parse()?,
+        part: part.parse()?,
+    };
+    let mut history = History::open(HISTORY_FILE)?;
+    let now = submit::unix_now();
+    let result = history
+        .check(puzzle, answer, now)
+        .and_then(|()| HttpClient::from_env())
+        .and_then(|client| submit::submit(&client, &mut history, puzzle, answer, now));
+    match result {
+        Ok(response) => {
+            println!("{puzzle}: {answer} is {:?}", response.verdict);
+            if !response.wait.is_zero() {
+                println!("wait {}s before the next answer", response.wait.as_secs());
+            }
+            Ok(())
+        }
+        Err(e @ SubmitError::AlreadySolved(_)) => {
+            println!("{puzzle}: {e}");
+            Ok(())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn new(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let [year, day] = args else {
+        return Err(USAGE.into());
+    };
+    scaffold::new_day(Path::new("."), year.parse()?, day.parse()?)?;
+    Ok(())
+}
+
+#[cfg(feature = "scaling")]
+fn scale(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (year, day, seed) = match args {
+        [year, day] => (year, day, 0),
+        [year, day, seed] => (year, day, seed.parse()?),
+        _ => return Err(USAGE.into()),
+    };
+    let (year, day) = (year.parse()?, day.parse()?);
+    let (unit, series) =
+        scale::scale_day(year, day, seed).ok_or(format!("{year} day {day} has no generator"))?;
+    for series in &series {
+        println!("{}", scale::plot(unit, series));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "scaling"))]
+fn scale(_args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    Err("built without the `scaling` feature".into())
+}
+
+fn watch(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let [year, day] = args else {
+        return Err(USAGE.into());
+    };
+    watch::watch(Path::new("."), year.parse()?, day.parse()?)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.split_first() {
+        None => time(&args),
+        Some((flag, _)) if flag == "--timeout" => time(&args),
+        Some((command, rest)) if command == "submit" => submit(rest),
+        Some((command, rest)) if command == "new" => new(rest),
+        Some((command, rest)) if command == "scale" => scale(rest),
+        Some((command, rest)) if command == "watch" => watch(rest),
+        Some(_) => Err(USAGE.into()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
 }
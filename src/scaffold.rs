@@ -0,0 +1,416 @@
+use std::{
+    fs,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+const DAY_TEMPLATE: &str = include_str!("scaffold/day.rs.in");
+const LIB_TEMPLATE: &str = include_str!("scaffold/lib.rs.in");
+const MAIN_TEMPLATE: &str = include_str!("scaffold/main.rs.in");
+const CARGO_TEMPLATE: &str = include_str!("scaffold/Cargo.toml.in");
+
+/// Helper modules every year starts with, copied from the latest year
+const SHARED_MODULES: [&str; 2] = ["grid.rs", "parse.rs"];
+
+fn fill_template(template: &str, year: u16, day: u8) -> String {
+    template
+        .replace("__YEAR__", &year.to_string())
+        .replace("__DAY__", &day.to_string())
+}
+
+fn read(path: impl AsRef<Path>) -> std::io::Result<String> {
+    let path = path.as_ref();
+    fs::read_to_string(path).map_err(|e| Error::new(e.kind(), format!("{}: {e}", path.display())))
+}
+
+/// Position of `key` among the lines matched by `key_of`, which are assumed sorted.
+/// Returns `Ok` with the line index if `key` is already there, or `Err` with
+/// the index where it should be inserted
+fn find_sorted_line<K: Ord>(
+    lines: &[String],
+    key: &K,
+    key_of: impl Fn(&str) -> Option<K>,
+) -> Result<usize, usize> {
+    let mut insert_at = None;
+    for (i, line) in lines.iter().enumerate() {
+        let Some(line_key) = key_of(line) else {
+            continue;
+        };
+        match line_key.cmp(key) {
+            std::cmp::Ordering::Equal => return Ok(i),
+            std::cmp::Ordering::Less => insert_at = Some(i + 1),
+            std::cmp::Ordering::Greater => {
+                return Err(insert_at.unwrap_or(i));
+            }
+        }
+    }
+    Err(insert_at.unwrap_or(lines.len()))
+}
+
+fn mod_key(line: &str) -> Option<String> {
+    let line = line.trim_start_matches("// ").trim_start_matches("pub ");
+    let name = line.strip_prefix("mod ")?.strip_suffix(';')?;
+    name.starts_with("day").then(|| name.to_owned())
+}
+
+fn run_day_key(line: &str) -> Option<u8> {
+    let line = line.trim_start().trim_start_matches("// ");
+    let (_, day) = line.strip_prefix("run_day!(")?.split_once("=> day")?;
+    let (day, _parts) = day.split_once(':')?;
+    day.parse().ok()
+}
+
+fn day_feature_key(line: &str) -> Option<u8> {
+    let (day, _deps) = line.strip_prefix("day")?.split_once(" = [")?;
+    day.parse().ok()
+}
+
+/// Puts `line` where `find_sorted_line` found it, in place of a commented out
+/// version, behind a `gate` attribute indented like it
+fn insert_gated(lines: &mut Vec<String>, found: Result<usize, usize>, gate: &str, line: String) {
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let gate = format!("{indent}{gate}");
+    match found {
+        Ok(i) => {
+            lines[i] = line;
+            if i == 0 || lines[i - 1] != gate {
+                lines.insert(i, gate);
+            }
+        }
+        Err(mut i) => {
+            // keep the next line's own gate with it
+            if i > 0 && lines[i - 1].trim_start().starts_with("#[cfg(") {
+                i -= 1;
+            }
+            lines.insert(i, line);
+            lines.insert(i, gate);
+        }
+    }
+}
+
+/// Adds `item` to the `name = [...]` list on its own line, keeping it sorted by `key`
+fn add_to_list<K: Ord>(line: &mut String, name: &str, item: &str, key: impl Fn(&str) -> K) {
+    let Some(list) = line
+        .strip_prefix(&format!("{name} = ["))
+        .and_then(|rest| rest.strip_suffix(']'))
+    else {
+        return;
+    };
+    let mut items: Vec<String> = list
+        .split(',')
+        .map(|item| item.trim().to_owned())
+        .filter(|item| !item.is_empty())
+        .collect();
+    let item = format!("\"{item}\"");
+    if !items.contains(&item) {
+        items.push(item);
+        items.sort_by_key(|item| key(item));
+    }
+    *line = format!("{name} = [{}]", items.join(", "));
+}
+
+/// Declares the day module and adds its parts to the runner in a year's
+/// `lib.rs`, both only built with the day's feature
+pub fn register_day(lib: &str, day: u8) -> String {
+    let mut lines: Vec<String> = lib.lines().map(str::to_owned).collect();
+
+    let module = format!("day{day}");
+    let gate = format!("#[cfg(feature = \"{module}\")]");
+    let found = find_sorted_line(&lines, &module, mod_key);
+    insert_gated(&mut lines, found, &gate, format!("pub mod {module};"));
+
+    let parts = if day == 25 { "part1" } else { "part1, part2" };
+    let found = find_sorted_line(&lines, &day, run_day_key);
+    insert_gated(
+        &mut lines,
+        found,
+        &gate,
+        format!("        run_day!(timeout, input_folder, \"/{day}.txt\" => {module}: {parts}),"),
+    );
+
+    let mut res = lines.join("\n");
+    res.push('\n');
+    res
+}
+
+/// Adds the day's feature to a year's `Cargo.toml`, and to the `all` feature
+pub fn register_day_features(manifest: &str, day: u8) -> String {
+    let mut lines: Vec<String> = manifest.lines().map(str::to_owned).collect();
+    let feature = format!("day{day}");
+    if let Err(i) = find_sorted_line(&lines, &day, day_feature_key) {
+        let i = match i {
+            // the first day goes right after `all`
+            i if i == lines.len() => lines
+                .iter()
+                .position(|line| line.starts_with("all = ["))
+                .map_or(i, |all| all + 1),
+            i => i,
+        };
+        lines.insert(i, format!("{feature} = []"));
+    }
+    for line in &mut lines {
+        add_to_list(line, "all", &feature, |item| {
+            item.trim_matches('"')
+                .trim_start_matches("day")
+                .parse::<u8>()
+                .ok()
+        });
+    }
+    let mut res = lines.join("\n");
+    res.push('\n');
+    res
+}
+
+/// Adds the year crate to the root `Cargo.toml` dependencies, features and
+/// workspace members
+pub fn register_year_manifest(manifest: &str, year: u16) -> String {
+    let dependency = format!(
+        "adventofrust{year} = {{ path = \"{year}\", default-features = false, optional = true }}"
+    );
+    let feature = format!("{year} = [\"adventofrust{year}/all\"]");
+    let mut lines: Vec<String> = manifest.lines().map(str::to_owned).collect();
+    if !lines.contains(&dependency) {
+        let last = lines
+            .iter()
+            .rposition(|line| line.starts_with("adventofrust"))
+            .map_or(lines.len(), |i| i + 1);
+        lines.insert(last, dependency);
+    }
+    if !lines.contains(&feature) {
+        if let Some(last) = lines.iter().rposition(|line| line.ends_with("/all\"]")) {
+            lines.insert(last + 1, feature);
+        }
+    }
+    for line in &mut lines {
+        add_to_list(line, "default", &year.to_string(), str::to_owned);
+        add_to_list(
+            line,
+            "alloc-stats",
+            &format!("adventofrust{year}?/alloc-stats"),
+            str::to_owned,
+        );
+    }
+    for line in &mut lines {
+        let Some(members) = line
+            .strip_prefix("members = [")
+            .and_then(|rest| rest.strip_suffix(']'))
+        else {
+            continue;
+        };
+        let mut members: Vec<String> = members
+            .split(',')
+            .map(|m| m.trim().to_owned())
+            .filter(|m| !m.is_empty())
+            .collect();
+        let member = format!("\"{year}\"");
+        if !members.contains(&member) {
+            members.push(member);
+            members.sort();
+        }
+        *line = format!("members = [ {} ]", members.join(", "));
+    }
+    let mut res = lines.join("\n");
+    res.push('\n');
+    res
+}
+
+/// Makes the root runner time the new year too, when it is built
+pub fn register_year_main(main: &str, year: u16) -> String {
+    let gate = format!("    #[cfg(feature = \"adventofrust{year}\")]");
+    let entry = format!("    year!({year}, adventofrust{year}::time_all_days_{year}");
+    let mut lines: Vec<String> = main.lines().map(str::to_owned).collect();
+    if !lines.iter().any(|line| line.starts_with(&entry)) {
+        if let Some(last) = lines
+            .iter()
+            .rposition(|line| line.trim_start().starts_with("year!("))
+        {
+            // a new year has no generators for the `scale` command yet
+            lines.insert(last + 1, entry + "),");
+            lines.insert(last + 1, gate);
+        }
+    }
+    let mut res = lines.join("\n");
+    res.push('\n');
+    res
+}
+
+/// Creates the `YYYY` crate and adds it to the workspace and root runner
+fn new_year(root: &Path, year: u16) -> std::io::Result<()> {
+    let latest_year = fs::read_dir(root)?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u16>().ok())
+        .filter(|&y| y < year)
+        .max()
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "no year to copy helpers from"))?;
+
+    let crate_dir = root.join(year.to_string());
+    fs::create_dir_all(crate_dir.join("src"))?;
+    fs::create_dir_all(crate_dir.join("inputs"))?;
+    fs::write(crate_dir.join(".gitignore"), "/target\n")?;
+    fs::write(
+        crate_dir.join("Cargo.toml"),
+        fill_template(CARGO_TEMPLATE, year, 0),
+    )?;
+    fs::write(
+        crate_dir.join("src/lib.rs"),
+        fill_template(LIB_TEMPLATE, year, 0),
+    )?;
+    fs::write(
+        crate_dir.join("src/main.rs"),
+        fill_template(MAIN_TEMPLATE, year, 0),
+    )?;
+    for module in SHARED_MODULES {
+        let from = root.join(latest_year.to_string()).join("src").join(module);
+        fs::copy(from, crate_dir.join("src").join(module))?;
+    }
+
+    let manifest = root.join("Cargo.toml");
+    fs::write(&manifest, register_year_manifest(&read(&manifest)?, year))?;
+    let main = root.join("src/main.rs");
+    fs::write(&main, register_year_main(&read(&main)?, year))?;
+    println!("created crate {year}");
+    Ok(())
+}
+
+/// Creates `dayN.rs` from the template and registers it in the year's runner,
+/// bootstrapping the year crate first if it does not exist
+pub fn new_day(root: &Path, year: u16, day: u8) -> std::io::Result<()> {
+    if !(1..=25).contains(&day) {
+        return Err(Error::new(ErrorKind::InvalidInput, "day must be 1-25"));
+    }
+    let crate_dir = root.join(year.to_string());
+    if !crate_dir.exists() {
+        new_year(root, year)?;
+    }
+    let day_file = crate_dir.join(format!("src/day{day}.rs"));
+    if day_file.exists() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} already exists", day_file.display()),
+        ));
+    }
+    fs::write(&day_file, fill_template(DAY_TEMPLATE, year, day))?;
+
+    let lib = crate_dir.join("src/lib.rs");
+    fs::write(&lib, register_day(&read(&lib)?, day))?;
+    let manifest = crate_dir.join("Cargo.toml");
+    fs::write(&manifest, register_day_features(&read(&manifest)?, day))?;
+    println!("created {}", day_file.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB: &str = "#[cfg(feature = \"day1\")]
+pub mod day1;
+#[cfg(feature = \"day14\")]
+pub mod day14;
+// mod day15;
+#[cfg(feature = \"day2\")]
+pub mod day2;
+pub mod grid;
+
+pub fn time_all_days_2023(input_folder: &str, timeout: Duration) -> Report {
+    let reports: &[Report] = &[
+        #[cfg(feature = \"day1\")]
+        run_day!(timeout, input_folder, \"/1.txt\" => day1: part1, part2),
+        #[cfg(feature = \"day2\")]
+        run_day!(timeout, input_folder, \"/2.txt\" => day2: part1, part2),
+        #[cfg(feature = \"day14\")]
+        run_day!(timeout, input_folder, \"/14.txt\" => day14: part1, part2),
+        // run_day!(timeout, input_folder, \"/15.txt\" => day15: part1, part2),
+    ];
+    let total: Report = reports.iter().copied().sum();
+";
+
+    #[test]
+    fn test_register_commented_day() {
+        let lib = register_day(LIB, 15);
+        assert!(lib.contains(
+            "\n#[cfg(feature = \"day15\")]\npub mod day15;\n#[cfg(feature = \"day2\")]\n"
+        ));
+        assert!(lib.contains(
+            "\n        #[cfg(feature = \"day15\")]
+        run_day!(timeout, input_folder, \"/15.txt\" => day15: part1, part2),\n"
+        ));
+        assert!(!lib.contains("// "));
+    }
+
+    #[test]
+    fn test_register_missing_day() {
+        let lib = register_day(LIB, 3);
+        assert!(
+            lib.contains("pub mod day2;\n#[cfg(feature = \"day3\")]\npub mod day3;\npub mod grid;")
+        );
+        assert!(lib.contains(
+            "day2: part1, part2),
+        #[cfg(feature = \"day3\")]
+        run_day!(timeout, input_folder, \"/3.txt\" => day3: part1, part2),
+        #[cfg(feature = \"day14\")]
+        run_day!(timeout, input_folder, \"/14.txt\""
+        ));
+        assert_eq!(register_day(&lib, 3), lib);
+    }
+
+    #[test]
+    fn test_register_day_features() {
+        let manifest = "[features]
+default = [\"all\"]
+all = []
+
+[dev-dependencies]
+";
+        let manifest = register_day_features(manifest, 10);
+        let manifest = register_day_features(&manifest, 2);
+        let manifest = register_day_features(&manifest, 3);
+        assert!(manifest.contains(
+            "all = [\"day2\", \"day3\", \"day10\"]\nday2 = []\nday3 = []\nday10 = []\n\n[dev"
+        ));
+        assert_eq!(register_day_features(&manifest, 3), manifest);
+    }
+
+    #[test]
+    fn test_register_year() {
+        let manifest = "[dependencies]
+adventofrust2022 = { path = \"2022\", default-features = false, optional = true }
+adventofrust2023 = { path = \"2023\", default-features = false, optional = true }
+ureq = \"2.12.1\"
+
+[features]
+default = [\"2022\", \"2023\"]
+2022 = [\"adventofrust2022/all\"]
+2023 = [\"adventofrust2023/all\"]
+alloc-stats = [\"adventofrust2022?/alloc-stats\", \"adventofrust2023?/alloc-stats\"]
+
+[workspace]
+members = [ \"2022\", \"2023\", \"alloc-stats\" ]
+";
+        let manifest = register_year_manifest(manifest, 2024);
+        assert!(manifest.contains(
+            "optional = true }\nadventofrust2024 = { path = \"2024\", default-features = false, optional = true }\nureq"
+        ));
+        assert!(manifest.contains("default = [\"2022\", \"2023\", \"2024\"]"));
+        assert!(manifest
+            .contains("2023 = [\"adventofrust2023/all\"]\n2024 = [\"adventofrust2024/all\"]\n"));
+        assert!(manifest.contains(
+            "alloc-stats = [\"adventofrust2022?/alloc-stats\", \"adventofrust2023?/alloc-stats\", \"adventofrust2024?/alloc-stats\"]"
+        ));
+        assert!(manifest.contains("members = [ \"2022\", \"2023\", \"2024\", \"alloc-stats\" ]"));
+        assert_eq!(register_year_manifest(&manifest, 2024), manifest);
+
+        let main = "const YEARS: &[Year] = &[
+    #[cfg(feature = \"adventofrust2023\")]
+    year!(2023, adventofrust2023::time_all_days_2023, scaling),
+];
+";
+        let main = register_year_main(main, 2024);
+        assert!(main.contains(
+            "year!(2023, adventofrust2023::time_all_days_2023, scaling),
+    #[cfg(feature = \"adventofrust2024\")]
+    year!(2024, adventofrust2024::time_all_days_2024),
+];\n"
+        ));
+        assert_eq!(register_year_main(&main, 2024), main);
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use harness::scaling::Series;
+
+use crate::YEARS;
+
+/// A part is not run on larger inputs once it took longer than this
+const BUDGET: Duration = Duration::from_secs(1);
+
+/// Width of the longest bar in the plot
+const PLOT_WIDTH: usize = 50;
+
+/// A year's `scaling::scale_day`
+pub type ScaleDay = fn(u8, u64, Duration) -> Option<(&'static str, Vec<Series>)>;
+
+/// Times every part of a day on generated inputs of growing size, or `None` if
+/// the day has no generator
+pub fn scale_day(year: u16, day: u8, seed: u64) -> Option<(&'static str, Vec<Series>)> {
+    let scale_day = YEARS.iter().find(|y| y.year == year)?.scale_day?;
+    scale_day(day, seed, BUDGET)
+}
+
+/// How fast the time grows with the size between two runs, as the `k` of
+/// O(n^k)
+fn growth((size0, time0): (usize, Duration), (size1, time1): (usize, Duration)) -> f64 {
+    let time_ratio = time1.as_secs_f64() / time0.as_secs_f64().max(1e-9);
+    time_ratio.ln() / (size1 as f64 / size0 as f64).ln()
+}
+
+/// Plots the time against the input size as a bar per size, on a log scale
+/// from the fastest to the slowest run, followed by the growth since the
+/// previous size
+pub fn plot(unit: &str, series: &Series) -> String {
+    let log_times: Vec<f64> = series
+        .times
+        .iter()
+        .map(|(_, time)| time.as_secs_f64().max(1e-9).log10())
+        .collect();
+    let fastest = log_times.iter().copied().fold(f64::INFINITY, f64::min);
+    let slowest = log_times.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let mut plot = format!("{} ({unit})\n", series.part);
+    for (i, &(size, time)) in series.times.iter().enumerate() {
+        let width = if slowest > fastest {
+            1 + ((log_times[i] - fastest) / (slowest - fastest) * (PLOT_WIDTH - 1) as f64).round()
+                as usize
+        } else {
+            1
+        };
+        let growth = match i.checked_sub(1) {
+            Some(previous) => format!("n^{:.1}", growth(series.times[previous], (size, time))),
+            None => String::new(),
+        };
+        plot += &format!(
+            "{size:>9} {:>10.3}ms |{:<PLOT_WIDTH$}| {growth}\n",
+            time.as_secs_f64() * 1000.0,
+            "#".repeat(width),
+        );
+    }
+    plot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quadratic_growth() {
+        let growth = growth(
+            (100, Duration::from_millis(3)),
+            (200, Duration::from_millis(12)),
+        );
+        assert!((growth - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn plot_bars() {
+        let series = Series {
+            part: "day1_1",
+            times: vec![
+                (10, Duration::from_millis(1)),
+                (20, Duration::from_millis(10)),
+                (40, Duration::from_millis(100)),
+            ],
+        };
+        let plot = plot("lines", &series);
+        let lines: Vec<&str> = plot.lines().collect();
+        assert_eq!(lines[0], "day1_1 (lines)");
+        assert!(lines[1].ends_with(&format!("|#{}| ", " ".repeat(49))));
+        assert!(lines[2].contains(&format!("|{}{}| n^3.3", "#".repeat(26), " ".repeat(24))));
+        assert!(lines[3].contains(&format!("|{}| n^3.3", "#".repeat(50))));
+    }
+}
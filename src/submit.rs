@@ -0,0 +1,582 @@
+use std::{
+    fmt::Display,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const USER_AGENT: &str = "github.com/Civileiro/advent-of-rust";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    Incorrect,
+    /// The answer was not checked because one was given too recently
+    Wait,
+    /// The part was already solved or is not unlocked yet
+    WrongLevel,
+}
+
+impl Verdict {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Correct => "correct",
+            Verdict::TooHigh => "high",
+            Verdict::TooLow => "low",
+            Verdict::Incorrect => "wrong",
+            Verdict::Wait => "wait",
+            Verdict::WrongLevel => "level",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "correct" => Some(Verdict::Correct),
+            "high" => Some(Verdict::TooHigh),
+            "low" => Some(Verdict::TooLow),
+            "wrong" => Some(Verdict::Incorrect),
+            "wait" => Some(Verdict::Wait),
+            "level" => Some(Verdict::WrongLevel),
+            _ => None,
+        }
+    }
+
+    fn is_wrong(&self) -> bool {
+        matches!(
+            self,
+            Verdict::TooHigh | Verdict::TooLow | Verdict::Incorrect
+        )
+    }
+}
+
+/// What the website said about a submitted answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Response {
+    pub verdict: Verdict,
+    /// How long until the next answer is accepted
+    pub wait: Duration,
+}
+
+impl Response {
+    pub fn parse(html: &str) -> Option<Self> {
+        let article = html
+            .split_once("<article>")
+            .and_then(|(_, rest)| rest.split_once("</article>"))
+            .map_or(html, |(article, _)| article);
+        let verdict = if article.contains("That's the right answer") {
+            Verdict::Correct
+        } else if article.contains("That's not the right answer") {
+            if article.contains("too high") {
+                Verdict::TooHigh
+            } else if article.contains("too low") {
+                Verdict::TooLow
+            } else {
+                Verdict::Incorrect
+            }
+        } else if article.contains("You gave an answer too recently") {
+            Verdict::Wait
+        } else if article.contains("You don't seem to be solving the right level") {
+            Verdict::WrongLevel
+        } else {
+            return None;
+        };
+        let wait = parse_wait(article).unwrap_or_default();
+        Some(Self { verdict, wait })
+    }
+}
+
+/// Parses either "please wait one minute" / "wait 5 minutes" or "you have 1m 30s left to wait"
+fn parse_wait(text: &str) -> Option<Duration> {
+    if let Some((before, _)) = text.split_once(" left to wait") {
+        let words = before.rsplit_once("have ")?.1;
+        let mut secs = 0;
+        for word in words.split_whitespace() {
+            let (n, unit) = word.split_at(word.find(|c: char| !c.is_ascii_digit())?);
+            let n: u64 = n.parse().ok()?;
+            secs += match unit {
+                "h" => n * 3600,
+                "m" => n * 60,
+                "s" => n,
+                _ => return None,
+            };
+        }
+        return Some(Duration::from_secs(secs));
+    }
+    let (_, after) = text.split_once("wait ")?;
+    let mut words = after.split_whitespace();
+    let n = match words.next()? {
+        "one" => 1,
+        n => n.parse().ok()?,
+    };
+    match words.next()? {
+        "minute" | "minutes" => Some(Duration::from_secs(n * 60)),
+        "second" | "seconds" => Some(Duration::from_secs(n)),
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    /// The part was already solved with this answer
+    AlreadySolved(String),
+    /// This exact answer was already rejected
+    KnownWrong(Verdict),
+    /// A previous answer was already too high and this one is not lower
+    KnownTooHigh(String),
+    /// A previous answer was already too low and this one is not higher
+    KnownTooLow(String),
+    Throttled(Duration),
+    UnrecognizedResponse(String),
+    MissingSession,
+    Http(String),
+    Io(std::io::Error),
+}
+
+impl Display for SubmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmitError::AlreadySolved(answer) => write!(f, "already solved with {answer}"),
+            SubmitError::KnownWrong(verdict) => {
+                write!(f, "answer was already rejected ({})", verdict.as_str())
+            }
+            SubmitError::KnownTooHigh(answer) => write!(f, "{answer} was already too high"),
+            SubmitError::KnownTooLow(answer) => write!(f, "{answer} was already too low"),
+            SubmitError::Throttled(wait) => write!(f, "wait {}s before submitting", wait.as_secs()),
+            SubmitError::UnrecognizedResponse(body) => write!(f, "unrecognized response: {body}"),
+            SubmitError::MissingSession => write!(f, "AOC_SESSION is not set"),
+            SubmitError::Http(e) => write!(f, "http error: {e}"),
+            SubmitError::Io(e) => write!(f, "history error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+impl From<std::io::Error> for SubmitError {
+    fn from(e: std::io::Error) -> Self {
+        SubmitError::Io(e)
+    }
+}
+
+/// Posts answers to the website and returns the response page
+pub trait AnswerClient {
+    fn post_answer(&self, puzzle: Puzzle, answer: &str) -> Result<String, SubmitError>;
+}
+
+pub struct HttpClient {
+    session: String,
+}
+
+impl HttpClient {
+    pub fn new(session: String) -> Self {
+        Self { session }
+    }
+
+    pub fn from_env() -> Result<Self, SubmitError> {
+        let session = std::env::var("AOC_SESSION").map_err(|_| SubmitError::MissingSession)?;
+        Ok(Self::new(session))
+    }
+}
+
+impl AnswerClient for HttpClient {
+    fn post_answer(&self, puzzle: Puzzle, answer: &str) -> Result<String, SubmitError> {
+        let url = format!(
+            "https://adventofcode.com/{}/day/{}/answer",
+            puzzle.year, puzzle.day
+        );
+        ureq::post(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .set("User-Agent", USER_AGENT)
+            .send_form(&[("level", &puzzle.part.to_string()), ("answer", answer)])
+            .map_err(|e| SubmitError::Http(e.to_string()))?
+            .into_string()
+            .map_err(|e| SubmitError::Http(e.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Puzzle {
+    pub year: u16,
+    pub day: u8,
+    pub part: u8,
+}
+
+impl Display for Puzzle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} day {} part {}", self.year, self.day, self.part)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    /// Seconds since the unix epoch
+    pub timestamp: u64,
+    pub puzzle: Puzzle,
+    pub answer: String,
+    pub response: Response,
+}
+
+impl Attempt {
+    fn to_line(&self) -> String {
+        let Puzzle { year, day, part } = self.puzzle;
+        format!(
+            "{}\t{year}\t{day}\t{part}\t{}\t{}\t{}",
+            self.timestamp,
+            self.response.verdict.as_str(),
+            self.response.wait.as_secs(),
+            self.answer
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(7, '\t');
+        let timestamp = fields.next()?.parse().ok()?;
+        let year = fields.next()?.parse().ok()?;
+        let day = fields.next()?.parse().ok()?;
+        let part = fields.next()?.parse().ok()?;
+        let verdict = Verdict::from_str(fields.next()?)?;
+        let wait = Duration::from_secs(fields.next()?.parse().ok()?);
+        let answer = fields.next()?.to_owned();
+        Some(Self {
+            timestamp,
+            puzzle: Puzzle { year, day, part },
+            answer,
+            response: Response { verdict, wait },
+        })
+    }
+}
+
+/// The verdict of an answer that wasn't submitted through this command, like
+/// the ones written down before the history was kept. Notes have no time, so
+/// they never hold back the next answer, they only tell which ones to refuse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    pub puzzle: Puzzle,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+impl Note {
+    /// Notes start with `note` where attempts have their timestamp
+    const TAG: &'static str = "note";
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(6, '\t');
+        if fields.next()? != Self::TAG {
+            return None;
+        }
+        let year = fields.next()?.parse().ok()?;
+        let day = fields.next()?.parse().ok()?;
+        let part = fields.next()?.parse().ok()?;
+        let verdict = Verdict::from_str(fields.next()?)?;
+        let answer = fields.next()?.to_owned();
+        Some(Self {
+            puzzle: Puzzle { year, day, part },
+            answer,
+            verdict,
+        })
+    }
+}
+
+/// Every answer submitted so far, and the noted ones, optionally backed by a
+/// tab separated file
+#[derive(Debug, Default)]
+pub struct History {
+    path: Option<PathBuf>,
+    attempts: Vec<Attempt>,
+    notes: Vec<Note>,
+}
+
+impl History {
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let mut history = Self {
+            path: Some(path.to_owned()),
+            ..Self::default()
+        };
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(history),
+            Err(e) => return Err(e),
+        };
+        for line in content
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            if let Some(note) = Note::from_line(line) {
+                history.notes.push(note);
+            } else if let Some(attempt) = Attempt::from_line(line) {
+                history.attempts.push(attempt);
+            } else {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("bad history line: {line:?}"),
+                ));
+            }
+        }
+        Ok(history)
+    }
+
+    pub fn record(&mut self, attempt: Attempt) -> std::io::Result<()> {
+        if let Some(path) = &self.path {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", attempt.to_line())?;
+        }
+        self.attempts.push(attempt);
+        Ok(())
+    }
+
+    /// Every answer to the puzzle we know the verdict of, submitted or noted
+    fn verdicts(&self, puzzle: Puzzle) -> impl Iterator<Item = (&str, Verdict)> {
+        let attempts = self.attempts.iter().filter(move |a| a.puzzle == puzzle);
+        let notes = self.notes.iter().filter(move |n| n.puzzle == puzzle);
+        attempts
+            .map(|a| (a.answer.as_str(), a.response.verdict))
+            .chain(notes.map(|n| (n.answer.as_str(), n.verdict)))
+    }
+
+    /// The correct answer for a puzzle, if it was found already
+    pub fn solution(&self, puzzle: Puzzle) -> Option<&str> {
+        self.verdicts(puzzle)
+            .find(|&(_, verdict)| verdict == Verdict::Correct)
+            .map(|(answer, _)| answer)
+    }
+
+    /// When the website takes answers to the puzzle again. It only makes us
+    /// wait before answering the same puzzle, so answers to other days and
+    /// parts don't count
+    pub fn ready_at(&self, puzzle: Puzzle) -> u64 {
+        self.attempts
+            .iter()
+            .filter(|a| a.puzzle == puzzle)
+            .map(|a| a.timestamp + a.response.wait.as_secs())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Checks the answer against everything we already know about the puzzle
+    pub fn check(&self, puzzle: Puzzle, answer: &str, now: u64) -> Result<(), SubmitError> {
+        if let Some(solution) = self.solution(puzzle) {
+            return Err(SubmitError::AlreadySolved(solution.to_owned()));
+        }
+        let ready_at = self.ready_at(puzzle);
+        if ready_at > now {
+            return Err(SubmitError::Throttled(Duration::from_secs(ready_at - now)));
+        }
+        let number = answer.parse::<i64>().ok();
+        for (known, verdict) in self.verdicts(puzzle) {
+            if known == answer && verdict.is_wrong() {
+                return Err(SubmitError::KnownWrong(verdict));
+            }
+            let (Some(number), Ok(previous)) = (number, known.parse::<i64>()) else {
+                continue;
+            };
+            match verdict {
+                Verdict::TooHigh if number >= previous => {
+                    return Err(SubmitError::KnownTooHigh(known.to_owned()))
+                }
+                Verdict::TooLow if number <= previous => {
+                    return Err(SubmitError::KnownTooLow(known.to_owned()))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Submits an answer unless the history already tells us what the response
+/// would be. The answer is posted and recorded as is, callers trim it
+pub fn submit(
+    client: &impl AnswerClient,
+    history: &mut History,
+    puzzle: Puzzle,
+    answer: &str,
+    now: u64,
+) -> Result<Response, SubmitError> {
+    history.check(puzzle, answer, now)?;
+    let body = client.post_answer(puzzle, answer)?;
+    let response =
+        Response::parse(&body).ok_or_else(|| SubmitError::UnrecognizedResponse(body.clone()))?;
+    history.record(Attempt {
+        timestamp: now,
+        puzzle,
+        answer: answer.to_owned(),
+        response,
+    })?;
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    const PUZZLE: Puzzle = Puzzle {
+        year: 2022,
+        day: 22,
+        part: 2,
+    };
+
+    struct MockClient {
+        responses: RefCell<Vec<&'static str>>,
+        posted: RefCell<Vec<String>>,
+    }
+
+    impl MockClient {
+        fn new(mut responses: Vec<&'static str>) -> Self {
+            responses.reverse();
+            Self {
+                responses: RefCell::new(responses),
+                posted: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl AnswerClient for MockClient {
+        fn post_answer(&self, _puzzle: Puzzle, answer: &str) -> Result<String, SubmitError> {
+            self.posted.borrow_mut().push(answer.to_owned());
+            Ok(self.responses.borrow_mut().pop().unwrap().to_owned())
+        }
+    }
+
+    const CORRECT: &str = "<main><article><p>That's the right answer! You are one gold star closer.</p></article></main>";
+    const TOO_HIGH: &str = "<main><article><p>That's not the right answer; your answer is too high. Please wait one minute before trying again.</p></article></main>";
+    const TOO_LOW: &str = "<main><article><p>That's not the right answer; your answer is too low. Please wait 5 minutes before trying again.</p></article></main>";
+    const TOO_RECENT: &str = "<main><article><p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 30s left to wait.</p></article></main>";
+
+    #[test]
+    fn test_parse_responses() {
+        let parsed = Response::parse(CORRECT).unwrap();
+        assert_eq!(parsed.verdict, Verdict::Correct);
+        let parsed = Response::parse(TOO_HIGH).unwrap();
+        assert_eq!(parsed.verdict, Verdict::TooHigh);
+        assert_eq!(parsed.wait, Duration::from_secs(60));
+        let parsed = Response::parse(TOO_LOW).unwrap();
+        assert_eq!(parsed.verdict, Verdict::TooLow);
+        assert_eq!(parsed.wait, Duration::from_secs(300));
+        let parsed = Response::parse(TOO_RECENT).unwrap();
+        assert_eq!(parsed.verdict, Verdict::Wait);
+        assert_eq!(parsed.wait, Duration::from_secs(90));
+        assert_eq!(Response::parse("<article>huh</article>"), None);
+    }
+
+    #[test]
+    fn test_refuses_known_wrong() {
+        let client = MockClient::new(vec![TOO_HIGH, TOO_LOW, CORRECT]);
+        let mut history = History::default();
+
+        let res = submit(&client, &mut history, PUZZLE, "155222", 0).unwrap();
+        assert_eq!(res.verdict, Verdict::TooHigh);
+        assert!(matches!(
+            submit(&client, &mut history, PUZZLE, "155222", 1000),
+            Err(SubmitError::KnownWrong(Verdict::TooHigh))
+        ));
+        assert!(matches!(
+            submit(&client, &mut history, PUZZLE, "200000", 1000),
+            Err(SubmitError::KnownTooHigh(_))
+        ));
+        let res = submit(&client, &mut history, PUZZLE, "40362", 1000).unwrap();
+        assert_eq!(res.verdict, Verdict::TooLow);
+        assert!(matches!(
+            submit(&client, &mut history, PUZZLE, "100", 2000),
+            Err(SubmitError::KnownTooLow(_))
+        ));
+        let res = submit(&client, &mut history, PUZZLE, "122153", 2000).unwrap();
+        assert_eq!(res.verdict, Verdict::Correct);
+        assert!(matches!(
+            submit(&client, &mut history, PUZZLE, "1", 3000),
+            Err(SubmitError::AlreadySolved(_))
+        ));
+        assert_eq!(
+            client.posted.into_inner(),
+            vec!["155222", "40362", "122153"]
+        );
+    }
+
+    #[test]
+    fn test_throttle() {
+        let client = MockClient::new(vec![TOO_HIGH, TOO_RECENT]);
+        let mut history = History::default();
+
+        submit(&client, &mut history, PUZZLE, "155222", 100).unwrap();
+        assert!(matches!(
+            submit(&client, &mut history, PUZZLE, "1", 130),
+            Err(SubmitError::Throttled(wait)) if wait == Duration::from_secs(30)
+        ));
+        let res = submit(&client, &mut history, PUZZLE, "1", 160).unwrap();
+        assert_eq!(res.verdict, Verdict::Wait);
+        assert!(matches!(
+            submit(&client, &mut history, PUZZLE, "1", 200),
+            Err(SubmitError::Throttled(_))
+        ));
+    }
+
+    #[test]
+    fn test_throttle_per_puzzle() {
+        let client = MockClient::new(vec![TOO_HIGH, CORRECT]);
+        let mut history = History::default();
+
+        submit(&client, &mut history, PUZZLE, "155222", 100).unwrap();
+        let part1 = Puzzle { part: 1, ..PUZZLE };
+        let res = submit(&client, &mut history, part1, "6032", 110).unwrap();
+        assert_eq!(res.verdict, Verdict::Correct);
+        assert!(matches!(
+            submit(&client, &mut history, PUZZLE, "1", 120),
+            Err(SubmitError::Throttled(wait)) if wait == Duration::from_secs(40)
+        ));
+    }
+
+    #[test]
+    fn test_history_lines() {
+        let attempt = Attempt {
+            timestamp: 1671700000,
+            puzzle: PUZZLE,
+            answer: "64238".to_owned(),
+            response: Response {
+                verdict: Verdict::TooLow,
+                wait: Duration::from_secs(60),
+            },
+        };
+        assert_eq!(Attempt::from_line(&attempt.to_line()), Some(attempt));
+        let note = Note {
+            puzzle: PUZZLE,
+            answer: "64238".to_owned(),
+            verdict: Verdict::TooLow,
+        };
+        assert_eq!(Note::from_line("note\t2022\t22\t2\tlow\t64238"), Some(note));
+        assert_eq!(Attempt::from_line("note\t2022\t22\t2\tlow\t64238"), None);
+    }
+
+    #[test]
+    fn test_notes_are_not_attempts() {
+        let path = std::env::temp_dir().join(format!("submissions-{}.tsv", std::process::id()));
+        std::fs::write(
+            &path,
+            "# timestamp\tyear\tday\tpart\tverdict\twait\tanswer\n\
+             note\t2022\t22\t2\thigh\t155222\n\
+             1000\t2022\t22\t1\tlow\t300\t100\n",
+        )
+        .unwrap();
+        let history = History::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(history.ready_at(PUZZLE), 0);
+        assert_eq!(history.ready_at(Puzzle { part: 1, ..PUZZLE }), 1300);
+        assert!(matches!(
+            history.check(PUZZLE, "200000", 10),
+            Err(SubmitError::KnownTooHigh(_))
+        ));
+        assert_eq!(history.check(PUZZLE, "100000", 10).ok(), Some(()));
+    }
+}
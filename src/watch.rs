@@ -0,0 +1,254 @@
+use std::{
+    error::Error,
+    path::Path,
+    process::{Command, Output},
+    sync::mpsc,
+    time::Duration,
+};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+
+/// Changes closer than this to the first one are handled with it, editors
+/// often write a file in several steps
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// A line of the runner's output, like `[   181us] 2023  day5 part1: 340994526`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    /// The day and step, like `day5 part1`
+    pub name: String,
+    pub time: Duration,
+    /// Missing for the parse step, and can span several lines
+    pub answer: Option<String>,
+}
+
+fn parse_step(line: &str) -> Option<Step> {
+    let (time, mut rest) = line.strip_prefix('[')?.split_once("us] ")?;
+    let time = Duration::from_micros(time.trim().parse().ok()?);
+    // the columns after the time, like how an alternative compares to its part
+    while let Some(column) = rest.strip_prefix('[') {
+        (_, rest) = column.split_once("] ")?;
+    }
+    let (_year, rest) = rest.split_once(' ')?;
+    let (name, answer) = match rest.split_once(": ") {
+        Some((name, answer)) => (name, Some(answer.to_owned())),
+        None => (rest, None),
+    };
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some(Step { name, time, answer })
+}
+
+/// The steps in the output of a year's runner. Lines that are not a step
+/// continue the answer of the previous one
+pub fn parse_steps(output: &str) -> Vec<Step> {
+    let mut steps: Vec<Step> = Vec::new();
+    for line in output.lines() {
+        if let Some(step) = parse_step(line) {
+            steps.push(step);
+        } else if line.starts_with('[') {
+            // the totals
+            continue;
+        } else if let Some(answer) = steps.last_mut().and_then(|step| step.answer.as_mut()) {
+            answer.push('\n');
+            answer.push_str(line);
+        }
+    }
+    steps
+}
+
+/// Shows each step of the current run, with how its time and answer changed
+/// since the previous run
+pub fn compare(previous: &[Step], current: &[Step]) -> String {
+    let mut report = String::new();
+    for step in current {
+        report += &format!("[{:>6}us] {:>11}", step.time.as_micros(), step.name);
+        let before = previous.iter().find(|before| before.name == step.name);
+        if let Some(before) = before {
+            let (now, then) = (step.time.as_micros() as f64, before.time.as_micros() as f64);
+            report += &format!(" ({:+.0}us", now - then);
+            if then > 0.0 {
+                report += &format!(", {:+.1}%", (now - then) / then * 100.0);
+            }
+            report += ")";
+        }
+        if let Some(answer) = &step.answer {
+            report += &format!(": {answer}");
+            match before.and_then(|before| before.answer.as_ref()) {
+                Some(then) if then != answer => report += &format!(" (was {then})"),
+                _ => (),
+            }
+        }
+        report.push('\n');
+    }
+    report
+}
+
+fn cargo(year_dir: &Path, args: &[&str]) -> std::io::Result<Output> {
+    Command::new(env!("CARGO"))
+        .args(args)
+        .current_dir(year_dir)
+        .output()
+}
+
+/// Builds only the day, runs it and then its tests, and prints what changed
+/// since `previous`. Returns the steps of this run
+fn run_day(year_dir: &Path, day: u8, previous: &[Step]) -> std::io::Result<Vec<Step>> {
+    let feature = format!("day{day}");
+    let build = ["--no-default-features", "--features", &feature];
+
+    let run = cargo(
+        year_dir,
+        &[&["run", "--release", "-q"], &build[..]].concat(),
+    )?;
+    // it also fails when an answer is wrong, after printing every step
+    if !run.status.success() {
+        eprint!("{}", String::from_utf8_lossy(&run.stderr));
+    }
+    let steps = parse_steps(&String::from_utf8_lossy(&run.stdout));
+    if steps.is_empty() {
+        return Ok(previous.to_vec());
+    }
+    print!("{}", compare(previous, &steps));
+
+    let test = cargo(year_dir, &[&["test", "-q"], &build[..]].concat())?;
+    let test_output = String::from_utf8_lossy(&test.stdout);
+    if test.status.success() {
+        let passed = test_output
+            .lines()
+            .filter_map(|line| line.strip_prefix("test result: ok. "))
+            .filter_map(|line| line.split_once(" passed")?.0.parse::<usize>().ok())
+            .sum::<usize>();
+        println!("{passed} tests passed");
+    } else {
+        print!("{test_output}");
+        eprint!("{}", String::from_utf8_lossy(&test.stderr));
+    }
+    Ok(steps)
+}
+
+/// Whether `path` is one of the day's inputs or their answers: `N.txt`, or
+/// any `.txt` in the `N/` directory next to it, and their `.answers`
+fn is_input(path: &Path, inputs: &Path, day: u8) -> bool {
+    let is_input_file = path
+        .extension()
+        .is_some_and(|ext| ext == "txt" || ext == "answers");
+    let Some(dir) = path.parent() else {
+        return false;
+    };
+    is_input_file
+        && (dir == inputs.join(day.to_string())
+            || (dir == inputs && path.file_stem() == Some(day.to_string().as_ref())))
+}
+
+/// Runs the day every time its source or one of its inputs changes, until
+/// interrupted
+pub fn watch(root: &Path, year: u16, day: u8) -> Result<(), Box<dyn Error>> {
+    // events name the files by their absolute path
+    let year_dir = root.join(year.to_string()).canonicalize()?;
+    let source = year_dir.join(format!("src/day{day}.rs"));
+    let inputs = year_dir.join("inputs");
+    let day_inputs = inputs.join(day.to_string());
+    if !source.exists() {
+        return Err(format!("{} does not exist", source.display()).into());
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    // editors may replace the files instead of writing to them, which only
+    // the directories see
+    for dir in [year_dir.join("src"), inputs.clone(), day_inputs.clone()] {
+        if dir.exists() {
+            watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        }
+    }
+    let is_watched = |event: &notify::Event| {
+        matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            && event
+                .paths
+                .iter()
+                .any(|path| path == &source || is_input(path, &inputs, day))
+    };
+
+    println!(
+        "watching {} and the inputs of day {day} in {}",
+        source.display(),
+        inputs.display()
+    );
+    let mut steps = run_day(&year_dir, day, &[])?;
+    loop {
+        let event = receiver.recv()??;
+        if matches!(event.kind, EventKind::Create(_)) && event.paths.contains(&day_inputs) {
+            watcher.watch(&day_inputs, RecursiveMode::NonRecursive)?;
+        }
+        if !is_watched(&event) {
+            continue;
+        }
+        while receiver.recv_timeout(DEBOUNCE).is_ok() {}
+        println!("\n{year} day {day} changed");
+        steps = run_day(&year_dir, day, &steps)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUN: &str = "[   177us] 2022  day2 parse
+[    24us] 2022  day2 part1: 17189
+[    25us] 2022  day2 part2: 13490
+[     0ms] 2022 total
+";
+
+    #[test]
+    fn test_is_input() {
+        let inputs = Path::new("/aoc/2023/inputs");
+        assert!(is_input(&inputs.join("5.txt"), inputs, 5));
+        assert!(is_input(&inputs.join("5.answers"), inputs, 5));
+        assert!(is_input(&inputs.join("5/alice.txt"), inputs, 5));
+        assert!(is_input(&inputs.join("5/alice.answers"), inputs, 5));
+        assert!(!is_input(&inputs.join("15.txt"), inputs, 5));
+        assert!(!is_input(&inputs.join("6/alice.txt"), inputs, 5));
+        assert!(!is_input(&inputs.join("5/notes.md"), inputs, 5));
+    }
+
+    #[test]
+    fn test_parse_steps() {
+        let steps = parse_steps(RUN);
+        assert_eq!(
+            steps[0],
+            Step {
+                name: "day2 parse".to_owned(),
+                time: Duration::from_micros(177),
+                answer: None
+            }
+        );
+        assert_eq!(steps[2].answer.as_deref(), Some("13490"));
+        assert_eq!(steps.len(), 3);
+
+        let steps = parse_steps("[    13us] 2022 day10 part2: \n#..#\n.##.\n");
+        assert_eq!(steps[0].answer.as_deref(), Some("\n#..#\n.##."));
+
+        let steps = parse_steps("[   104us] [0.69x part1] 2023 day14 part1_rolling: 106990\n");
+        assert_eq!(steps[0].name, "day14 part1_rolling");
+        assert_eq!(steps[0].answer.as_deref(), Some("106990"));
+    }
+
+    #[test]
+    fn test_compare() {
+        let previous = parse_steps(RUN);
+        let current = parse_steps(
+            "[   177us] 2022  day2 parse
+[    12us] 2022  day2 part1: 17189
+[    25us] 2022  day2 part2: 13491
+",
+        );
+        assert_eq!(
+            compare(&previous, &current),
+            "[   177us]  day2 parse (+0us, +0.0%)
+[    12us]  day2 part1 (-12us, -50.0%): 17189
+[    25us]  day2 part2 (+0us, +0.0%): 13491 (was 13490)
+"
+        );
+        assert_eq!(compare(&[], &previous[..1]), "[   177us]  day2 parse\n");
+    }
+}